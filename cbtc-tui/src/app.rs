@@ -64,6 +64,8 @@ pub enum Command {
     CreateWithdrawAccount { btc_address: String },
     /// Submit a withdraw of `amount` CBTC from the given withdraw account.
     SubmitWithdraw { account_cid: String, amount: String },
+    /// Close (archive) a withdraw account with no withdrawal pending.
+    CloseWithdrawAccount { account_cid: String },
 }
 
 impl Command {
@@ -72,7 +74,9 @@ impl Command {
         match self {
             Command::Accept { cid } | Command::Reject { cid } | Command::Cancel { cid } => cid,
             Command::CancelExpired { cids } => cids.first().map(String::as_str).unwrap_or(""),
-            Command::SubmitWithdraw { account_cid, .. } => account_cid,
+            Command::SubmitWithdraw { account_cid, .. } | Command::CloseWithdrawAccount { account_cid } => {
+                account_cid
+            }
             Command::MergeHoldings
             | Command::CreateDepositAccount
             | Command::CreateWithdrawAccount { .. } => "",
@@ -90,6 +94,7 @@ impl Command {
             Command::CreateDepositAccount => "Create deposit account",
             Command::CreateWithdrawAccount { .. } => "Create withdraw account",
             Command::SubmitWithdraw { .. } => "Submit withdraw",
+            Command::CloseWithdrawAccount { .. } => "Close withdraw account",
         }
     }
 }
@@ -117,15 +122,18 @@ pub struct FormState {
     pub label: String,
     pub input: String,
     pub error: Option<String>,
+    /// Limits of the account the form acts on, checked before confirming.
+    pub limits: Option<cbtc::mint_redeem::models::EffectiveLimits>,
 }
 
 impl FormState {
-    fn new(kind: FormKind) -> Self {
-        let label = match kind {
-            FormKind::CreateWithdrawAccount => "Destination BTC address".to_string(),
-            FormKind::SubmitWithdraw { .. } => "Amount (CBTC)".to_string(),
+    fn new(kind: FormKind, limits: Option<cbtc::mint_redeem::models::EffectiveLimits>) -> Self {
+        let label = match (&kind, limits) {
+            (FormKind::CreateWithdrawAccount, _) => "Destination BTC address".to_string(),
+            (FormKind::SubmitWithdraw { .. }, Some(limits)) => format!("Amount (CBTC, {limits})"),
+            (FormKind::SubmitWithdraw { .. }, None) => "Amount (CBTC)".to_string(),
         };
-        FormState { kind, label, input: String::new(), error: None }
+        FormState { kind, label, input: String::new(), error: None, limits }
     }
 }
 
@@ -396,7 +404,11 @@ impl App {
                         self.screen = Screen::Confirm;
                     }
                     PendingAction::Form(kind) => {
-                        self.form = Some(FormState::new(kind));
+                        let limits = match &kind {
+                            FormKind::SubmitWithdraw { .. } => self.selected_row_limits(),
+                            FormKind::CreateWithdrawAccount => None,
+                        };
+                        self.form = Some(FormState::new(kind, limits));
                         self.screen = Screen::Form;
                     }
                 }
@@ -452,6 +464,8 @@ impl App {
                 if input.is_empty() {
                     return Err("Destination BTC address is required".to_string());
                 }
+                let network = self.bitcoin_network()?;
+                cbtc::mint_redeem::bitcoin_address::validate(input, network)?;
                 Ok(Command::CreateWithdrawAccount { btc_address: input.to_string() })
             }
             FormKind::SubmitWithdraw { account_cid } => {
@@ -459,6 +473,9 @@ impl App {
                 if amount <= cbtc::DamlDecimal::ZERO {
                     return Err("Amount must be greater than zero".to_string());
                 }
+                if let Some(limits) = &form.limits {
+                    limits.check("Withdraw", amount)?;
+                }
                 Ok(Command::SubmitWithdraw {
                     account_cid: account_cid.clone(),
                     amount: input.to_string(),
@@ -564,6 +581,14 @@ impl App {
         }
     }
 
+    /// Amount limits of the account in the selected results row, if known.
+    fn selected_row_limits(&self) -> Option<cbtc::mint_redeem::models::EffectiveLimits> {
+        match &self.result {
+            Some(OpResult::Table { rows, .. }) => rows.get(self.result_selected).and_then(|r| r.limits),
+            _ => None,
+        }
+    }
+
     /// Open the detail view for the selected results row, if it has a payload.
     fn open_detail(&mut self) {
         let detail = match &self.result {
@@ -640,7 +665,11 @@ impl App {
                 if let Some(cid) = row_cid {
                     items.push((
                         "Submit withdraw".to_string(),
-                        PendingAction::Form(FormKind::SubmitWithdraw { account_cid: cid }),
+                        PendingAction::Form(FormKind::SubmitWithdraw { account_cid: cid.clone() }),
+                    ));
+                    items.push((
+                        "Close withdraw account".to_string(),
+                        PendingAction::Command(Command::CloseWithdrawAccount { account_cid: cid }),
                     ));
                 }
                 items.push((
@@ -691,6 +720,22 @@ impl App {
             .map(|p| p.environment == "mainnet")
             .unwrap_or(false)
     }
+
+    /// Bitcoin network withdrawals pay out on: the active environment's
+    /// `bitcoin_network`, else inferred from its Bitsafe API URL.
+    pub fn bitcoin_network(
+        &self,
+    ) -> Result<cbtc::mint_redeem::bitcoin_address::BitcoinNetwork, String> {
+        let env = self
+            .active_profile
+            .and_then(|i| self.config.profiles.get(i))
+            .and_then(|profile| self.config.resolved_environment(&profile.environment))
+            .ok_or("No active environment")?;
+        cbtc::mint_redeem::bitcoin_address::BitcoinNetwork::resolve(
+            env.bitcoin_network.as_deref(),
+            &env.bitsafe_api_url,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -1076,20 +1121,62 @@ mod tests {
         assert!(app.action_items[0].0.contains("Create withdraw account"));
         app.update(Event::Key(KeyKind::Enter)); // → Form
         assert_eq!(app.screen, Screen::Form);
-        // bech32 address contains 'q','p','a','r' — must type, not trigger shortcuts.
-        type_str(&mut app, "bc1qpar");
-        assert_eq!(app.form.as_ref().unwrap().input, "bc1qpar");
+        // bech32 address contains 'q','a','r' — must type, not trigger shortcuts.
+        // The profile is on devnet, so the address must be a regtest one.
+        let address = "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080";
+        type_str(&mut app, address);
+        assert_eq!(app.form.as_ref().unwrap().input, address);
         app.update(Event::Key(KeyKind::Enter)); // validate → Confirm
         assert_eq!(app.screen, Screen::Confirm);
         let effects = app.update(Event::Key(KeyKind::Enter));
         assert_eq!(
             effects,
             vec![Effect::RunCommand(Command::CreateWithdrawAccount {
-                btc_address: "bc1qpar".into()
+                btc_address: address.into()
             })]
         );
     }
 
+    #[test]
+    fn create_withdraw_account_form_rejects_invalid_address() {
+        let mut app = logged_in();
+        app.selected_op = 4; // WithdrawAccounts
+        app.result = Some(empty_table());
+        app.focus = Focus::Results;
+        app.update(Event::Key(KeyKind::Char('a')));
+        app.update(Event::Key(KeyKind::Enter)); // → Form
+        // A mainnet address on a devnet (regtest) profile.
+        type_str(&mut app, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+        app.update(Event::Key(KeyKind::Enter));
+        assert_eq!(app.screen, Screen::Form);
+        assert_eq!(
+            app.form.as_ref().unwrap().error.as_deref(),
+            Some("Address is for the 'bc' network, expected regtest (bcrt)")
+        );
+    }
+
+    #[test]
+    fn create_withdraw_account_form_uses_configured_network() {
+        let mut app = logged_in();
+        // A devnet override behind a proxy URL, with the network set explicitly.
+        app.config.environments.insert(
+            "devnet".into(),
+            crate::config::Environment {
+                bitsafe_api_url: "https://bitsafe-proxy.internal".into(),
+                bitcoin_network: Some("testnet".into()),
+                ..Default::default()
+            },
+        );
+        app.selected_op = 4; // WithdrawAccounts
+        app.result = Some(empty_table());
+        app.focus = Focus::Results;
+        app.update(Event::Key(KeyKind::Char('a')));
+        app.update(Event::Key(KeyKind::Enter)); // → Form
+        type_str(&mut app, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+        app.update(Event::Key(KeyKind::Enter));
+        assert_eq!(app.screen, Screen::Confirm);
+    }
+
     #[test]
     fn submit_withdraw_form_flow() {
         let mut app = logged_in();
@@ -1102,8 +1189,8 @@ mod tests {
         app.focus = Focus::Results;
         app.result_selected = 0;
         app.update(Event::Key(KeyKind::Char('a')));
-        // Row "Submit withdraw" + global "Create withdraw account".
-        assert_eq!(app.action_items.len(), 2);
+        // Row "Submit withdraw" and "Close withdraw account" + global "Create withdraw account".
+        assert_eq!(app.action_items.len(), 3);
         assert!(app.action_items[0].0.contains("Submit withdraw"));
         app.update(Event::Key(KeyKind::Enter)); // Submit withdraw → amount Form
         assert_eq!(app.screen, Screen::Form);
@@ -1120,6 +1207,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn close_withdraw_account_action() {
+        let mut app = logged_in();
+        app.selected_op = 4; // WithdrawAccounts
+        app.result = Some(OpResult::Table {
+            title: String::new(),
+            columns: vec!["Dest".into()],
+            rows: vec![crate::ops::ResultRow::new(vec!["bc1dest".into()], None).with_id("00wacct".into())],
+        });
+        app.focus = Focus::Results;
+        app.result_selected = 0;
+        app.update(Event::Key(KeyKind::Char('a')));
+        assert_eq!(app.action_items[1].0, "Close withdraw account");
+        app.update(Event::Key(KeyKind::Down));
+        app.update(Event::Key(KeyKind::Enter)); // → Confirm
+        assert_eq!(app.screen, Screen::Confirm);
+        let effects = app.update(Event::Key(KeyKind::Enter));
+        assert_eq!(
+            effects,
+            vec![Effect::RunCommand(Command::CloseWithdrawAccount { account_cid: "00wacct".into() })]
+        );
+    }
+
+    #[test]
+    fn submit_withdraw_form_enforces_account_limits() {
+        let mut app = logged_in();
+        app.selected_op = 4; // WithdrawAccounts
+        let limits = cbtc::mint_redeem::models::EffectiveLimits {
+            min_amount: Some(cbtc::DamlDecimal::parse("0.01").unwrap()),
+            max_amount: Some(cbtc::DamlDecimal::parse("1").unwrap()),
+        };
+        app.result = Some(OpResult::Table {
+            title: String::new(),
+            columns: vec!["Dest".into()],
+            rows: vec![
+                crate::ops::ResultRow::new(vec!["bc1dest".into()], None).with_id("00wacct".into()).with_limits(limits),
+            ],
+        });
+        app.focus = Focus::Results;
+        app.result_selected = 0;
+        app.update(Event::Key(KeyKind::Char('a')));
+        app.update(Event::Key(KeyKind::Enter)); // Submit withdraw → amount Form
+        assert_eq!(app.screen, Screen::Form);
+        assert!(app.form.as_ref().unwrap().label.contains(&limits.to_string()));
+        type_str(&mut app, "2");
+        app.update(Event::Key(KeyKind::Enter));
+        assert_eq!(app.screen, Screen::Form);
+        assert!(app.form.as_ref().unwrap().error.as_deref().unwrap().contains("exceeds maximum"));
+    }
+
     #[test]
     fn form_rejects_invalid_input() {
         let mut app = logged_in();
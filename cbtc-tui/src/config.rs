@@ -11,6 +11,10 @@ pub struct Environment {
     pub registry_url: String,
     pub decentralized_party_id: String,
     pub bitsafe_api_url: String,
+    /// Bitcoin network withdrawals pay out on (`mainnet`/`testnet`/`regtest`).
+    /// Inferred from `bitsafe_api_url` when unset.
+    #[serde(default)]
+    pub bitcoin_network: Option<String>,
 }
 
 /// A Canton user on a participant node. Secrets are plaintext; the file is 0600.
@@ -51,6 +55,7 @@ impl Config {
                     "cbtc-network::12202a83c6f4082217c175e29bc53da5f2703ba2675778ab99217a5a881a949203ff"
                         .to_string(),
                 bitsafe_api_url: "https://api.devnet.bitsafe.finance".to_string(),
+                bitcoin_network: Some("regtest".to_string()),
             },
         );
         m.insert(
@@ -61,6 +66,7 @@ impl Config {
                     "cbtc-network::12201b1741b63e2494e4214cf0bedc3d5a224da53b3bf4d76dba468f8e97eb15508f"
                         .to_string(),
                 bitsafe_api_url: "https://api.testnet.bitsafe.finance".to_string(),
+                bitcoin_network: Some("testnet".to_string()),
             },
         );
         m.insert(
@@ -71,6 +77,7 @@ impl Config {
                     "cbtc-network::12205af3b949a04776fc48cdcc05a060f6bda2e470632935f375d1049a8546a3b262"
                         .to_string(),
                 bitsafe_api_url: "https://api.mainnet.bitsafe.finance".to_string(),
+                bitcoin_network: Some("mainnet".to_string()),
             },
         );
         m
@@ -246,6 +253,7 @@ mod tests {
                 registry_url: "https://override".to_string(),
                 decentralized_party_id: "dp::1220".to_string(),
                 bitsafe_api_url: "https://api".to_string(),
+                bitcoin_network: None,
             },
         );
         // Act
@@ -48,6 +48,7 @@ pub fn import(content: &str, profile_name: &str) -> (Profile, Option<(String, En
     let override_env = if map.contains_key("REGISTRY_URL")
         || map.contains_key("DECENTRALIZED_PARTY_ID")
         || map.contains_key("BITSAFE_API_URL")
+        || map.contains_key("BITCOIN_NETWORK")
     {
         Some((
             env_name,
@@ -55,6 +56,7 @@ pub fn import(content: &str, profile_name: &str) -> (Profile, Option<(String, En
                 registry_url: get("REGISTRY_URL"),
                 decentralized_party_id: get("DECENTRALIZED_PARTY_ID"),
                 bitsafe_api_url: get("BITSAFE_API_URL"),
+                bitcoin_network: map.get("BITCOIN_NETWORK").cloned(),
             },
         ))
     } else {
@@ -80,6 +82,7 @@ KEYCLOAK_PASSWORD='sekret'
 REGISTRY_URL=https://reg.example
 DECENTRALIZED_PARTY_ID=cbtc-network::1220ab
 BITSAFE_API_URL=https://api.example
+BITCOIN_NETWORK=testnet
 "#;
 
     #[test]
@@ -107,5 +110,6 @@ BITSAFE_API_URL=https://api.example
         assert_eq!(env_name, "devnet");
         assert_eq!(ov.registry_url, "https://reg.example");
         assert_eq!(ov.bitsafe_api_url, "https://api.example");
+        assert_eq!(ov.bitcoin_network.as_deref(), Some("testnet"));
     }
 }
@@ -26,17 +26,19 @@ pub fn map_key(code: KeyCode) -> Option<KeyKind> {
 
 /// Spawn a blocking thread that reads terminal input and forwards mapped keys.
 pub fn spawn_input_reader(tx: UnboundedSender<Event>) {
-    std::thread::spawn(move || loop {
-        if event::poll(Duration::from_millis(200)).unwrap_or(false) {
-            if let Ok(CtEvent::Key(key)) = event::read()
-                && key.kind == KeyEventKind::Press
-                && let Some(k) = map_key(key.code)
-                && tx.send(Event::Key(k)).is_err()
-            {
+    std::thread::spawn(move || {
+        loop {
+            if event::poll(Duration::from_millis(200)).unwrap_or(false) {
+                if let Ok(CtEvent::Key(key)) = event::read()
+                    && key.kind == KeyEventKind::Press
+                    && let Some(k) = map_key(key.code)
+                    && tx.send(Event::Key(k)).is_err()
+                {
+                    break;
+                }
+            } else if tx.is_closed() {
                 break;
             }
-        } else if tx.is_closed() {
-            break;
         }
     });
 }
@@ -61,7 +63,9 @@ pub fn spawn_op(tx: UnboundedSender<Event>, op: Operation, ctx: OpContext) {
     });
     tokio::spawn(async move {
         if let Err(join_err) = handle.await {
-            let _ = panic_tx.send(Event::OpResult(Err(format!("operation panicked: {join_err}"))));
+            let _ = panic_tx.send(Event::OpResult(Err(format!(
+                "operation panicked: {join_err}"
+            ))));
         }
     });
 }
@@ -93,7 +97,9 @@ pub fn spawn_login(tx: UnboundedSender<Event>, profile: Profile) {
     });
     tokio::spawn(async move {
         if let Err(join_err) = handle.await {
-            let _ = panic_tx.send(Event::LoginResult(Err(format!("login panicked: {join_err}"))));
+            let _ = panic_tx.send(Event::LoginResult(Err(format!(
+                "login panicked: {join_err}"
+            ))));
         }
     });
 }
@@ -139,6 +145,7 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
             access_token: ctx.access_token.clone(),
             registry_url: ctx.registry_url.clone(),
             decentralized_party_id: ctx.decentralized_party_id.clone(),
+            reason: None,
         })
         .await
         .map(|()| "Rejected offer".to_string()),
@@ -208,14 +215,8 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
         }
         Command::CreateDepositAccount => {
             let rules =
-                cbtc::mint_redeem::attestor::get_account_contract_rules(&ctx.bitsafe_api_url).await?;
-            let credential_cids = minter_credential_cids(ctx).await?;
-            if credential_cids.is_empty() {
-                return Err(
-                    "No Minter credential found — accept a Minter credential offer first."
-                        .to_string(),
-                );
-            }
+                cbtc::mint_redeem::attestor::get_account_contract_rules(&ctx.bitsafe_api_url)
+                    .await?;
             cbtc::mint_redeem::mint::create_deposit_account(
                 cbtc::mint_redeem::mint::CreateDepositAccountParams {
                     ledger_host: ctx.ledger_host.clone(),
@@ -223,22 +224,21 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
                     user_name: ctx.user_name.clone(),
                     access_token: ctx.access_token.clone(),
                     account_rules: rules,
-                    credential_cids,
+                    credential_cids: vec![],
+                    decentralized_party_id: Some(ctx.decentralized_party_id.clone()),
                 },
             )
             .await
             .map(|_| "Created deposit account".to_string())
         }
         Command::CreateWithdrawAccount { btc_address } => {
+            let bitcoin_network = cbtc::mint_redeem::bitcoin_address::BitcoinNetwork::resolve(
+                ctx.bitcoin_network.as_deref(),
+                &ctx.bitsafe_api_url,
+            )?;
             let rules =
-                cbtc::mint_redeem::attestor::get_account_contract_rules(&ctx.bitsafe_api_url).await?;
-            let credential_cids = minter_credential_cids(ctx).await?;
-            if credential_cids.is_empty() {
-                return Err(
-                    "No Minter credential found — accept a Minter credential offer first."
-                        .to_string(),
-                );
-            }
+                cbtc::mint_redeem::attestor::get_account_contract_rules(&ctx.bitsafe_api_url)
+                    .await?;
             cbtc::mint_redeem::redeem::create_withdraw_account(
                 cbtc::mint_redeem::redeem::CreateWithdrawAccountParams {
                     ledger_host: ctx.ledger_host.clone(),
@@ -249,16 +249,20 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
                     account_rules_template_id: rules.wa_rules.template_id.clone(),
                     account_rules_created_event_blob: rules.wa_rules.created_event_blob.clone(),
                     destination_btc_address: btc_address.clone(),
-                    credential_cids,
+                    bitcoin_network,
+                    credential_cids: vec![],
+                    decentralized_party_id: Some(ctx.decentralized_party_id.clone()),
                 },
             )
             .await
             .map(|_| format!("Created withdraw account to {btc_address}"))
         }
-        Command::SubmitWithdraw { account_cid, amount } => {
+        Command::SubmitWithdraw {
+            account_cid,
+            amount,
+        } => {
             let amount_dec =
                 cbtc::DamlDecimal::parse(amount).map_err(|e| format!("invalid amount: {e}"))?;
-            let credential_cids = minter_credential_cids(ctx).await?;
             let holdings = cbtc::mint_redeem::redeem::list_holdings(
                 cbtc::mint_redeem::redeem::ListHoldingsParams {
                     ledger_host: ctx.ledger_host.clone(),
@@ -280,6 +284,17 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
             if holding_contract_ids.is_empty() {
                 return Err("No CBTC holdings available to withdraw".to_string());
             }
+            let account = cbtc::mint_redeem::redeem::list_withdraw_accounts(
+                cbtc::mint_redeem::redeem::ListWithdrawAccountsParams {
+                    ledger_host: ctx.ledger_host.clone(),
+                    party: ctx.party.clone(),
+                    access_token: ctx.access_token.clone(),
+                },
+            )
+            .await?
+            .into_iter()
+            .find(|a| &a.contract_id == account_cid)
+            .ok_or_else(|| format!("Withdraw account {account_cid} is no longer active"))?;
             cbtc::mint_redeem::redeem::submit_withdraw(
                 cbtc::mint_redeem::redeem::SubmitWithdrawParams {
                     ledger_host: ctx.ledger_host.clone(),
@@ -287,37 +302,31 @@ async fn run_command(command: &Command, ctx: &OpContext) -> Result<String, Strin
                     user_name: ctx.user_name.clone(),
                     access_token: ctx.access_token.clone(),
                     api_url: ctx.bitsafe_api_url.clone(),
-                    withdraw_account_contract_id: account_cid.clone(),
+                    account,
                     amount: amount_dec,
                     holding_contract_ids,
-                    credential_cids: Some(credential_cids),
+                    credential_cids: None,
                 },
             )
             .await
             .map(|_| format!("Submitted withdraw of {amount} CBTC"))
+            .map_err(String::from)
+        }
+        Command::CloseWithdrawAccount { account_cid } => {
+            cbtc::mint_redeem::redeem::close_withdraw_account(
+                cbtc::mint_redeem::redeem::CloseWithdrawAccountParams {
+                    ledger_host: ctx.ledger_host.clone(),
+                    party: ctx.party.clone(),
+                    access_token: ctx.access_token.clone(),
+                    withdraw_account_contract_id: account_cid.clone(),
+                },
+            )
+            .await
+            .map(|()| "Closed withdraw account".to_string())
         }
     }
 }
 
-/// Fetch the party's Minter credential contract ids (CBTC role).
-async fn minter_credential_cids(ctx: &OpContext) -> Result<Vec<String>, String> {
-    let credentials = cbtc::credentials::list_credentials(cbtc::credentials::ListCredentialsParams {
-        ledger_host: ctx.ledger_host.clone(),
-        party: ctx.party.clone(),
-        access_token: ctx.access_token.clone(),
-    })
-    .await?;
-    Ok(credentials
-        .iter()
-        .filter(|c| {
-            c.claims
-                .iter()
-                .any(|cl| cl.property == "hasCBTCRole" && cl.value == "Minter")
-        })
-        .map(|c| c.contract_id.clone())
-        .collect())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
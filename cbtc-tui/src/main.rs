@@ -244,6 +244,7 @@ fn build_context(app: &App) -> Option<OpContext> {
         party,
         access_token: token,
         bitsafe_api_url: env.bitsafe_api_url,
+        bitcoin_network: env.bitcoin_network,
         registry_url: env.registry_url,
         decentralized_party_id: env.decentralized_party_id,
         user_name: profile.keycloak_username.clone(),
@@ -38,11 +38,19 @@ pub struct ResultRow {
     pub detail: Option<String>,
     pub id: Option<String>,
     pub expired: bool,
+    /// Amount limits of the account this row shows, for validating forms.
+    pub limits: Option<cbtc::mint_redeem::models::EffectiveLimits>,
 }
 
 impl ResultRow {
     pub fn new(cells: Vec<String>, detail: Option<String>) -> Self {
-        Self { cells, detail, id: None, expired: false }
+        Self {
+            cells,
+            detail,
+            id: None,
+            expired: false,
+            limits: None,
+        }
     }
 
     pub fn with_id(mut self, id: String) -> Self {
@@ -54,6 +62,11 @@ impl ResultRow {
         self.expired = expired;
         self
     }
+
+    pub fn with_limits(mut self, limits: cbtc::mint_redeem::models::EffectiveLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
 }
 
 /// Normalized result shape the UI renders generically.
@@ -81,6 +94,7 @@ pub struct OpContext {
     pub party: String,
     pub access_token: String,
     pub bitsafe_api_url: String,
+    pub bitcoin_network: Option<String>,
     pub registry_url: String,
     pub decentralized_party_id: String,
     pub user_name: String,
@@ -137,7 +151,11 @@ pub fn balance_to_result(rows: &[(String, cbtc::DamlDecimal, Option<String>)]) -
         .collect();
     OpResult::Table {
         title: format!("Total CBTC: {total}  ({} UTXOs)", rows.len()),
-        columns: vec!["#".to_string(), "Amount".to_string(), "Contract".to_string()],
+        columns: vec![
+            "#".to_string(),
+            "Amount".to_string(),
+            "Contract".to_string(),
+        ],
         rows: table_rows,
     }
 }
@@ -256,25 +274,31 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
             // order — a user with many deposit accounts shouldn't wait on N serial
             // round-trips.
             let semaphore = Arc::new(Semaphore::new(8));
+            let client = cbtc::mint_redeem::attestor::BitsafeClient::new(&ctx.bitsafe_api_url);
             let mut set = JoinSet::new();
             for (i, a) in accounts.iter().enumerate() {
-                let api_url = ctx.bitsafe_api_url.clone();
+                let client = client.clone();
                 let account_id = a.account_id().to_string();
                 let semaphore = semaphore.clone();
                 set.spawn(async move {
                     let _permit = semaphore.acquire().await.ok();
-                    let addr = cbtc::mint_redeem::mint::get_bitcoin_address(
-                        cbtc::mint_redeem::mint::GetBitcoinAddressParams { api_url, account_id },
-                    )
-                    .await
-                    .unwrap_or_else(|e| format!("<error: {e}>"));
-                    (i, addr)
+                    let (addr, attestors) = match client.get_bitcoin_address(&account_id).await {
+                        Ok(r) => {
+                            let quorum =
+                                format!("{}/{}", r.attestors_responded, r.attestors_requested);
+                            (r.bitcoin_address, quorum)
+                        }
+                        Err(e) => (format!("<error: {e}>"), "-".to_string()),
+                    };
+                    (i, addr, attestors)
                 });
             }
             let mut addresses = vec![String::from("<error: task failed>"); accounts.len()];
+            let mut attestors = vec![String::from("-"); accounts.len()];
             while let Some(joined) = set.join_next().await {
-                if let Ok((i, addr)) = joined {
+                if let Ok((i, addr, quorum)) = joined {
                     addresses[i] = addr;
+                    attestors[i] = quorum;
                 }
             }
             let rows = accounts
@@ -282,14 +306,27 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
                 .enumerate()
                 .map(|(i, a)| {
                     ResultRow::new(
-                        vec![a.account_id().to_string(), addresses[i].clone(), short(&a.contract_id)],
+                        vec![
+                            a.account_id().to_string(),
+                            addresses[i].clone(),
+                            attestors[i].clone(),
+                            a.effective_limits().to_string(),
+                            short(&a.contract_id),
+                        ],
                         Some(format!("{a:#?}\n\nbitcoin_address: {}", addresses[i])),
                     )
+                    .with_limits(a.effective_limits())
                 })
                 .collect();
             Ok(OpResult::Table {
                 title: format!("Deposit Accounts ({})", accounts.len()),
-                columns: vec!["Account".into(), "BTC Address".into(), "Contract".into()],
+                columns: vec![
+                    "Account".into(),
+                    "BTC Address".into(),
+                    "Attestors".into(),
+                    "Limits".into(),
+                    "Contract".into(),
+                ],
                 rows,
             })
         }
@@ -316,11 +353,13 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
                             a.destination_btc_address.clone(),
                             a.pending_balance.to_string(),
                             status.to_string(),
+                            a.effective_limits().to_string(),
                             short(&a.contract_id),
                         ],
                         Some(format!("{a:#?}")),
                     )
                     .with_id(a.contract_id.clone())
+                    .with_limits(a.effective_limits())
                 })
                 .collect();
             Ok(OpResult::Table {
@@ -329,6 +368,7 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
                     "Destination".into(),
                     "Pending".into(),
                     "Status".into(),
+                    "Limits".into(),
                     "Contract".into(),
                 ],
                 rows,
@@ -417,15 +457,14 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
             })
         }
         Operation::Credentials => {
-            let creds = cbtc::credentials::list_credentials(
-                cbtc::credentials::ListCredentialsParams {
+            let creds =
+                cbtc::credentials::list_credentials(cbtc::credentials::ListCredentialsParams {
                     ledger_host: ctx.ledger_host.clone(),
                     party: ctx.party.clone(),
                     access_token: ctx.access_token.clone(),
-                },
-            )
-            .await
-            .map_err(AppError::Op)?;
+                })
+                .await
+                .map_err(AppError::Op)?;
             let rows = creds
                 .iter()
                 .map(|c| {
@@ -435,15 +474,27 @@ pub async fn run(op: Operation, ctx: &OpContext) -> Result<OpResult> {
                         .map(|cl| format!("{}={}", cl.property, cl.value))
                         .collect::<Vec<_>>()
                         .join(", ");
+                    let valid_until = match c.valid_until {
+                        Some(until) if !c.is_valid_at(chrono::Utc::now()) => {
+                            format!("{} (expired)", until.format("%Y-%m-%d %H:%M"))
+                        }
+                        Some(until) => until.format("%Y-%m-%d %H:%M").to_string(),
+                        None => "-".to_string(),
+                    };
                     ResultRow::new(
-                        vec![c.id.clone(), c.description.clone(), claims],
+                        vec![c.id.clone(), c.description.clone(), claims, valid_until],
                         Some(format!("{c:#?}")),
                     )
                 })
                 .collect();
             Ok(OpResult::Table {
                 title: format!("Credentials ({})", creds.len()),
-                columns: vec!["ID".into(), "Description".into(), "Claims".into()],
+                columns: vec![
+                    "ID".into(),
+                    "Description".into(),
+                    "Claims".into(),
+                    "Valid until".into(),
+                ],
                 rows,
             })
         }
@@ -471,14 +522,26 @@ mod tests {
     fn balance_formats_total_and_rows() {
         // Arrange
         let rows = vec![
-            ("00aabbccddeeff".to_string(), DamlDecimal::parse("0.5").unwrap(), None),
-            ("00112233445566".to_string(), DamlDecimal::parse("0.25").unwrap(), None),
+            (
+                "00aabbccddeeff".to_string(),
+                DamlDecimal::parse("0.5").unwrap(),
+                None,
+            ),
+            (
+                "00112233445566".to_string(),
+                DamlDecimal::parse("0.25").unwrap(),
+                None,
+            ),
         ];
         // Act
         let result = balance_to_result(&rows);
         // Assert
         match result {
-            OpResult::Table { title, columns, rows } => {
+            OpResult::Table {
+                title,
+                columns,
+                rows,
+            } => {
                 assert!(title.contains("0.75"));
                 assert_eq!(columns, vec!["#", "Amount", "Contract"]);
                 assert_eq!(rows.len(), 2);
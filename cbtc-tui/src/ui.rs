@@ -707,6 +707,7 @@ mod tests {
             label: "Destination BTC address".into(),
             input: "bc1qexample".into(),
             error: None,
+            limits: None,
         });
         let theme = Theme { truecolor: true };
         let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
@@ -27,6 +27,8 @@ async fn main() -> Result<(), String> {
             &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
             &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
         ),
+        filter: Default::default(),
+        batch_size: None,
     };
 
     cbtc::accept::accept_all(params).await?;
@@ -113,6 +113,7 @@ async fn main() -> Result<(), String> {
         keycloak_url,
         reference_base: Some(format!("batch-{}", chrono::Utc::now().timestamp())),
         on_transfer_complete: Some(callback),
+        preflight: None,
     })
     .await?;
 
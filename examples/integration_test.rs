@@ -26,13 +26,17 @@
 /// Optional:
 ///   TRANSFER_AMOUNT (default: "0.00001")
 ///   CONSOLIDATION_THRESHOLD (default: "10")
-///   DESTINATION_BTC_ADDRESS (default: test address)
+///   BITCOIN_NETWORK (mainnet/testnet/regtest; default: inferred from
+///     BITSAFE_API_URL, devnet pays out on regtest)
+///   DESTINATION_BTC_ADDRESS (default: a test address on BITCOIN_NETWORK;
+///     required on mainnet)
 ///   WITHDRAW_AMOUNT (default: TRANSFER_AMOUNT)
 ///   FAUCET_URL (if set, enables faucet deposit steps)
 ///   FAUCET_NETWORK (default: "devnet")
 ///
 /// Note: Deposit and withdraw accounts created during the test are persistent
 /// Canton contracts. No cleanup API exists; they remain after the test.
+use cbtc::mint_redeem::bitcoin_address::BitcoinNetwork;
 use std::env;
 use std::time::Instant;
 
@@ -199,10 +203,24 @@ async fn main() -> Result<(), String> {
         .expect("CONSOLIDATION_THRESHOLD must be a valid number");
 
     let bitsafe_api_url = env::var("BITSAFE_API_URL").expect("BITSAFE_API_URL must be set");
-    let destination_btc_address = env::var("DESTINATION_BTC_ADDRESS")
+    let bitcoin_network = BitcoinNetwork::resolve(
+        env::var("BITCOIN_NETWORK").ok().as_deref(),
+        &bitsafe_api_url,
+    )?;
+    let destination_btc_address = match env::var("DESTINATION_BTC_ADDRESS")
         .ok()
         .filter(|s| !s.is_empty())
-        .unwrap_or_else(|| "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string());
+    {
+        Some(address) => address,
+        // BIP 173 test vectors; never default to a mainnet payout address
+        None => match bitcoin_network {
+            BitcoinNetwork::Regtest => "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            BitcoinNetwork::Testnet => "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string(),
+            BitcoinNetwork::Mainnet => {
+                return Err("DESTINATION_BTC_ADDRESS must be set on mainnet".to_string());
+            }
+        },
+    };
     let withdraw_amount = env::var("WITHDRAW_AMOUNT").unwrap_or_else(|_| amount.to_string());
     let faucet_url = env::var("FAUCET_URL").ok();
     let faucet_network = env::var("FAUCET_NETWORK").unwrap_or_else(|_| "devnet".to_string());
@@ -391,6 +409,7 @@ async fn main() -> Result<(), String> {
                 access_token: token,
                 account_rules: rules.clone(),
                 credential_cids: minter_credential_cids.clone(),
+                decentralized_party_id: None,
             },
         )
         .await?;
@@ -431,7 +450,9 @@ async fn main() -> Result<(), String> {
                 account_rules_template_id: rules.wa_rules.template_id.clone(),
                 account_rules_created_event_blob: rules.wa_rules.created_event_blob.clone(),
                 destination_btc_address: destination_btc_address.clone(),
+                bitcoin_network,
                 credential_cids: minter_credential_cids.clone(),
+                decentralized_party_id: None,
             },
         )
         .await?;
@@ -531,6 +552,8 @@ async fn main() -> Result<(), String> {
                 keycloak_username: sender.keycloak_username.clone(),
                 keycloak_password: sender.keycloak_password.clone(),
                 keycloak_url: sender.keycloak_url.clone(),
+                filter: Default::default(),
+                batch_size: None,
             })
             .await?;
             if result.failed_count > 0 {
@@ -614,6 +637,8 @@ async fn main() -> Result<(), String> {
             keycloak_username: receiver.keycloak_username.clone(),
             keycloak_password: receiver.keycloak_password.clone(),
             keycloak_url: receiver.keycloak_url.clone(),
+            filter: Default::default(),
+            batch_size: None,
         })
         .await?;
         sender_has_pending_offer = false;
@@ -670,6 +695,8 @@ async fn main() -> Result<(), String> {
             keycloak_username: sender.keycloak_username.clone(),
             keycloak_password: sender.keycloak_password.clone(),
             keycloak_url: sender.keycloak_url.clone(),
+            filter: Default::default(),
+            batch_size: None,
         })
         .await?;
         receiver_has_pending_offer = false;
@@ -734,7 +761,7 @@ async fn main() -> Result<(), String> {
                 user_name: sender.keycloak_username.clone(),
                 access_token: token,
                 api_url: bitsafe_api_url.clone(),
-                withdraw_account_contract_id: wa.contract_id.clone(),
+                account: wa.clone(),
                 amount: withdraw_amount_decimal,
                 holding_contract_ids: selected,
                 credential_cids: Some(minter_credential_cids.clone()),
@@ -117,6 +117,7 @@ async fn main() -> Result<(), String> {
             access_token: access_token.clone(),
             account_rules: account_rules.clone(),
             credential_cids: minter_credential_cids,
+            decentralized_party_id: None,
         })
         .await?;
 
@@ -149,11 +150,16 @@ async fn main() -> Result<(), String> {
             access_token: access_token.clone(),
             api_url: api_url.clone(),
             account_contract_id: deposit_account.contract_id.clone(),
+            quorum: Some(attestor::QuorumPolicy::default()),
         })
         .await?;
 
     println!("✓ Account status:");
     println!("  - Bitcoin Address: {}", status.bitcoin_address);
+    println!(
+        "  - Attestors: {}/{} responded",
+        status.attestors_responded, status.attestors_requested
+    );
     println!("  - Owner: {}", status.owner);
     println!(
         "  - Last Processed BTC Block: {}",
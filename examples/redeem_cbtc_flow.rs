@@ -1,5 +1,6 @@
 use cbtc::credentials::ListCredentialsParams;
 use cbtc::mint_redeem::attestor;
+use cbtc::mint_redeem::bitcoin_address::BitcoinNetwork;
 use cbtc::mint_redeem::models::check_limits;
 use cbtc::mint_redeem::redeem::{
     CreateWithdrawAccountParams, ListHoldingsParams, ListWithdrawAccountsParams,
@@ -121,8 +122,8 @@ async fn main() -> Result<(), String> {
     println!();
 
     // Step 5: Create a new withdraw account (or skip if one already exists)
-    // For production, you should provide a real Bitcoin address via DESTINATION_BTC_ADDRESS env var
-    // For testing/devnet, we use a test address
+    // Provide your Bitcoin address via the DESTINATION_BTC_ADDRESS env var (required on mainnet).
+    // On testnet and regtest, a test address for that network is used when it is unset.
 
     // Step 4b: Fetch Minter credentials
     println!("Step 4b: Fetching Minter credentials...");
@@ -156,8 +157,20 @@ async fn main() -> Result<(), String> {
         println!("  Using existing account: {}", accounts[0].contract_id);
         println!("  Destination: {}\n", accounts[0].destination_btc_address);
     } else {
-        let destination_btc_address = env::var("DESTINATION_BTC_ADDRESS")
-            .unwrap_or_else(|_| "bcrt1qexamplewithdrawaddressfortestingonly00000000".to_string());
+        let bitcoin_network =
+            BitcoinNetwork::resolve(env::var("BITCOIN_NETWORK").ok().as_deref(), &api_url)?;
+        let destination_btc_address = match env::var("DESTINATION_BTC_ADDRESS") {
+            Ok(address) if !address.is_empty() => address,
+            _ => match bitcoin_network {
+                BitcoinNetwork::Regtest => {
+                    "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string()
+                }
+                BitcoinNetwork::Testnet => "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx".to_string(),
+                BitcoinNetwork::Mainnet => {
+                    return Err("DESTINATION_BTC_ADDRESS must be set on mainnet".to_string());
+                }
+            },
+        };
 
         println!("Step 5: Creating a new withdraw account...");
         println!("  Destination BTC address: {}", destination_btc_address);
@@ -172,7 +185,9 @@ async fn main() -> Result<(), String> {
                 account_rules_template_id: account_rules.wa_rules.template_id.clone(),
                 account_rules_created_event_blob: account_rules.wa_rules.created_event_blob.clone(),
                 destination_btc_address: destination_btc_address.clone(),
+                bitcoin_network,
                 credential_cids: minter_credential_cids.clone(),
+                decentralized_party_id: None,
             })
             .await?;
 
@@ -250,7 +265,7 @@ async fn main() -> Result<(), String> {
         user_name: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
         access_token: access_token.clone(),
         api_url: api_url.clone(),
-        withdraw_account_contract_id: withdraw_account.contract_id.clone(),
+        account: withdraw_account.clone(),
         amount: withdraw_amount_decimal,
         holding_contract_ids: selected_holdings,
         credential_cids: Some(minter_credential_cids),
@@ -127,6 +127,7 @@ async fn main() -> Result<(), String> {
         keycloak_url: keycloak_url.clone(),
         reference_base: Some(format!("stream-{}", chrono::Utc::now().timestamp())),
         on_transfer_complete: Some(callback),
+        preflight: None,
     })
     .await?;
 
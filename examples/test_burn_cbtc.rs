@@ -113,7 +113,7 @@ async fn main() -> Result<(), String> {
         user_name: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
         access_token: access_token.clone(),
         api_url: api_url.clone(),
-        withdraw_account_contract_id: withdraw_account.contract_id.clone(),
+        account: withdraw_account.clone(),
         amount: burn_amount,
         holding_contract_ids: selected_holdings,
         credential_cids: Some(minter_credential_cids),
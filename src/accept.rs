@@ -15,6 +15,29 @@ pub struct Params {
     pub decentralized_party_id: String,
 }
 
+/// Default number of acceptances submitted per transaction.
+pub const DEFAULT_BATCH_SIZE: usize = 5;
+
+/// Parameters for accepting a specific set of CBTC transfer offers (by contract
+/// id) as the receiving party, using an existing access token. Submissions are
+/// batched; the registry accept-context is fetched once and shared.
+pub struct AcceptBatchParams {
+    /// Contract IDs of the TransferOffer/TransferInstructions to accept
+    pub contract_ids: Vec<String>,
+    /// The receiver party ID
+    pub receiver_party: String,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Access token for the receiver party
+    pub access_token: String,
+    /// Registry URL
+    pub registry_url: String,
+    /// Decentralized party ID for CBTC
+    pub decentralized_party_id: String,
+    /// Acceptances per transaction (defaults to [`DEFAULT_BATCH_SIZE`])
+    pub batch_size: Option<usize>,
+}
+
 /// Parameters for accepting all pending CBTC transfers for a party.
 pub struct AcceptAllParams {
     /// The receiver party ID
@@ -30,6 +53,10 @@ pub struct AcceptAllParams {
     pub keycloak_username: String,
     pub keycloak_password: String,
     pub keycloak_url: String,
+    /// Only accept offers matching this filter (the default accepts everything)
+    pub filter: crate::utils::OfferFilter,
+    /// Acceptances per transaction (defaults to [`DEFAULT_BATCH_SIZE`])
+    pub batch_size: Option<usize>,
 }
 
 /// Result of accepting a single transfer
@@ -40,6 +67,8 @@ pub struct AcceptResult {
     pub amount: Option<String>,
     pub sender: Option<String>,
     pub error: Option<String>,
+    /// Classification of `error`, so expired and already-archived offers can be told apart
+    pub error_kind: Option<crate::utils::OfferErrorKind>,
 }
 
 /// Result of accepting all pending transfers
@@ -129,13 +158,199 @@ pub async fn submit(params: Params) -> Result<(), String> {
     Ok(())
 }
 
+/// Build a single `TransferInstruction_Accept` exercise command from a shared context.
+fn build_accept_command(
+    contract_id: &str,
+    context: &registry::accept_context::Response,
+) -> common::submission::Command {
+    common::submission::Command::ExerciseCommand(common::submission::ExerciseCommand {
+        exercise_command: common::submission::ExerciseCommandData {
+            template_id: common::consts::TEMPLATE_TRANSFER_INSTRUCTION.to_string(),
+            contract_id: contract_id.to_string(),
+            choice: "TransferInstruction_Accept".to_string(),
+            choice_argument: common::submission::ChoiceArgumentsVariations::Accept(
+                common::accept::ChoiceArguments {
+                    extra_args: common::accept::ExtraArgs {
+                        context: common::accept::Context {
+                            values: context.choice_context_data.values.clone(),
+                        },
+                        meta: common::accept::Meta {
+                            values: common::accept::MetaValue {},
+                        },
+                    },
+                },
+            ),
+        },
+    })
+}
+
+/// Submit an accept for the given contract ids as one (atomic) transaction.
+async fn submit_accepts(
+    contract_ids: &[String],
+    receiver_party: &str,
+    ledger_host: &str,
+    access_token: &str,
+    context: &registry::accept_context::Response,
+) -> Result<(), String> {
+    let commands = contract_ids
+        .iter()
+        .map(|cid| build_accept_command(cid, context))
+        .collect();
+    let submission_request = common::submission::Submission {
+        act_as: vec![receiver_party.to_string()],
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts: context.disclosed_contracts.clone(),
+        commands,
+        ..Default::default()
+    };
+    ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
+        request: submission_request,
+    })
+    .await
+    .map(|_| ())
+}
+
+impl crate::utils::OfferOutcome for AcceptResult {
+    fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    fn mark_success(&mut self) {
+        self.success = true;
+    }
+
+    fn mark_failed(&mut self, error: String) {
+        self.error_kind = Some(crate::utils::OfferErrorKind::classify(&error));
+        self.error = Some(error);
+    }
+}
+
+/// Accept the given offers in batches of `batch_size`, sharing one context.
+///
+/// `pending` carries the per-offer result skeletons (contract id plus any known
+/// amount/sender); each is marked successful or failed as its batch completes.
+/// A failed batch is retried per offer (see [`crate::utils::submit_offer_batches`]).
+async fn accept_in_batches(
+    pending: Vec<AcceptResult>,
+    batch_size: usize,
+    receiver_party: &str,
+    ledger_host: &str,
+    access_token: &str,
+    context: &registry::accept_context::Response,
+) -> AcceptAllResult {
+    let (results, successful_count, failed_count) =
+        crate::utils::submit_offer_batches(pending, batch_size, |contract_ids| async move {
+            submit_accepts(
+                &contract_ids,
+                receiver_party,
+                ledger_host,
+                access_token,
+                context,
+            )
+            .await
+        })
+        .await;
+
+    log::debug!(
+        "Summary: Accepted: {}, Failed: {}",
+        successful_count,
+        failed_count
+    );
+
+    AcceptAllResult {
+        results,
+        successful_count,
+        failed_count,
+    }
+}
+
+/// Fetch the accept context for the CBTC transfers in this run from the registry.
+///
+/// The context is the same for all CBTC transfers, so it is fetched once using
+/// the first offer and shared across every command.
+async fn fetch_accept_context(
+    registry_url: &str,
+    decentralized_party_id: &str,
+    first_contract_id: &str,
+) -> Result<registry::accept_context::Response, String> {
+    registry::accept_context::get(registry::accept_context::Params {
+        registry_url: registry_url.to_string(),
+        decentralized_party_id: decentralized_party_id.to_string(),
+        transfer_offer_contract_id: first_contract_id.to_string(),
+        request: registry::accept_context::Request {
+            meta: registry::accept_context::Meta {
+                values: String::new(),
+            },
+        },
+    })
+    .await
+}
+
+/// Accept a specific set of CBTC transfer offers by contract id, batched.
+///
+/// Like [`accept_all`] but operates on a provided list of contract ids and an
+/// existing access token (no re-authentication), so services that manage their
+/// own tokens can accept a chosen subset of offers. The registry accept-context
+/// is fetched once and reused for every command. A failed batch is retried per
+/// offer, and each failure carries an [`crate::utils::OfferErrorKind`].
+///
+/// # Errors
+/// Returns an error string only if the shared registry context cannot be fetched.
+/// Individual offer failures are recorded in the returned result (with the error)
+/// rather than aborting the whole run.
+pub async fn accept_batch(params: AcceptBatchParams) -> Result<AcceptAllResult, String> {
+    if params.contract_ids.is_empty() {
+        return Ok(AcceptAllResult {
+            results: Vec::new(),
+            successful_count: 0,
+            failed_count: 0,
+        });
+    }
+
+    let accept_context = fetch_accept_context(
+        &params.registry_url,
+        &params.decentralized_party_id,
+        &params.contract_ids[0],
+    )
+    .await?;
+
+    let pending = params
+        .contract_ids
+        .into_iter()
+        .map(|contract_id| AcceptResult {
+            success: false,
+            contract_id,
+            amount: None,
+            sender: None,
+            error: None,
+            error_kind: None,
+        })
+        .collect();
+
+    Ok(accept_in_batches(
+        pending,
+        params.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        &params.receiver_party,
+        &params.ledger_host,
+        &params.access_token,
+        &accept_context,
+    )
+    .await)
+}
+
 /// Accept all pending CBTC transfers for a party.
 ///
 /// This function:
 /// 1. Authenticates with Keycloak
 /// 2. Fetches all pending TransferInstruction contracts for the party
-/// 3. Filters for CBTC transfers where the party is the receiver
-/// 4. Batches acceptances into groups of 5 per submission
+/// 3. Filters for CBTC transfers where the party is the receiver and that match
+///    `params.filter`
+/// 4. Batches acceptances into groups of `params.batch_size` (default 5) per submission,
+///    retrying a failed batch per offer so one withdrawn or expired offer does not
+///    fail the others
 ///
 /// Returns a summary of successful and failed acceptances.
 pub async fn accept_all(params: AcceptAllParams) -> Result<AcceptAllResult, String> {
@@ -155,12 +370,16 @@ pub async fn accept_all(params: AcceptAllParams) -> Result<AcceptAllResult, Stri
         "Checking for pending transfers for party: {}",
         params.receiver_party
     );
-    let pending_transfers = crate::utils::fetch_incoming_transfers(
-        params.ledger_host.clone(),
-        params.receiver_party.clone(),
-        auth.access_token.clone(),
-    )
-    .await?;
+    let pending_transfers: Vec<ledger::models::JsActiveContract> =
+        crate::utils::fetch_incoming_transfers(
+            params.ledger_host.clone(),
+            params.receiver_party.clone(),
+            auth.access_token.clone(),
+        )
+        .await?
+        .into_iter()
+        .filter(|contract| params.filter.matches(contract))
+        .collect();
 
     if pending_transfers.is_empty() {
         log::debug!("No pending transfers found");
@@ -175,202 +394,38 @@ pub async fn accept_all(params: AcceptAllParams) -> Result<AcceptAllResult, Stri
 
     // Fetch accept_context once (assumed to be the same for all CBTC transfers in this run)
     log::debug!("Fetching accept context (shared for all CBTC transfers)...");
-    let first_contract_id = &pending_transfers[0].created_event.contract_id;
-    let accept_context = registry::accept_context::get(registry::accept_context::Params {
-        registry_url: params.registry_url.clone(),
-        decentralized_party_id: params.decentralized_party_id.clone(),
-        transfer_offer_contract_id: first_contract_id.clone(),
-        request: registry::accept_context::Request {
-            meta: registry::accept_context::Meta {
-                values: String::new(),
-            },
-        },
-    })
+    let accept_context = fetch_accept_context(
+        &params.registry_url,
+        &params.decentralized_party_id,
+        &pending_transfers[0].created_event.contract_id,
+    )
     .await?;
     log::debug!("✓ Accept context fetched\n");
 
-    const BATCH_SIZE: usize = 5;
-    let total_transfers = pending_transfers.len();
-    let num_batches = (total_transfers + BATCH_SIZE - 1) / BATCH_SIZE;
-
-    log::debug!(
-        "Submitting {} acceptances in {} batch(es) of up to {}...",
-        total_transfers,
-        num_batches,
-        BATCH_SIZE
-    );
-
-    let mut results = Vec::new();
-    let mut successful_count = 0;
-    let mut failed_count = 0;
-
-    // Process transfers in chunks of BATCH_SIZE
-    for (batch_idx, batch_transfers) in pending_transfers.chunks(BATCH_SIZE).enumerate() {
-        let batch_num = batch_idx + 1;
-        let start_idx = batch_idx * BATCH_SIZE;
-        let end_idx = std::cmp::min(start_idx + batch_transfers.len(), total_transfers);
-
-        log::debug!(
-            "--- Batch {}/{}: Preparing acceptances {}-{} ---",
-            batch_num,
-            num_batches,
-            start_idx + 1,
-            end_idx
-        );
-
-        // Build exercise commands for this batch
-        let mut batch_commands = Vec::new();
-        let mut batch_results = Vec::new();
-
-        for (idx_in_batch, transfer) in batch_transfers.iter().enumerate() {
-            let global_idx = start_idx + idx_in_batch;
-            let contract_id = &transfer.created_event.contract_id;
-            let short_id = if contract_id.len() > 16 {
-                format!(
-                    "{}...{}",
-                    &contract_id[..8],
-                    &contract_id[contract_id.len() - 8..]
-                )
-            } else {
-                contract_id.clone()
-            };
-
-            log::debug!("{}. Preparing {}", global_idx + 1, short_id);
-
-            // Extract transfer details from create_argument
-            let mut amount = None;
-            let mut sender = None;
-
-            if let Some(create_arg) = &transfer.created_event.create_argument {
-                if let Some(transfer_data) = create_arg.get("transfer") {
-                    if let Some(amt) = transfer_data.get("amount") {
-                        amount = amt.as_str().map(|s| s.to_string());
-                        log::debug!("Amount: {}", amt);
-                    }
-                    if let Some(sndr) = transfer_data.get("sender") {
-                        sender = sndr.as_str().map(|s| s.to_string());
-                        log::debug!("From: {}", sndr.as_str().unwrap_or("unknown"));
-                    }
-                }
-            }
-
-            // Build exercise command using shared context
-            let exercise_command = common::submission::ExerciseCommand {
-                exercise_command: common::submission::ExerciseCommandData {
-                    template_id: common::consts::TEMPLATE_TRANSFER_INSTRUCTION.to_string(),
-                    contract_id: contract_id.clone(),
-                    choice: "TransferInstruction_Accept".to_string(),
-                    choice_argument: common::submission::ChoiceArgumentsVariations::Accept(
-                        common::accept::ChoiceArguments {
-                            extra_args: common::accept::ExtraArgs {
-                                context: common::accept::Context {
-                                    values: accept_context.choice_context_data.values.clone(),
-                                },
-                                meta: common::accept::Meta {
-                                    values: common::accept::MetaValue {},
-                                },
-                            },
-                        },
-                    ),
-                },
-            };
-
-            batch_commands.push(common::submission::Command::ExerciseCommand(
-                exercise_command,
-            ));
-
-            // Prepare result tracking for this transfer
-            batch_results.push(AcceptResult {
-                success: false, // Will update after submission
-                contract_id: contract_id.clone(),
+    let pending = pending_transfers
+        .iter()
+        .map(|transfer| {
+            let (amount, sender) = crate::utils::offer_details(transfer);
+            AcceptResult {
+                success: false,
+                contract_id: transfer.created_event.contract_id.clone(),
                 amount,
                 sender,
                 error: None,
-            });
-        }
-
-        // Submit this batch
-        log::debug!("Submitting batch {}/{}...", batch_num, num_batches);
-
-        let submission_request = common::submission::Submission {
-            act_as: vec![params.receiver_party.clone()],
-            read_as: None,
-            command_id: uuid::Uuid::new_v4().to_string(),
-            disclosed_contracts: accept_context.disclosed_contracts.clone(),
-            commands: batch_commands,
-            ..Default::default()
-        };
-
-        match ledger::submit::wait_for_transaction(ledger::submit::Params {
-            ledger_host: params.ledger_host.clone(),
-            access_token: auth.access_token.clone(),
-            request: submission_request,
-        })
-        .await
-        {
-            Ok(_) => {
-                log::debug!("  ✓ Batch {}/{} successful", batch_num, num_batches);
-                // Mark this batch's results as successful
-                for (idx_in_batch, result) in batch_results.iter_mut().enumerate() {
-                    result.success = true;
-                    successful_count += 1;
-
-                    let short_id = if result.contract_id.len() > 16 {
-                        format!(
-                            "{}...{}",
-                            &result.contract_id[..8],
-                            &result.contract_id[result.contract_id.len() - 8..]
-                        )
-                    } else {
-                        result.contract_id.clone()
-                    };
-                    log::debug!(
-                        "    {}. {} [SUCCESS]",
-                        start_idx + idx_in_batch + 1,
-                        short_id
-                    );
-                }
-            }
-            Err(e) => {
-                log::debug!("  ✗ Batch {}/{} failed: {}", batch_num, num_batches, e);
-                // Mark this batch's results as failed
-                for (idx_in_batch, result) in batch_results.iter_mut().enumerate() {
-                    result.error = Some(e.clone());
-                    failed_count += 1;
-
-                    let short_id = if result.contract_id.len() > 16 {
-                        format!(
-                            "{}...{}",
-                            &result.contract_id[..8],
-                            &result.contract_id[result.contract_id.len() - 8..]
-                        )
-                    } else {
-                        result.contract_id.clone()
-                    };
-                    log::debug!(
-                        "    {}. {} [FAILED]",
-                        start_idx + idx_in_batch + 1,
-                        short_id
-                    );
-                }
+                error_kind: None,
             }
-        }
-
-        // Append batch results to overall results
-        results.extend(batch_results);
-    }
-
-    log::debug!(
-        "Summary: Accepted: {}, Failed: {}",
-        successful_count,
-        failed_count
-    );
-
-    Ok(AcceptAllResult {
-        successful_count,
-        failed_count,
-        results,
-    })
+        })
+        .collect();
+
+    Ok(accept_in_batches(
+        pending,
+        params.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        &params.receiver_party,
+        &params.ledger_host,
+        &auth.access_token,
+        &accept_context,
+    )
+    .await)
 }
 
 #[cfg(test)]
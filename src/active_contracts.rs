@@ -6,33 +6,7 @@ pub struct Params {
 }
 
 pub async fn get(params: Params) -> Result<Vec<ledger::models::JsActiveContract>, String> {
-    use ledger::ledger_end;
-    use ledger::websocket::active_contracts;
-
-    let ledger_end_result = ledger_end::get(ledger_end::Params {
-        access_token: params.access_token.clone(),
-        ledger_host: params.ledger_host.clone(),
-    })
-    .await?;
-
-    let result = active_contracts::get(active_contracts::Params {
-        ledger_host: params.ledger_host,
-        party: params.party,
-        filter: ledger::common::IdentifierFilter::InterfaceIdentifierFilter(
-            ledger::common::InterfaceIdentifierFilter {
-                interface_filter: ledger::common::InterfaceFilter {
-                    value: ledger::common::InterfaceFilterValue {
-                        interface_id: Some(common::consts::INTERFACE_HOLDING.to_string()),
-                        include_interface_view: true,
-                        include_created_event_blob: true,
-                    },
-                },
-            },
-        ),
-        access_token: params.access_token,
-        ledger_end: ledger_end_result.offset,
-    })
-    .await?;
+    let result = get_by_interface(params, common::consts::INTERFACE_HOLDING).await?;
 
     let filtered: Vec<ledger::models::JsActiveContract> = result
         .into_iter()
@@ -62,34 +36,54 @@ pub async fn get(params: Params) -> Result<Vec<ledger::models::JsActiveContract>
     Ok(filtered)
 }
 
+/// Fetch all active contracts visible to `params.party` that implement
+/// `interface_id`, including their interface views.
+pub(crate) async fn get_by_interface(
+    params: Params,
+    interface_id: &str,
+) -> Result<Vec<ledger::models::JsActiveContract>, String> {
+    use ledger::ledger_end;
+    use ledger::websocket::active_contracts;
+
+    let ledger_end_result = ledger_end::get(ledger_end::Params {
+        access_token: params.access_token.clone(),
+        ledger_host: params.ledger_host.clone(),
+    })
+    .await?;
+
+    active_contracts::get(active_contracts::Params {
+        ledger_host: params.ledger_host,
+        party: params.party,
+        filter: ledger::common::IdentifierFilter::InterfaceIdentifierFilter(
+            ledger::common::InterfaceIdentifierFilter {
+                interface_filter: ledger::common::InterfaceFilter {
+                    value: ledger::common::InterfaceFilterValue {
+                        interface_id: Some(interface_id.to_string()),
+                        include_interface_view: true,
+                        include_created_event_blob: true,
+                    },
+                },
+            },
+        ),
+        access_token: params.access_token,
+        ledger_end: ledger_end_result.offset,
+    })
+    .await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
     #[tokio::test]
     async fn test_get_by_party() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
 
         let contracts = get(Params {
-            ledger_host: ledger_host.to_string(),
-            party: party_id,
-            access_token: login_response.access_token,
+            ledger_host: env.ledger_host(),
+            party: env.party(),
+            access_token: env.access_token().await,
         })
         .await
         .unwrap();
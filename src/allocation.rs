@@ -35,6 +35,37 @@ pub struct ActionParams {
     pub decentralized_party_id: String,
 }
 
+/// Build the `AllocationSpecification` for one `leg` of `settlement`.
+pub fn specification(
+    settlement: &Settlement,
+    leg: &Leg,
+) -> common::allocation::AllocationSpecification {
+    common::allocation::AllocationSpecification {
+        settlement: common::allocation::SettlementInfo {
+            executor: settlement.executor.clone(),
+            settlement_ref: common::allocation::Reference {
+                id: settlement.settlement_ref.clone(),
+                cid: settlement.settlement_ref_cid.clone(),
+            },
+            requested_at: settlement.requested_at.clone(),
+            allocate_before: settlement.allocate_before.clone(),
+            settle_before: settlement.settle_before.clone(),
+            meta: settlement.meta.clone(),
+        },
+        transfer_leg_id: leg.leg_id.clone(),
+        transfer_leg: common::allocation::TransferLeg {
+            sender: leg.sender.clone(),
+            receiver: leg.receiver.clone(),
+            amount: leg.amount,
+            instrument_id: common::transfer::InstrumentId {
+                admin: leg.instrument_admin.clone(),
+                id: leg.instrument_id.clone(),
+            },
+            meta: leg.meta.clone(),
+        },
+    }
+}
+
 /// Allocate a transfer leg: lock the sender's holdings into a settlement leg via
 /// the registry's `AllocationFactory_Allocate` choice.
 ///
@@ -112,8 +143,8 @@ pub async fn allocate(params: Params) -> Result<(), String> {
 /// Execute the transfer of an allocated leg (`Allocation_ExecuteTransfer`).
 ///
 /// Submitted by the settlement executor. A coordinating app normally settles all
-/// legs of a settlement together in one transaction; this exposes the single-leg
-/// choice for that purpose.
+/// legs of a settlement together in one transaction (see
+/// [`crate::settlement::settle`]); this exposes the single-leg choice.
 ///
 /// # Errors
 ///
@@ -155,30 +186,20 @@ async fn exercise_allocation_choice(
     daml_choice: &str,
     params: ActionParams,
 ) -> Result<(), String> {
-    let context = registry::allocation_context::get(registry::allocation_context::Params {
-        registry_url: params.registry_url,
-        decentralized_party_id: params.decentralized_party_id.clone(),
-        allocation_contract_id: params.allocation_contract_id.clone(),
+    let (exercise_command, disclosed_contracts) = action_command_with_context(
         choice,
-        request: registry::allocation_context::Request {
-            meta: registry::allocation_context::Meta {
-                values: String::new(),
-            },
-        },
-    })
-    .await?;
-
-    let exercise_command = build_action_command(
-        params.allocation_contract_id,
         daml_choice,
-        context.choice_context_data.values,
-    );
+        &params.allocation_contract_id,
+        &params.registry_url,
+        &params.decentralized_party_id,
+    )
+    .await?;
 
     let submission_request = common::submission::Submission {
         act_as: vec![params.actor_party],
         read_as: None,
         command_id: uuid::Uuid::new_v4().to_string(),
-        disclosed_contracts: context.disclosed_contracts,
+        disclosed_contracts,
         commands: vec![common::submission::Command::ExerciseCommand(
             exercise_command,
         )],
@@ -195,6 +216,76 @@ async fn exercise_allocation_choice(
     Ok(())
 }
 
+/// Fetch the registry choice context for `choice` on an allocation and build
+/// the `daml_choice` exercise command, returning it with the disclosed
+/// contracts the submission needs. Lets callers put several allocation
+/// actions into one transaction.
+pub(crate) async fn action_command_with_context(
+    choice: AllocationChoice,
+    daml_choice: &str,
+    allocation_contract_id: &str,
+    registry_url: &str,
+    decentralized_party_id: &str,
+) -> Result<
+    (
+        common::submission::ExerciseCommand,
+        Vec<common::transfer::DisclosedContract>,
+    ),
+    String,
+> {
+    let context = registry::allocation_context::get(registry::allocation_context::Params {
+        registry_url: registry_url.to_string(),
+        decentralized_party_id: decentralized_party_id.to_string(),
+        allocation_contract_id: allocation_contract_id.to_string(),
+        choice,
+        request: registry::allocation_context::Request {
+            meta: registry::allocation_context::Meta {
+                values: String::new(),
+            },
+        },
+    })
+    .await?;
+
+    let exercise_command = build_action_command(
+        allocation_contract_id.to_string(),
+        daml_choice,
+        context.choice_context_data.values,
+    );
+
+    Ok((exercise_command, context.disclosed_contracts))
+}
+
+/// Fetch the registry choice context for `choice` on an allocation and return
+/// it as an `extraArgs` value, with the disclosed contracts the submission
+/// needs. For app choices that exercise the allocation on our behalf and take
+/// its `extraArgs` as an argument (e.g. the collateral app).
+pub(crate) async fn extra_args_with_context(
+    choice: AllocationChoice,
+    allocation_contract_id: &str,
+    registry_url: &str,
+    decentralized_party_id: &str,
+) -> Result<(serde_json::Value, Vec<common::transfer::DisclosedContract>), String> {
+    let context = registry::allocation_context::get(registry::allocation_context::Params {
+        registry_url: registry_url.to_string(),
+        decentralized_party_id: decentralized_party_id.to_string(),
+        allocation_contract_id: allocation_contract_id.to_string(),
+        choice,
+        request: registry::allocation_context::Request {
+            meta: registry::allocation_context::Meta {
+                values: String::new(),
+            },
+        },
+    })
+    .await?;
+
+    let extra_args = serde_json::json!({
+        "context": { "values": context.choice_context_data.values },
+        "meta": { "values": {} }
+    });
+
+    Ok((extra_args, context.disclosed_contracts))
+}
+
 /// An empty `extraArgs` (empty context and meta), as required on the allocate
 /// request before the registry fills in the choice context.
 fn empty_extra_args() -> common::transfer_factory::ExtraArgs {
@@ -270,10 +361,323 @@ fn build_action_command(
     }
 }
 
+/// Interface implemented by every token-standard allocation.
+pub const INTERFACE_ALLOCATION: &str =
+    "#splice-api-token-allocation-v1:Splice.Api.Token.AllocationV1:Allocation";
+
+/// Interface implemented by every token-standard allocation request.
+pub const INTERFACE_ALLOCATION_REQUEST: &str = "#splice-api-token-allocation-request-v1:Splice.Api.Token.AllocationRequestV1:AllocationRequest";
+
+/// Parameters for listing allocations or allocation requests visible to a party.
+pub struct ListParams {
+    /// The party whose allocations (as sender, receiver or executor) to list
+    pub party: String,
+    pub ledger_host: String,
+    pub access_token: String,
+}
+
+/// The settlement an allocation or allocation request belongs to.
+#[derive(Debug, Clone)]
+pub struct Settlement {
+    /// The settlement executor (typically the trading venue)
+    pub executor: String,
+    /// The settlement reference id shared by all legs
+    pub settlement_ref: String,
+    /// Optional contract id attached to the settlement reference
+    pub settlement_ref_cid: Option<String>,
+    pub requested_at: String,
+    /// Deadline for funding the legs (RFC3339)
+    pub allocate_before: String,
+    /// Deadline for settling the legs (RFC3339)
+    pub settle_before: String,
+    /// Settlement metadata, carried into every leg's allocation
+    pub meta: common::allocation::Metadata,
+}
+
+/// One transfer leg of a settlement.
+#[derive(Debug, Clone)]
+pub struct Leg {
+    pub leg_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub amount: common::decimal::DamlDecimal,
+    pub instrument_admin: String,
+    pub instrument_id: String,
+    /// Leg metadata, carried into the leg's allocation
+    pub meta: common::allocation::Metadata,
+}
+
+/// Status of an allocation or allocation request, relative to its deadlines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationStatus {
+    /// Within the allocation window (requests only)
+    AwaitingAllocation,
+    /// Allocation window has passed; settlement is still possible
+    AwaitingSettlement,
+    /// The settlement deadline has passed
+    Expired,
+}
+
+/// An allocation (one funded leg) decoded from its interface view.
+#[derive(Debug, Clone)]
+pub struct Allocation {
+    pub contract_id: String,
+    pub settlement: Settlement,
+    pub leg: Leg,
+    /// Holdings locked for this leg
+    pub holding_cids: Vec<String>,
+    pub status: AllocationStatus,
+}
+
+/// An allocation request (a settlement asking parties to fund its legs)
+/// decoded from its interface view.
+#[derive(Debug, Clone)]
+pub struct AllocationRequest {
+    pub contract_id: String,
+    pub settlement: Settlement,
+    /// Legs, ordered by leg id
+    pub legs: Vec<Leg>,
+    pub status: AllocationStatus,
+}
+
+/// List the allocations visible to a party.
+///
+/// Decodes the splice `Allocation` interface view of each active contract.
+/// Contracts whose view can't be decoded are skipped with a warning.
+///
+/// # Errors
+///
+/// Returns an error string if the active-contract query fails.
+pub async fn list_allocations(params: ListParams) -> Result<Vec<Allocation>, String> {
+    let contracts = active_contracts::get_by_interface(
+        active_contracts::Params {
+            ledger_host: params.ledger_host,
+            party: params.party,
+            access_token: params.access_token,
+        },
+        INTERFACE_ALLOCATION,
+    )
+    .await?;
+
+    let now = chrono::Utc::now();
+    Ok(contracts
+        .iter()
+        .filter_map(|contract| {
+            let cid = &contract.created_event.contract_id;
+            match interface_view(contract)
+                .ok_or_else(|| "missing interface view".to_string())
+                .and_then(|view| parse_allocation_view(cid, view, now))
+            {
+                Ok(allocation) => Some(allocation),
+                Err(e) => {
+                    log::warn!("Skipping allocation {}: {}", cid, e);
+                    None
+                }
+            }
+        })
+        .collect())
+}
+
+/// List the allocation requests visible to a party.
+///
+/// Decodes the splice `AllocationRequest` interface view of each active contract.
+/// Contracts whose view can't be decoded are skipped with a warning.
+///
+/// # Errors
+///
+/// Returns an error string if the active-contract query fails.
+pub async fn list_allocation_requests(
+    params: ListParams,
+) -> Result<Vec<AllocationRequest>, String> {
+    let contracts = active_contracts::get_by_interface(
+        active_contracts::Params {
+            ledger_host: params.ledger_host,
+            party: params.party,
+            access_token: params.access_token,
+        },
+        INTERFACE_ALLOCATION_REQUEST,
+    )
+    .await?;
+
+    let now = chrono::Utc::now();
+    Ok(contracts
+        .iter()
+        .filter_map(|contract| {
+            let cid = &contract.created_event.contract_id;
+            match interface_view(contract)
+                .ok_or_else(|| "missing interface view".to_string())
+                .and_then(|view| parse_allocation_request_view(cid, view, now))
+            {
+                Ok(request) => Some(request),
+                Err(e) => {
+                    log::warn!("Skipping allocation request {}: {}", cid, e);
+                    None
+                }
+            }
+        })
+        .collect())
+}
+
+/// The first interface view value of an active contract.
+fn interface_view(contract: &ledger::models::JsActiveContract) -> Option<&serde_json::Value> {
+    contract
+        .created_event
+        .interface_views
+        .as_ref()?
+        .iter()
+        .find_map(|view| match &view.view_value {
+            Some(Some(value)) => Some(value),
+            _ => None,
+        })
+}
+
+fn str_field(value: &serde_json::Value, key: &str) -> Result<String, String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| format!("Missing '{}' field", key))
+}
+
+fn parse_settlement(value: &serde_json::Value) -> Result<Settlement, String> {
+    let settlement_ref = value
+        .get("settlementRef")
+        .ok_or("Missing 'settlementRef' field")?;
+    Ok(Settlement {
+        executor: str_field(value, "executor")?,
+        settlement_ref: str_field(settlement_ref, "id")?,
+        settlement_ref_cid: settlement_ref
+            .get("cid")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string()),
+        requested_at: str_field(value, "requestedAt")?,
+        allocate_before: str_field(value, "allocateBefore")?,
+        settle_before: str_field(value, "settleBefore")?,
+        meta: parse_meta(value)?,
+    })
+}
+
+fn parse_leg(leg_id: &str, value: &serde_json::Value) -> Result<Leg, String> {
+    let amount = common::decimal::DamlDecimal::parse(&str_field(value, "amount")?)
+        .map_err(|e| format!("Invalid 'amount' field: {}", e))?;
+    let instrument = value
+        .get("instrumentId")
+        .ok_or("Missing 'instrumentId' field")?;
+    Ok(Leg {
+        leg_id: leg_id.to_string(),
+        sender: str_field(value, "sender")?,
+        receiver: str_field(value, "receiver")?,
+        amount,
+        instrument_admin: str_field(instrument, "admin")?,
+        instrument_id: str_field(instrument, "id")?,
+        meta: parse_meta(value)?,
+    })
+}
+
+/// Decode an optional Daml `Metadata` field (`meta`), empty when absent.
+fn parse_meta(value: &serde_json::Value) -> Result<common::allocation::Metadata, String> {
+    match value.get("meta") {
+        Some(meta) if !meta.is_null() => {
+            serde_json::from_value(meta.clone()).map_err(|e| format!("Invalid 'meta' field: {}", e))
+        }
+        _ => Ok(common::allocation::Metadata::default()),
+    }
+}
+
+fn parse_deadline(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .map_err(|e| format!("Invalid deadline '{}': {}", value, e))
+}
+
+fn settlement_status(
+    settlement: &Settlement,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<AllocationStatus, String> {
+    if now >= parse_deadline(&settlement.settle_before)? {
+        Ok(AllocationStatus::Expired)
+    } else if now >= parse_deadline(&settlement.allocate_before)? {
+        Ok(AllocationStatus::AwaitingSettlement)
+    } else {
+        Ok(AllocationStatus::AwaitingAllocation)
+    }
+}
+
+/// Decode an `AllocationView`: `{ allocation: { settlement, transferLegId,
+/// transferLeg }, holdingCids, meta }`.
+fn parse_allocation_view(
+    contract_id: &str,
+    view: &serde_json::Value,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Allocation, String> {
+    let allocation = view.get("allocation").ok_or("Missing 'allocation' field")?;
+    let settlement = parse_settlement(
+        allocation
+            .get("settlement")
+            .ok_or("Missing 'settlement' field")?,
+    )?;
+    let leg = parse_leg(
+        &str_field(allocation, "transferLegId")?,
+        allocation
+            .get("transferLeg")
+            .ok_or("Missing 'transferLeg' field")?,
+    )?;
+    let holding_cids = view
+        .get("holdingCids")
+        .and_then(|v| v.as_array())
+        .map(|cids| {
+            cids.iter()
+                .filter_map(|cid| cid.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // A funded leg is only waiting on settlement, whatever the allocation window.
+    let status = match settlement_status(&settlement, now)? {
+        AllocationStatus::Expired => AllocationStatus::Expired,
+        _ => AllocationStatus::AwaitingSettlement,
+    };
+
+    Ok(Allocation {
+        contract_id: contract_id.to_string(),
+        settlement,
+        leg,
+        holding_cids,
+        status,
+    })
+}
+
+/// Decode an `AllocationRequestView`: `{ settlement, transferLegs, meta }`,
+/// where `transferLegs` maps leg id to leg.
+fn parse_allocation_request_view(
+    contract_id: &str,
+    view: &serde_json::Value,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<AllocationRequest, String> {
+    let settlement = parse_settlement(view.get("settlement").ok_or("Missing 'settlement' field")?)?;
+    let mut legs = view
+        .get("transferLegs")
+        .and_then(|v| v.as_object())
+        .ok_or("Missing 'transferLegs' field")?
+        .iter()
+        .map(|(leg_id, leg)| parse_leg(leg_id, leg))
+        .collect::<Result<Vec<_>, _>>()?;
+    legs.sort_by(|a, b| a.leg_id.cmp(&b.leg_id));
+    let status = settlement_status(&settlement, now)?;
+
+    Ok(AllocationRequest {
+        contract_id: contract_id.to_string(),
+        settlement,
+        legs,
+        status,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use common::submission::ChoiceArgumentsVariations;
+    use serde_json::json;
 
     fn sample_allocation() -> common::allocation::AllocationSpecification {
         common::allocation::AllocationSpecification {
@@ -381,4 +785,128 @@ mod tests {
             }
         }
     }
+
+    fn at(s: &str) -> chrono::DateTime<chrono::Utc> {
+        parse_deadline(s).unwrap()
+    }
+
+    fn settlement() -> serde_json::Value {
+        json!({
+            "executor": "venue::1220",
+            "settlementRef": { "id": "trade-42", "cid": null },
+            "requestedAt": "2026-01-01T00:00:00Z",
+            "allocateBefore": "2026-01-02T00:00:00Z",
+            "settleBefore": "2026-01-03T00:00:00Z",
+            "meta": { "values": {} }
+        })
+    }
+
+    fn leg(sender: &str, receiver: &str, amount: &str, id: &str) -> serde_json::Value {
+        json!({
+            "sender": sender,
+            "receiver": receiver,
+            "amount": amount,
+            "instrumentId": { "admin": "admin::1220", "id": id },
+            "meta": { "values": {} }
+        })
+    }
+
+    #[test]
+    fn decodes_allocation_view() {
+        let view = json!({
+            "allocation": {
+                "settlement": settlement(),
+                "transferLegId": "leg-0",
+                "transferLeg": leg("alice::1220", "bob::1220", "0.5", "CBTC")
+            },
+            "holdingCids": ["00h1", "00h2"],
+            "meta": { "values": {} }
+        });
+
+        let allocation =
+            parse_allocation_view("00alloc", &view, at("2026-01-01T12:00:00Z")).unwrap();
+        assert_eq!(allocation.contract_id, "00alloc");
+        assert_eq!(allocation.settlement.settlement_ref, "trade-42");
+        assert!(allocation.settlement.settlement_ref_cid.is_none());
+        assert_eq!(allocation.leg.leg_id, "leg-0");
+        assert_eq!(allocation.leg.sender, "alice::1220");
+        assert_eq!(
+            allocation.leg.amount,
+            common::decimal::DamlDecimal::parse("0.5").unwrap()
+        );
+        assert_eq!(allocation.holding_cids, vec!["00h1", "00h2"]);
+        assert_eq!(allocation.status, AllocationStatus::AwaitingSettlement);
+    }
+
+    #[test]
+    fn allocation_past_settle_before_is_expired() {
+        let view = json!({
+            "allocation": {
+                "settlement": settlement(),
+                "transferLegId": "leg-0",
+                "transferLeg": leg("alice::1220", "bob::1220", "0.5", "CBTC")
+            },
+            "holdingCids": []
+        });
+
+        let allocation =
+            parse_allocation_view("00alloc", &view, at("2026-01-04T00:00:00Z")).unwrap();
+        assert_eq!(allocation.status, AllocationStatus::Expired);
+    }
+
+    #[test]
+    fn decodes_allocation_request_view_with_sorted_legs() {
+        let view = json!({
+            "settlement": settlement(),
+            "transferLegs": {
+                "leg-1": leg("bob::1220", "alice::1220", "100", "USDC"),
+                "leg-0": leg("alice::1220", "bob::1220", "0.001", "CBTC")
+            },
+            "meta": { "values": {} }
+        });
+
+        let request =
+            parse_allocation_request_view("00req", &view, at("2026-01-01T12:00:00Z")).unwrap();
+        assert_eq!(request.legs.len(), 2);
+        assert_eq!(request.legs[0].leg_id, "leg-0");
+        assert_eq!(request.legs[0].instrument_id, "CBTC");
+        assert_eq!(request.legs[1].instrument_id, "USDC");
+        assert_eq!(request.status, AllocationStatus::AwaitingAllocation);
+
+        let later =
+            parse_allocation_request_view("00req", &view, at("2026-01-02T12:00:00Z")).unwrap();
+        assert_eq!(later.status, AllocationStatus::AwaitingSettlement);
+    }
+
+    #[test]
+    fn specification_keeps_the_request_metadata() {
+        let mut settlement = settlement();
+        settlement["meta"] = json!({ "values": { "venue/trade-id": "T-7" } });
+        let mut cbtc_leg = leg("alice::1220", "bob::1220", "0.001", "CBTC");
+        cbtc_leg["meta"] = json!({ "values": { "venue/side": "sell" } });
+        let view = json!({
+            "settlement": settlement,
+            "transferLegs": { "leg-0": cbtc_leg },
+            "meta": { "values": {} }
+        });
+
+        let request =
+            parse_allocation_request_view("00req", &view, at("2026-01-01T12:00:00Z")).unwrap();
+        let spec = specification(&request.settlement, &request.legs[0]);
+        assert_eq!(
+            serde_json::to_value(&spec.settlement.meta).unwrap(),
+            json!({ "values": { "venue/trade-id": "T-7" } })
+        );
+        assert_eq!(
+            serde_json::to_value(&spec.transfer_leg.meta).unwrap(),
+            json!({ "values": { "venue/side": "sell" } })
+        );
+    }
+
+    #[test]
+    fn missing_settlement_is_an_error() {
+        let err = parse_allocation_request_view("00req", &json!({}), at("2026-01-01T00:00:00Z"))
+            .unwrap_err();
+        assert!(err.contains("settlement"), "unexpected error: {err}");
+    }
 }
@@ -0,0 +1,298 @@
+//! Respond to AllocationRequests sent by trading apps (counterparty side of DvP).
+//!
+//! A trading app asks us to fund our leg of a settlement by creating a
+//! `splice-api-token-allocation-request-v1` `AllocationRequest`. We can accept
+//! it, which allocates our leg through [`crate::allocation::allocate`], or
+//! reject it with `AllocationRequest_Reject`.
+
+use crate::allocation::{self, AllocationRequest, AllocationStatus, Leg};
+
+/// Parameters for accepting an allocation request by allocating our leg.
+pub struct AcceptParams {
+    /// The request to accept, as returned by [`list_incoming`]
+    pub request: AllocationRequest,
+    /// Our party; must be the sender of the leg being allocated
+    pub party: String,
+    /// Which leg to allocate. If `None`, the single leg sent by `party` is used.
+    pub leg_id: Option<String>,
+    /// Holdings to fund the allocation. If empty, holdings are auto-selected.
+    pub input_holding_cids: Vec<String>,
+    pub ledger_host: String,
+    pub access_token: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+}
+
+/// Parameters for rejecting an allocation request.
+pub struct RejectParams {
+    /// Contract id of the AllocationRequest
+    pub request_contract_id: String,
+    /// Our party (the rejecting actor)
+    pub party: String,
+    pub ledger_host: String,
+    pub access_token: String,
+}
+
+/// List allocation requests that ask `params.party` to fund a leg and are
+/// still within their allocation window.
+///
+/// # Errors
+///
+/// Returns an error string if the active-contract query fails.
+pub async fn list_incoming(
+    params: allocation::ListParams,
+) -> Result<Vec<AllocationRequest>, String> {
+    let party = params.party.clone();
+    Ok(allocation::list_allocation_requests(params)
+        .await?
+        .into_iter()
+        .filter(|request| {
+            request.status == AllocationStatus::AwaitingAllocation
+                && request.legs.iter().any(|leg| leg.sender == party)
+        })
+        .collect())
+}
+
+/// Pick the leg `party` should allocate: `leg_id` if given, else the only leg
+/// `party` sends.
+fn our_leg<'a>(
+    request: &'a AllocationRequest,
+    party: &str,
+    leg_id: Option<&str>,
+) -> Result<&'a Leg, String> {
+    let leg = match leg_id {
+        Some(leg_id) => request
+            .legs
+            .iter()
+            .find(|leg| leg.leg_id == leg_id)
+            .ok_or_else(|| format!("Allocation request has no leg '{}'", leg_id))?,
+        None => {
+            let ours: Vec<&Leg> = request.legs.iter().filter(|l| l.sender == party).collect();
+            match ours.as_slice() {
+                [leg] => *leg,
+                [] => return Err(format!("No leg in the request is sent by {}", party)),
+                _ => {
+                    return Err(format!(
+                        "{} legs are sent by {}; choose one with leg_id",
+                        ours.len(),
+                        party
+                    ));
+                }
+            }
+        }
+    };
+
+    if leg.sender != party {
+        return Err(format!(
+            "Leg '{}' is sent by {}, not {}",
+            leg.leg_id, leg.sender, party
+        ));
+    }
+    Ok(leg)
+}
+
+/// Accept an allocation request by allocating our leg.
+///
+/// Builds the `AllocationSpecification` for the leg from the request and calls
+/// [`allocation::allocate`]. The leg's instrument must be administered by
+/// `decentralized_party_id` (i.e. CBTC).
+///
+/// # Errors
+///
+/// Returns an error string if the leg can't be determined, is not CBTC, the
+/// allocation window has passed, or the allocation fails.
+pub async fn accept(params: AcceptParams) -> Result<(), String> {
+    if params.request.status != AllocationStatus::AwaitingAllocation {
+        return Err(format!(
+            "Allocation request {} is no longer open for allocation ({:?})",
+            params.request.contract_id, params.request.status
+        ));
+    }
+
+    let leg = our_leg(&params.request, &params.party, params.leg_id.as_deref())?;
+    if leg.instrument_admin != params.decentralized_party_id {
+        return Err(format!(
+            "Leg '{}' is for instrument {}/{}, not CBTC",
+            leg.leg_id, leg.instrument_admin, leg.instrument_id
+        ));
+    }
+
+    log::debug!(
+        "Allocating leg '{}' of settlement {} ({} {})",
+        leg.leg_id,
+        params.request.settlement.settlement_ref,
+        leg.amount,
+        leg.instrument_id
+    );
+
+    allocation::allocate(allocation::Params {
+        allocation: allocation::specification(&params.request.settlement, leg),
+        requested_at: chrono::Utc::now().to_rfc3339(),
+        input_holding_cids: params.input_holding_cids,
+        ledger_host: params.ledger_host,
+        access_token: params.access_token,
+        registry_url: params.registry_url,
+        decentralized_party_id: params.decentralized_party_id,
+    })
+    .await
+}
+
+/// Build the `AllocationRequest_Reject` exercise command.
+fn build_reject_command(
+    request_contract_id: String,
+    actor: &str,
+) -> common::submission::ExerciseCommand {
+    common::submission::ExerciseCommand {
+        exercise_command: common::submission::ExerciseCommandData {
+            template_id: allocation::INTERFACE_ALLOCATION_REQUEST.to_string(),
+            contract_id: request_contract_id,
+            choice: "AllocationRequest_Reject".to_string(),
+            choice_argument: common::submission::ChoiceArgumentsVariations::Generic(
+                serde_json::json!({
+                    "actor": actor,
+                    "extraArgs": {
+                        "context": { "values": {} },
+                        "meta": { "values": {} }
+                    }
+                }),
+            ),
+        },
+    }
+}
+
+/// Reject an allocation request (`AllocationRequest_Reject`) as `params.party`.
+///
+/// # Errors
+///
+/// Returns an error string if the ledger submission fails.
+pub async fn reject(params: RejectParams) -> Result<(), String> {
+    let exercise_command = build_reject_command(params.request_contract_id, &params.party);
+
+    let submission_request = common::submission::Submission {
+        act_as: vec![params.party],
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts: vec![],
+        commands: vec![common::submission::Command::ExerciseCommand(
+            exercise_command,
+        )],
+        ..Default::default()
+    };
+
+    ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: params.ledger_host,
+        access_token: params.access_token,
+        request: submission_request,
+    })
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::decimal::DamlDecimal;
+    use common::submission::ChoiceArgumentsVariations;
+
+    fn leg(leg_id: &str, sender: &str, receiver: &str, amount: &str, id: &str) -> Leg {
+        Leg {
+            leg_id: leg_id.to_string(),
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            amount: DamlDecimal::parse(amount).unwrap(),
+            instrument_admin: "cbtc-network::1220".to_string(),
+            instrument_id: id.to_string(),
+            meta: common::allocation::Metadata::default(),
+        }
+    }
+
+    fn request(legs: Vec<Leg>) -> AllocationRequest {
+        AllocationRequest {
+            contract_id: "00req".to_string(),
+            settlement: allocation::Settlement {
+                executor: "venue::1220".to_string(),
+                settlement_ref: "trade-42".to_string(),
+                settlement_ref_cid: Some("00ref".to_string()),
+                requested_at: "2026-01-01T00:00:00Z".to_string(),
+                allocate_before: "2026-01-02T00:00:00Z".to_string(),
+                settle_before: "2026-01-03T00:00:00Z".to_string(),
+                meta: common::allocation::Metadata::default(),
+            },
+            legs,
+            status: AllocationStatus::AwaitingAllocation,
+        }
+    }
+
+    #[test]
+    fn picks_the_only_leg_we_send() {
+        let request = request(vec![
+            leg("leg-0", "alice::1220", "bob::1220", "0.5", "CBTC"),
+            leg("leg-1", "bob::1220", "alice::1220", "100", "USDC"),
+        ]);
+        assert_eq!(
+            our_leg(&request, "alice::1220", None).unwrap().leg_id,
+            "leg-0"
+        );
+    }
+
+    #[test]
+    fn rejects_leg_sent_by_someone_else() {
+        let request = request(vec![
+            leg("leg-0", "alice::1220", "bob::1220", "0.5", "CBTC"),
+            leg("leg-1", "bob::1220", "alice::1220", "100", "USDC"),
+        ]);
+        let err = our_leg(&request, "alice::1220", Some("leg-1")).unwrap_err();
+        assert!(err.contains("sent by bob::1220"), "unexpected error: {err}");
+        assert!(our_leg(&request, "carol::1220", None).is_err());
+    }
+
+    #[test]
+    fn ambiguous_legs_need_a_leg_id() {
+        let request = request(vec![
+            leg("leg-0", "alice::1220", "bob::1220", "0.5", "CBTC"),
+            leg("leg-1", "alice::1220", "carol::1220", "0.1", "CBTC"),
+        ]);
+        assert!(our_leg(&request, "alice::1220", None).is_err());
+        assert_eq!(
+            our_leg(&request, "alice::1220", Some("leg-1"))
+                .unwrap()
+                .leg_id,
+            "leg-1"
+        );
+    }
+
+    #[test]
+    fn specification_mirrors_request_and_leg() {
+        let request = request(vec![leg(
+            "leg-0",
+            "alice::1220",
+            "bob::1220",
+            "0.5",
+            "CBTC",
+        )]);
+        let spec = allocation::specification(&request.settlement, &request.legs[0]);
+
+        assert_eq!(spec.settlement.executor, "venue::1220");
+        assert_eq!(spec.settlement.settlement_ref.id, "trade-42");
+        assert_eq!(spec.settlement.settlement_ref.cid.as_deref(), Some("00ref"));
+        assert_eq!(spec.settlement.allocate_before, "2026-01-02T00:00:00Z");
+        assert_eq!(spec.transfer_leg_id, "leg-0");
+        assert_eq!(spec.transfer_leg.sender, "alice::1220");
+        assert_eq!(spec.transfer_leg.receiver, "bob::1220");
+        assert_eq!(spec.transfer_leg.instrument_id.id, "CBTC");
+    }
+
+    #[test]
+    fn reject_command_names_actor() {
+        let command = build_reject_command("00req".to_string(), "alice::1220");
+        assert_eq!(command.exercise_command.choice, "AllocationRequest_Reject");
+        assert_eq!(command.exercise_command.contract_id, "00req");
+        match command.exercise_command.choice_argument {
+            ChoiceArgumentsVariations::Generic(args) => {
+                assert_eq!(args["actor"], "alice::1220");
+            }
+            _ => panic!("expected Generic choice argument"),
+        }
+    }
+}
@@ -81,6 +81,7 @@ pub async fn submit_from_csv(params: Params) -> Result<(), String> {
         keycloak_url: params.keycloak_url,
         reference_base: params.reference_base,
         on_transfer_complete: None,
+        preflight: None,
     })
     .await?;
 
@@ -108,16 +109,13 @@ pub async fn submit_from_csv(params: Params) -> Result<(), String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::password_url;
-    use std::env;
+    use crate::utils::test_env::TestEnv;
     use std::io::Write;
 
     #[tokio::test]
     async fn test_batch_from_csv() {
-        dotenvy::dotenv().ok();
-
-        let receiver =
-            env::var("LIB_TEST_RECEIVER_PARTY_ID").expect("LIB_TEST_RECEIVER_PARTY_ID must be set");
+        let env = TestEnv::load().await;
+        let receiver = env.receiver();
 
         // Create a temporary CSV file
         let csv_content = format!(
@@ -136,25 +134,18 @@ mod tests {
         // Run batch distribution (authentication handled internally)
         let batch_params = Params {
             csv_path: temp_path.to_string(),
-            sender: env::var("PARTY_ID").expect("PARTY_ID must be set"),
+            sender: env.party(),
             instrument_id: common::transfer::InstrumentId {
-                admin: common::consts::DEVNET_DECENTRALIZED_PARTY_ID.to_string(),
+                admin: env.decentralized_party_id(),
                 id: "CBTC".to_string(),
             },
-            ledger_host: env::var("LEDGER_HOST").expect("LEDGER_HOST must be set"),
-            registry_url: env::var("REGISTRY_URL").expect("REGISTRY_URL must be set"),
-            decentralized_party_id: env::var("DECENTRALIZED_PARTY_ID")
-                .expect("DECENTRALIZED_PARTY_ID must be set"),
-            keycloak_client_id: env::var("KEYCLOAK_CLIENT_ID")
-                .expect("KEYCLOAK_CLIENT_ID must be set"),
-            keycloak_username: env::var("KEYCLOAK_USERNAME")
-                .expect("KEYCLOAK_USERNAME must be set"),
-            keycloak_password: env::var("KEYCLOAK_PASSWORD")
-                .expect("KEYCLOAK_PASSWORD must be set"),
-            keycloak_url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
+            ledger_host: env.ledger_host(),
+            registry_url: env.registry_url(),
+            decentralized_party_id: env.decentralized_party_id(),
+            keycloak_client_id: env.keycloak_client_id(),
+            keycloak_username: env.keycloak_username(),
+            keycloak_password: env.keycloak_password(),
+            keycloak_url: env.keycloak_url(),
             reference_base: Some(format!("batch-test-{}", chrono::Utc::now().timestamp())),
         };
 
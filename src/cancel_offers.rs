@@ -58,6 +58,8 @@ pub struct WithdrawResult {
     pub amount: Option<String>,
     pub receiver: Option<String>,
     pub error: Option<String>,
+    /// Classification of `error`, so expired and already-archived offers can be told apart
+    pub error_kind: Option<crate::utils::OfferErrorKind>,
 }
 
 /// Result of withdrawing all pending transfers
@@ -203,31 +205,19 @@ async fn submit_withdraws(
     .map(|_| ())
 }
 
-/// Record one offer's outcome into the running result tally.
-fn record_withdraw(
-    results: &mut Vec<WithdrawResult>,
-    successful_count: &mut usize,
-    failed_count: &mut usize,
-    contract_id: &str,
-    outcome: Result<(), String>,
-) {
-    let (success, error) = match outcome {
-        Ok(()) => {
-            *successful_count += 1;
-            (true, None)
-        }
-        Err(e) => {
-            *failed_count += 1;
-            (false, Some(e))
-        }
-    };
-    results.push(WithdrawResult {
-        success,
-        contract_id: contract_id.to_string(),
-        amount: None,
-        receiver: None,
-        error,
-    });
+impl crate::utils::OfferOutcome for WithdrawResult {
+    fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    fn mark_success(&mut self) {
+        self.success = true;
+    }
+
+    fn mark_failed(&mut self, error: String) {
+        self.error_kind = Some(crate::utils::OfferErrorKind::classify(&error));
+        self.error = Some(error);
+    }
 }
 
 /// Withdraw a specific set of CBTC transfer offers by contract id, batched.
@@ -266,47 +256,34 @@ pub async fn withdraw_batch(params: WithdrawBatchParams) -> Result<WithdrawAllRe
     .await?;
 
     const BATCH_SIZE: usize = 5;
-    let mut results = Vec::new();
-    let mut successful_count = 0;
-    let mut failed_count = 0;
-
-    for batch in params.contract_ids.chunks(BATCH_SIZE) {
-        let outcome = submit_withdraws(
-            batch,
-            &params.sender_party,
-            &params.ledger_host,
-            &params.access_token,
-            &withdraw_context,
-        )
-        .await;
-
-        match outcome {
-            Ok(()) => {
-                for cid in batch {
-                    record_withdraw(&mut results, &mut successful_count, &mut failed_count, cid, Ok(()));
-                }
-            }
-            // A Canton transaction is atomic: one non-withdrawable offer fails the
-            // whole batch. Retry each offer individually so the good ones still get
-            // cancelled and the offender(s) fail in isolation with their own error.
-            Err(_) if batch.len() > 1 => {
-                for cid in batch {
-                    let single = submit_withdraws(
-                        std::slice::from_ref(cid),
-                        &params.sender_party,
-                        &params.ledger_host,
-                        &params.access_token,
-                        &withdraw_context,
-                    )
-                    .await;
-                    record_withdraw(&mut results, &mut successful_count, &mut failed_count, cid, single);
-                }
-            }
-            Err(e) => {
-                record_withdraw(&mut results, &mut successful_count, &mut failed_count, &batch[0], Err(e));
+    let pending = params
+        .contract_ids
+        .iter()
+        .map(|cid| WithdrawResult {
+            success: false,
+            contract_id: cid.clone(),
+            amount: None,
+            receiver: None,
+            error: None,
+            error_kind: None,
+        })
+        .collect();
+    let (results, successful_count, failed_count) =
+        crate::utils::submit_offer_batches(pending, BATCH_SIZE, |contract_ids| {
+            let params = &params;
+            let withdraw_context = &withdraw_context;
+            async move {
+                submit_withdraws(
+                    &contract_ids,
+                    &params.sender_party,
+                    &params.ledger_host,
+                    &params.access_token,
+                    withdraw_context,
+                )
+                .await
             }
-        }
-    }
+        })
+        .await;
 
     Ok(WithdrawAllResult {
         results,
@@ -478,6 +455,7 @@ pub async fn withdraw_all(params: WithdrawAllParams) -> Result<WithdrawAllResult
                 amount,
                 receiver,
                 error: None,
+                error_kind: None,
             });
         }
 
@@ -528,6 +506,7 @@ pub async fn withdraw_all(params: WithdrawAllParams) -> Result<WithdrawAllResult
                 // Mark this batch's results as failed
                 for (idx_in_batch, result) in batch_results.iter_mut().enumerate() {
                     result.error = Some(e.clone());
+                    result.error_kind = Some(crate::utils::OfferErrorKind::classify(&e));
                     failed_count += 1;
 
                     let short_id = if result.contract_id.len() > 16 {
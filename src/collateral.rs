@@ -0,0 +1,879 @@
+//! Pledge CBTC as collateral through the utility collateral app
+//! (`utility-collateral-app-v1`).
+//!
+//! Two parties share a `CollateralAgreement` (with a companion
+//! `CollateralState` tracking pledged positions). Moving collateral is a
+//! three-step DvP driven by the moving party:
+//!
+//! 1. `UserService_TransferCollateral` creates an `InstructedCollateral`, which
+//!    is a token-standard allocation request executed by the app operator.
+//! 2. The moving party allocates its CBTC leg via [`allocation::allocate`].
+//! 3. `UserService_ExecuteTransfer` executes the allocation and updates the
+//!    agreement's `CollateralState`.
+//!
+//! [`pledge`] and [`top_up`] move CBTC from the pledgor to the secured party;
+//! [`release`] is run by the secured party and moves it back.
+
+use crate::allocation::{self, Leg, Settlement};
+use crate::mint_redeem::constants::CBTC_INSTRUMENT_ID;
+use common::decimal::DamlDecimal;
+use ledger::active_contracts;
+use ledger::common::{TemplateFilter, TemplateFilterValue, TemplateIdentifierFilter};
+use ledger::ledger_end;
+use ledger::models::{JsActiveContract, JsSubmitAndWaitForTransactionResponse};
+use registry::allocation_context::AllocationChoice;
+use serde_json::{Value, json};
+
+// Template IDs for collateral app contracts
+const USER_SERVICE_TEMPLATE_ID: &str =
+    "#utility-collateral-app-v1:Utility.Collateral.App.Service.User:UserService";
+const COLLATERAL_AGREEMENT_TEMPLATE_ID: &str =
+    "#utility-collateral-app-v1:Utility.Collateral.App.Model.Collateral:CollateralAgreement";
+const COLLATERAL_STATE_TEMPLATE_ID: &str =
+    "#utility-collateral-app-v1:Utility.Collateral.App.Model.State:CollateralState";
+
+/// Default time the moving party has to allocate its CBTC
+pub const DEFAULT_ALLOCATE_WITHIN: chrono::Duration = chrono::Duration::minutes(10);
+
+/// Default time until the instructed transfer can no longer be executed
+pub const DEFAULT_SETTLE_WITHIN: chrono::Duration = chrono::Duration::minutes(20);
+
+/// Leg ID the collateral app gives the only position of an instruction
+const SINGLE_POSITION_LEG_ID: &str = "1";
+
+/// A token-standard instrument (admin party and instrument ID).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instrument {
+    pub admin: String,
+    pub id: String,
+}
+
+/// An active collateral agreement between two parties.
+#[derive(Debug, Clone)]
+pub struct CollateralAgreement {
+    pub contract_id: String,
+    /// Agreement id
+    pub id: String,
+    pub party_a: String,
+    pub party_b: String,
+    /// Collateral app operator (executes instructed transfers)
+    pub operator: String,
+    /// Instruments that may be pledged under the agreement
+    pub eligible_instruments: Vec<Instrument>,
+}
+
+/// A pledged collateral position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pledge {
+    /// Agreement the position is held under
+    pub agreement_id: String,
+    pub pledgor: String,
+    pub secured_party: String,
+    pub instrument: Instrument,
+    pub amount: DamlDecimal,
+}
+
+/// The pledged positions of one agreement.
+#[derive(Debug, Clone)]
+pub struct CollateralState {
+    pub contract_id: String,
+    pub agreement_id: String,
+    pub party_a: String,
+    pub party_b: String,
+    pub operator: String,
+    pub positions: Vec<Pledge>,
+}
+
+/// Parameters for listing agreements and pledges.
+pub struct ListParams {
+    pub party: String,
+    pub ledger_host: String,
+    pub access_token: String,
+}
+
+/// Parameters for moving CBTC collateral under an agreement.
+pub struct Params {
+    /// The party sending CBTC: the pledgor for [`pledge`] / [`top_up`], the
+    /// secured party for [`release`]
+    pub party: String,
+    pub agreement_id: String,
+    pub amount: DamlDecimal,
+    /// Holdings to fund the allocation. If empty, holdings are auto-selected.
+    pub input_holding_cids: Vec<String>,
+    pub allocate_within: chrono::Duration,
+    pub settle_within: chrono::Duration,
+    pub ledger_host: String,
+    pub access_token: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+}
+
+/// Result of a collateral movement.
+#[derive(Debug, Clone)]
+pub struct CollateralTransferResult {
+    /// Reference of the instructed transfer (unique per movement)
+    pub reference: String,
+    pub instructed_collateral_cid: String,
+    /// The executed allocation
+    pub allocation_cid: String,
+}
+
+/// The kind of collateral movement, used to validate it against the current state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Movement {
+    Pledge,
+    TopUp,
+    Release,
+}
+
+fn str_field(args: &Value, field: &str) -> Result<String, String> {
+    args.get(field)
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Missing '{}' field", field))
+}
+
+fn parse_instrument(value: &Value) -> Result<Instrument, String> {
+    Ok(Instrument {
+        admin: str_field(value, "admin")?,
+        id: str_field(value, "id")?,
+    })
+}
+
+/// Parse a `CollateralAgreement` create argument.
+fn parse_agreement(contract_id: &str, args: &Value) -> Result<CollateralAgreement, String> {
+    let eligible_instruments = args
+        .pointer("/terms/eligibleCollateral/eligibleInstruments")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing 'terms.eligibleCollateral.eligibleInstruments' field")?
+        .iter()
+        .map(|eligible| {
+            eligible
+                .get("instrument")
+                .ok_or_else(|| "Missing 'instrument' field".to_string())
+                .and_then(parse_instrument)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(CollateralAgreement {
+        contract_id: contract_id.to_string(),
+        id: str_field(args, "id")?,
+        party_a: str_field(args, "partyA")?,
+        party_b: str_field(args, "partyB")?,
+        operator: str_field(args, "operator")?,
+        eligible_instruments,
+    })
+}
+
+/// Parse a `CollateralState` create argument.
+fn parse_state(contract_id: &str, args: &Value) -> Result<CollateralState, String> {
+    let agreement_id = str_field(args, "id")?;
+    let positions = args
+        .get("collateralPositions")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing 'collateralPositions' field")?
+        .iter()
+        .map(|position| {
+            let amount = str_field(position, "amount")?;
+            Ok(Pledge {
+                agreement_id: agreement_id.clone(),
+                pledgor: str_field(position, "pledgor")?,
+                secured_party: str_field(position, "securedParty")?,
+                instrument: parse_instrument(
+                    position
+                        .get("instrument")
+                        .ok_or("Missing 'instrument' field")?,
+                )?,
+                amount: DamlDecimal::parse(&amount)
+                    .map_err(|e| format!("Invalid amount '{}': {}", amount, e))?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(CollateralState {
+        contract_id: contract_id.to_string(),
+        agreement_id,
+        party_a: str_field(args, "partyA")?,
+        party_b: str_field(args, "partyB")?,
+        operator: str_field(args, "operator")?,
+        positions,
+    })
+}
+
+/// Active contracts of `template_id` visible to `party`.
+async fn active_by_template(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    template_id: &str,
+) -> Result<Vec<JsActiveContract>, String> {
+    let ledger_end_response = ledger_end::get(ledger_end::Params {
+        access_token: access_token.to_string(),
+        ledger_host: ledger_host.to_string(),
+    })
+    .await?;
+
+    let filter =
+        ledger::common::IdentifierFilter::TemplateIdentifierFilter(TemplateIdentifierFilter {
+            template_filter: TemplateFilter {
+                value: TemplateFilterValue {
+                    template_id: Some(template_id.to_string()),
+                    include_created_event_blob: false,
+                },
+            },
+        });
+
+    active_contracts::get_by_party(active_contracts::Params {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        filter,
+        access_token: access_token.to_string(),
+        ledger_end: ledger_end_response.offset,
+        unknown_contract_entry_handler: None,
+    })
+    .await
+}
+
+/// Parse every contract with `parse`, skipping (and logging) the ones that fail.
+fn parse_contracts<T>(
+    contracts: &[JsActiveContract],
+    kind: &str,
+    parse: fn(&str, &Value) -> Result<T, String>,
+) -> Vec<T> {
+    contracts
+        .iter()
+        .filter_map(|contract| {
+            let cid = &contract.created_event.contract_id;
+            match contract
+                .created_event
+                .create_argument
+                .as_ref()
+                .ok_or_else(|| "missing createArgument".to_string())
+                .and_then(|args| parse(cid, args))
+            {
+                Ok(parsed) => Some(parsed),
+                Err(e) => {
+                    log::warn!("Skipping {} {}: {}", kind, cid, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// List the collateral agreements `params.party` is a party to.
+///
+/// # Errors
+///
+/// Returns an error string if the active-contract query fails.
+pub async fn list_agreements(params: ListParams) -> Result<Vec<CollateralAgreement>, String> {
+    let contracts = active_by_template(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        COLLATERAL_AGREEMENT_TEMPLATE_ID,
+    )
+    .await?;
+    Ok(parse_contracts(
+        &contracts,
+        "collateral agreement",
+        parse_agreement,
+    ))
+}
+
+/// List the collateral states of the agreements `params.party` is a party to.
+async fn list_states(params: &ListParams) -> Result<Vec<CollateralState>, String> {
+    let contracts = active_by_template(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        COLLATERAL_STATE_TEMPLATE_ID,
+    )
+    .await?;
+    Ok(parse_contracts(&contracts, "collateral state", parse_state))
+}
+
+/// List the active pledges where `params.party` is the pledgor or the secured party.
+///
+/// # Errors
+///
+/// Returns an error string if the active-contract query fails.
+pub async fn list_pledges(params: ListParams) -> Result<Vec<Pledge>, String> {
+    Ok(list_states(&params)
+        .await?
+        .into_iter()
+        .flat_map(|state| state.positions)
+        .filter(|p| p.pledgor == params.party || p.secured_party == params.party)
+        .collect())
+}
+
+/// Pledge CBTC to the counterparty of an agreement.
+///
+/// # Errors
+///
+/// Returns an error string if the agreement isn't found, CBTC isn't eligible
+/// under it, or any of the three submissions fails.
+pub async fn pledge(params: Params) -> Result<CollateralTransferResult, String> {
+    transfer_collateral(Movement::Pledge, params).await
+}
+
+/// Add CBTC to an existing pledge.
+///
+/// # Errors
+///
+/// As [`pledge`], and also if `params.party` has no CBTC pledged under the
+/// agreement yet.
+pub async fn top_up(params: Params) -> Result<CollateralTransferResult, String> {
+    transfer_collateral(Movement::TopUp, params).await
+}
+
+/// Release pledged CBTC back to the pledgor. Run as the secured party, which
+/// holds the pledged CBTC and sends it back.
+///
+/// # Errors
+///
+/// As [`pledge`], and also if `params.party` is not secured by a CBTC pledge
+/// of at least `params.amount` under the agreement.
+pub async fn release(params: Params) -> Result<CollateralTransferResult, String> {
+    transfer_collateral(Movement::Release, params).await
+}
+
+/// Check a movement of `amount` of `instrument` by `party` against the
+/// agreement and its current state, returning the receiving counterparty.
+fn check_movement(
+    movement: Movement,
+    agreement: &CollateralAgreement,
+    state: &CollateralState,
+    party: &str,
+    instrument: &Instrument,
+    amount: DamlDecimal,
+) -> Result<String, String> {
+    let counterparty = if party == agreement.party_a {
+        agreement.party_b.clone()
+    } else if party == agreement.party_b {
+        agreement.party_a.clone()
+    } else {
+        return Err(format!(
+            "{} is not a party to collateral agreement {}",
+            party, agreement.id
+        ));
+    };
+
+    if amount <= DamlDecimal::ZERO {
+        return Err("Collateral amount must be positive".to_string());
+    }
+    if !agreement.eligible_instruments.contains(instrument) {
+        return Err(format!(
+            "{}/{} is not eligible collateral under agreement {}",
+            instrument.admin, instrument.id, agreement.id
+        ));
+    }
+
+    let position = state.positions.iter().find(|p| &p.instrument == instrument);
+    match movement {
+        Movement::Pledge => {}
+        Movement::TopUp => {
+            if !position.is_some_and(|p| p.pledgor == party) {
+                return Err(format!(
+                    "{} has no {} pledged under agreement {}; use pledge",
+                    party, instrument.id, agreement.id
+                ));
+            }
+        }
+        Movement::Release => match position {
+            Some(p) if p.secured_party == party && p.amount >= amount => {}
+            Some(p) if p.secured_party == party => {
+                return Err(format!(
+                    "Cannot release {} {}: only {} is pledged",
+                    amount, instrument.id, p.amount
+                ));
+            }
+            _ => {
+                return Err(format!(
+                    "{} holds no {} pledge under agreement {}",
+                    party, instrument.id, agreement.id
+                ));
+            }
+        },
+    }
+
+    Ok(counterparty)
+}
+
+/// Find the collateral app `UserService` contract ID for `party`.
+async fn find_user_service(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+) -> Result<String, String> {
+    active_by_template(ledger_host, party, access_token, USER_SERVICE_TEMPLATE_ID)
+        .await?
+        .into_iter()
+        .find(|contract| {
+            contract
+                .created_event
+                .create_argument
+                .as_ref()
+                .and_then(|args| args.get("user"))
+                .and_then(|v| v.as_str())
+                == Some(party)
+        })
+        .map(|contract| contract.created_event.contract_id)
+        .ok_or_else(|| {
+            format!(
+                "No collateral app UserService contract found for party {}",
+                party
+            )
+        })
+}
+
+/// The settlement the collateral app derives from an instructed transfer:
+/// executed by the operator, referenced as `<agreement id>;<reference>`.
+fn instructed_settlement(
+    agreement: &CollateralAgreement,
+    reference: &str,
+    created_at: &str,
+    allocate_before: &str,
+    settle_before: &str,
+) -> Settlement {
+    Settlement {
+        executor: agreement.operator.clone(),
+        settlement_ref: format!("{};{}", agreement.id, reference),
+        settlement_ref_cid: None,
+        requested_at: created_at.to_string(),
+        allocate_before: allocate_before.to_string(),
+        settle_before: settle_before.to_string(),
+        meta: common::allocation::Metadata::default(),
+    }
+}
+
+/// Exercise a `UserService` choice as `party` and return the raw response.
+async fn exercise_user_service(
+    params: &Params,
+    user_service_cid: &str,
+    choice: &str,
+    argument: Value,
+    disclosed_contracts: Vec<common::transfer::DisclosedContract>,
+) -> Result<String, String> {
+    let exercise_command = common::submission::ExerciseCommand {
+        exercise_command: common::submission::ExerciseCommandData {
+            template_id: USER_SERVICE_TEMPLATE_ID.to_string(),
+            contract_id: user_service_cid.to_string(),
+            choice: choice.to_string(),
+            choice_argument: common::submission::ChoiceArgumentsVariations::Generic(argument),
+        },
+    };
+
+    let submission_request = common::submission::Submission {
+        act_as: vec![params.party.clone()],
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts,
+        commands: vec![common::submission::Command::ExerciseCommand(
+            exercise_command,
+        )],
+        ..Default::default()
+    };
+
+    ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+        request: submission_request,
+    })
+    .await
+}
+
+/// Extract the `InstructedCollateral` created by `UserService_TransferCollateral`.
+fn parse_instructed_collateral_cid(
+    response: &JsSubmitAndWaitForTransactionResponse,
+) -> Result<String, String> {
+    response
+        .transaction
+        .events
+        .iter()
+        .filter_map(crate::event_helpers::as_created_event)
+        .find(|created| {
+            created
+                .template_id
+                .ends_with(":Utility.Collateral.App.Model.Collateral:InstructedCollateral")
+        })
+        .map(|created| created.contract_id.clone())
+        .ok_or_else(|| {
+            "No InstructedCollateral contract was created in the transaction".to_string()
+        })
+}
+
+/// Instruct, allocate and execute one CBTC collateral movement.
+async fn transfer_collateral(
+    movement: Movement,
+    params: Params,
+) -> Result<CollateralTransferResult, String> {
+    let list_params = ListParams {
+        party: params.party.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+    };
+    let states = list_states(&list_params).await?;
+    let agreement = list_agreements(list_params)
+        .await?
+        .into_iter()
+        .find(|a| a.id == params.agreement_id)
+        .ok_or_else(|| format!("Collateral agreement {} not found", params.agreement_id))?;
+    let state = states
+        .into_iter()
+        .find(|s| s.agreement_id == agreement.id && s.operator == agreement.operator)
+        .ok_or_else(|| format!("Collateral state for agreement {} not found", agreement.id))?;
+
+    let instrument = Instrument {
+        admin: params.decentralized_party_id.clone(),
+        id: CBTC_INSTRUMENT_ID.to_string(),
+    };
+    let counterparty = check_movement(
+        movement,
+        &agreement,
+        &state,
+        &params.party,
+        &instrument,
+        params.amount,
+    )?;
+    let user_service_cid =
+        find_user_service(&params.ledger_host, &params.party, &params.access_token).await?;
+
+    // Daml compares the allocation's settlement times with the instruction's,
+    // so use whole seconds to keep both representations identical.
+    let now = chrono::Utc::now();
+    let at =
+        |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let created_at = at(now);
+    let allocate_before = at(now + params.allocate_within);
+    let settle_before = at(now + params.settle_within);
+    let reference = uuid::Uuid::new_v4().to_string();
+
+    log::debug!(
+        "{:?} of {} CBTC from {} to {} under agreement {} (ref {})",
+        movement,
+        params.amount,
+        params.party,
+        counterparty,
+        agreement.id,
+        reference
+    );
+
+    // 1. Instruct the transfer
+    let response_raw = exercise_user_service(
+        &params,
+        &user_service_cid,
+        "UserService_TransferCollateral",
+        json!({
+            "cid": agreement.contract_id,
+            "positions": [{
+                "instrument": { "admin": instrument.admin, "id": instrument.id },
+                "amount": params.amount.to_string(),
+            }],
+            "reference": reference,
+            "createdAt": created_at,
+            "allocateBefore": allocate_before,
+            "settleBefore": settle_before,
+        }),
+        vec![],
+    )
+    .await?;
+    let response: JsSubmitAndWaitForTransactionResponse = serde_json::from_str(&response_raw)
+        .map_err(|e| format!("Failed to parse submit response: {}", e))?;
+    let instructed_collateral_cid = parse_instructed_collateral_cid(&response)?;
+
+    // 2. Allocate our leg
+    let settlement = instructed_settlement(
+        &agreement,
+        &reference,
+        &created_at,
+        &allocate_before,
+        &settle_before,
+    );
+    let leg = Leg {
+        leg_id: SINGLE_POSITION_LEG_ID.to_string(),
+        sender: params.party.clone(),
+        receiver: counterparty,
+        amount: params.amount,
+        instrument_admin: instrument.admin.clone(),
+        instrument_id: instrument.id.clone(),
+        meta: common::allocation::Metadata::default(),
+    };
+    allocation::allocate(allocation::Params {
+        allocation: allocation::specification(&settlement, &leg),
+        requested_at: created_at.clone(),
+        input_holding_cids: params.input_holding_cids.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+        registry_url: params.registry_url.clone(),
+        decentralized_party_id: params.decentralized_party_id.clone(),
+    })
+    .await
+    .map_err(|e| {
+        format!(
+            "Collateral transfer {} instructed but allocation failed: {}",
+            reference, e
+        )
+    })?;
+
+    let allocation_cid = allocation::list_allocations(allocation::ListParams {
+        party: params.party.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?
+    .into_iter()
+    .find(|a| {
+        a.settlement.settlement_ref == settlement.settlement_ref && a.leg.leg_id == leg.leg_id
+    })
+    .map(|a| a.contract_id)
+    .ok_or_else(|| format!("Allocation for collateral transfer {} not found", reference))?;
+
+    // 3. Execute the transfer and update the collateral state
+    let (extra_args, disclosed_contracts) = allocation::extra_args_with_context(
+        AllocationChoice::ExecuteTransfer,
+        &allocation_cid,
+        &params.registry_url,
+        &params.decentralized_party_id,
+    )
+    .await?;
+    exercise_user_service(
+        &params,
+        &user_service_cid,
+        "UserService_ExecuteTransfer",
+        json!({
+            "cid": instructed_collateral_cid,
+            "allocations": [allocation_cid],
+            "executeTransferArgs": [extra_args],
+            "collateralStateCid": state.contract_id,
+        }),
+        disclosed_contracts,
+    )
+    .await
+    .map_err(|e| {
+        format!(
+            "Collateral transfer {} allocated but execution failed: {}",
+            reference, e
+        )
+    })?;
+
+    Ok(CollateralTransferResult {
+        reference,
+        instructed_collateral_cid,
+        allocation_cid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_fixtures::{created_event_value, transaction_response};
+
+    fn d(s: &str) -> DamlDecimal {
+        DamlDecimal::parse(s).unwrap()
+    }
+
+    fn cbtc() -> Instrument {
+        Instrument {
+            admin: "cbtc-network::1220".to_string(),
+            id: "CBTC".to_string(),
+        }
+    }
+
+    fn agreement() -> CollateralAgreement {
+        parse_agreement(
+            "00agr",
+            &json!({
+                "partyA": "desk::1220",
+                "partyB": "lender::1220",
+                "operator": "utility::1220",
+                "id": "csa-1",
+                "terms": { "eligibleCollateral": { "eligibleInstruments": [
+                    { "instrument": { "admin": "cbtc-network::1220", "id": "CBTC" } }
+                ]}}
+            }),
+        )
+        .unwrap()
+    }
+
+    fn state(positions: Value) -> CollateralState {
+        parse_state(
+            "00state",
+            &json!({
+                "partyA": "desk::1220",
+                "partyB": "lender::1220",
+                "operator": "utility::1220",
+                "id": "csa-1",
+                "collateralPositions": positions,
+            }),
+        )
+        .unwrap()
+    }
+
+    fn pledged(amount: &str) -> CollateralState {
+        state(json!([{
+            "pledgor": "desk::1220",
+            "securedParty": "lender::1220",
+            "instrument": { "admin": "cbtc-network::1220", "id": "CBTC" },
+            "amount": amount,
+        }]))
+    }
+
+    #[test]
+    fn parses_agreement_and_state() {
+        let agreement = agreement();
+        assert_eq!(agreement.id, "csa-1");
+        assert_eq!(agreement.operator, "utility::1220");
+        assert_eq!(agreement.eligible_instruments, vec![cbtc()]);
+
+        let state = pledged("1.5");
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions[0].agreement_id, "csa-1");
+        assert_eq!(state.positions[0].pledgor, "desk::1220");
+        assert_eq!(state.positions[0].amount, d("1.5"));
+    }
+
+    #[test]
+    fn pledge_goes_to_the_counterparty() {
+        let to = check_movement(
+            Movement::Pledge,
+            &agreement(),
+            &state(json!([])),
+            "desk::1220",
+            &cbtc(),
+            d("1"),
+        )
+        .unwrap();
+        assert_eq!(to, "lender::1220");
+    }
+
+    #[test]
+    fn rejects_outsiders_and_ineligible_instruments() {
+        let agreement = agreement();
+        let empty = state(json!([]));
+        assert!(
+            check_movement(
+                Movement::Pledge,
+                &agreement,
+                &empty,
+                "carol::1220",
+                &cbtc(),
+                d("1")
+            )
+            .is_err()
+        );
+        let other = Instrument {
+            admin: "other::1220".to_string(),
+            id: "CBTC".to_string(),
+        };
+        let err = check_movement(
+            Movement::Pledge,
+            &agreement,
+            &empty,
+            "desk::1220",
+            &other,
+            d("1"),
+        )
+        .unwrap_err();
+        assert!(err.contains("not eligible"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn top_up_needs_an_existing_pledge() {
+        let agreement = agreement();
+        assert!(
+            check_movement(
+                Movement::TopUp,
+                &agreement,
+                &state(json!([])),
+                "desk::1220",
+                &cbtc(),
+                d("1")
+            )
+            .is_err()
+        );
+        assert!(
+            check_movement(
+                Movement::TopUp,
+                &agreement,
+                &pledged("1"),
+                "desk::1220",
+                &cbtc(),
+                d("1")
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn release_is_by_the_secured_party_up_to_the_pledged_amount() {
+        let agreement = agreement();
+        let state = pledged("1.5");
+        let to = check_movement(
+            Movement::Release,
+            &agreement,
+            &state,
+            "lender::1220",
+            &cbtc(),
+            d("1.5"),
+        )
+        .unwrap();
+        assert_eq!(to, "desk::1220");
+
+        let err = check_movement(
+            Movement::Release,
+            &agreement,
+            &state,
+            "lender::1220",
+            &cbtc(),
+            d("2"),
+        )
+        .unwrap_err();
+        assert!(err.contains("only 1.5"), "unexpected error: {err}");
+
+        assert!(
+            check_movement(
+                Movement::Release,
+                &agreement,
+                &state,
+                "desk::1220",
+                &cbtc(),
+                d("1")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn settlement_matches_the_apps_allocation_request() {
+        let settlement = instructed_settlement(
+            &agreement(),
+            "ref-1",
+            "2026-01-01T00:00:00Z",
+            "2026-01-01T00:10:00Z",
+            "2026-01-01T00:20:00Z",
+        );
+        assert_eq!(settlement.executor, "utility::1220");
+        assert_eq!(settlement.settlement_ref, "csa-1;ref-1");
+        assert_eq!(settlement.settlement_ref_cid, None);
+    }
+
+    #[test]
+    fn finds_instructed_collateral_in_response() {
+        let response = transaction_response(
+            "tx-1",
+            json!([created_event_value(
+                "pkg:Utility.Collateral.App.Model.Collateral:InstructedCollateral",
+                "00instr",
+                json!({}),
+            )]),
+        );
+        assert_eq!(
+            parse_instructed_collateral_cid(&response).unwrap(),
+            "00instr"
+        );
+
+        let empty = transaction_response("tx-2", json!([]));
+        assert!(parse_instructed_collateral_cid(&empty).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::active_contracts;
 use crate::mint_redeem::models::Holding;
 use common::decimal::DamlDecimal;
+use futures::StreamExt;
 use ledger::models::JsSubmitAndWaitForTransactionResponse;
 use std::collections::HashMap;
 use std::ops::Add;
@@ -62,6 +63,54 @@ pub struct ConsolidateParams {
     pub decentralized_party_id: String,
 }
 
+/// Default maximum number of holdings merged in one consolidation transaction.
+pub const DEFAULT_CHUNK_SIZE: usize = 25;
+
+/// Default maximum number of consolidation transactions in flight at once.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Callback invoked after each round of a chunked consolidation
+pub type RoundProgressCallback = dyn Fn(&RoundProgress) + Send + Sync;
+
+/// Progress report for one round of a chunked consolidation
+#[derive(Debug, Clone)]
+pub struct RoundProgress {
+    /// 1-based round number
+    pub round: usize,
+    /// The number of UTXOs at the start of the round
+    pub utxos_before: usize,
+    /// The number of UTXOs at the end of the round
+    pub utxos_after: usize,
+    /// Number of merge transactions that succeeded in this round
+    pub merges_succeeded: usize,
+    /// Errors from merge transactions that failed in this round
+    pub merge_errors: Vec<String>,
+}
+
+/// Parameters for consolidating UTXOs in bounded chunks
+pub struct ChunkedConsolidateParams {
+    /// The party ID whose UTXOs to consolidate
+    pub party: String,
+    /// The instrument ID (typically CBTC)
+    pub instrument_id: common::transfer::InstrumentId,
+    /// Stop once the party has at most this many UTXOs (values below 1 are treated as 1)
+    pub target_utxo_count: usize,
+    /// Maximum holdings merged per transaction (defaults to [`DEFAULT_CHUNK_SIZE`])
+    pub chunk_size: Option<usize>,
+    /// Maximum merge transactions in flight at once (defaults to [`DEFAULT_MAX_CONCURRENCY`])
+    pub max_concurrency: Option<usize>,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Access token for the party
+    pub access_token: String,
+    /// Registry URL
+    pub registry_url: String,
+    /// Decentralized party ID for CBTC
+    pub decentralized_party_id: String,
+    /// Optional callback invoked after each round
+    pub on_round_complete: Option<Box<RoundProgressCallback>>,
+}
+
 /// Get the count of CBTC UTXOs for a party.
 ///
 /// # Example
@@ -162,6 +211,33 @@ pub async fn consolidate_utxos(params: ConsolidateParams) -> Result<Vec<String>,
         return Err("Total amount to consolidate is zero".to_string());
     }
 
+    merge_holdings(
+        &params.party,
+        params.instrument_id,
+        input_holding_cids,
+        total_amount,
+        &params.ledger_host,
+        &params.access_token,
+        &params.registry_url,
+        &params.decentralized_party_id,
+    )
+    .await
+}
+
+/// Merge the given holdings into one via a self-transfer of their total amount.
+///
+/// Returns the resulting holding contract IDs.
+#[allow(clippy::too_many_arguments)]
+async fn merge_holdings(
+    party: &str,
+    instrument_id: common::transfer::InstrumentId,
+    input_holding_cids: Vec<String>,
+    total_amount: DamlDecimal,
+    ledger_host: &str,
+    access_token: &str,
+    registry_url: &str,
+    decentralized_party_id: &str,
+) -> Result<Vec<String>, String> {
     // Create metadata with the MergeSplit transaction kind
     let mut transfer_meta: HashMap<String, String> = HashMap::new();
     transfer_meta.insert(
@@ -175,10 +251,10 @@ pub async fn consolidate_utxos(params: ConsolidateParams) -> Result<Vec<String>,
 
     // Create a self-transfer to consolidate (sender == receiver)
     let transfer = common::transfer::Transfer {
-        sender: params.party.clone(),
-        receiver: params.party.clone(), // Self-transfer triggers consolidation
+        sender: party.to_string(),
+        receiver: party.to_string(), // Self-transfer triggers consolidation
         amount: total_amount,
-        instrument_id: params.instrument_id,
+        instrument_id,
         requested_at: chrono::Utc::now().to_rfc3339(),
         execute_before: chrono::Utc::now()
             .add(chrono::Duration::hours(5))
@@ -192,11 +268,11 @@ pub async fn consolidate_utxos(params: ConsolidateParams) -> Result<Vec<String>,
     // Get registry information for the transfer
     let additional_information =
         registry::transfer_factory::get(registry::transfer_factory::Params {
-            registry_url: params.registry_url,
-            decentralized_party_id: params.decentralized_party_id.clone(),
+            registry_url: registry_url.to_string(),
+            decentralized_party_id: decentralized_party_id.to_string(),
             request: registry::transfer_factory::Request {
                 choice_arguments: common::transfer_factory::ChoiceArguments {
-                    expected_admin: params.decentralized_party_id.clone(),
+                    expected_admin: decentralized_party_id.to_string(),
                     transfer: transfer.clone(),
                     extra_args: common::transfer_factory::ExtraArgs {
                         context: common::transfer_factory::Context {
@@ -220,7 +296,7 @@ pub async fn consolidate_utxos(params: ConsolidateParams) -> Result<Vec<String>,
             choice: "TransferFactory_Transfer".to_string(),
             choice_argument: common::submission::ChoiceArgumentsVariations::TransferFactory(
                 common::transfer_factory::ChoiceArguments {
-                    expected_admin: params.decentralized_party_id,
+                    expected_admin: decentralized_party_id.to_string(),
                     transfer: transfer.clone(),
                     extra_args: common::transfer_factory::ExtraArgs {
                         context: additional_information.choice_context.choice_context_data,
@@ -245,8 +321,8 @@ pub async fn consolidate_utxos(params: ConsolidateParams) -> Result<Vec<String>,
     };
 
     let response_raw = ledger::submit::wait_for_transaction(ledger::submit::Params {
-        ledger_host: params.ledger_host,
-        access_token: params.access_token,
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
         request: submission_request,
     })
     .await?;
@@ -298,10 +374,193 @@ fn parse_consolidate_response(
     Ok(result_cids)
 }
 
+/// Plan the merges for one round: the sizes of the chunks to merge, taken in
+/// order from the holdings sorted smallest first.
+///
+/// A chunk of `k` holdings reduces the count by `k - 1`, so only as many
+/// holdings as needed to reach `target` are merged; the last chunk is trimmed.
+fn plan_round(utxo_count: usize, chunk_size: usize, target: usize) -> Vec<usize> {
+    let chunk_size = chunk_size.max(2);
+    let mut excess = utxo_count.saturating_sub(target.max(1));
+    let mut remaining = utxo_count;
+    let mut chunks = Vec::new();
+
+    while excess > 0 && remaining >= 2 {
+        let size = chunk_size.min(excess + 1).min(remaining);
+        chunks.push(size);
+        excess -= size - 1;
+        remaining -= size;
+    }
+
+    chunks
+}
+
+/// Fetch the party's unlocked CBTC holdings, sorted by amount (smallest first).
+async fn fetch_unlocked_holdings(
+    party: &str,
+    ledger_host: &str,
+    access_token: &str,
+) -> Result<Vec<Holding>, String> {
+    let contracts = active_contracts::get(active_contracts::Params {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        access_token: access_token.to_string(),
+    })
+    .await?;
+
+    let mut holdings = contracts
+        .iter()
+        .filter(|c| !Holding::is_locked_in_contract(c))
+        .map(Holding::from_active_contract)
+        .collect::<Result<Vec<_>, _>>()?;
+    holdings.sort_by(|a, b| {
+        a.amount
+            .partial_cmp(&b.amount)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(holdings)
+}
+
+/// Consolidate CBTC UTXOs in bounded chunks until at most `target_utxo_count` remain.
+///
+/// Each round merges disjoint chunks of up to `chunk_size` holdings (smallest
+/// first) in concurrent self-transfers, forming a tree of merges: hundreds of
+/// UTXOs reach the target in a few rounds without any single transaction
+/// exceeding the size limit. Holdings are re-fetched between rounds and
+/// `on_round_complete` is called after each one.
+///
+/// # Errors
+/// Returns an error if fetching holdings fails, or if a round makes no progress
+/// (every merge in it failed).
+///
+/// # Example
+/// ```ignore
+/// use cbtc::consolidate;
+///
+/// let params = consolidate::ChunkedConsolidateParams {
+///     party: "party::1220...".to_string(),
+///     instrument_id: common::transfer::InstrumentId {
+///         admin: "cbtc-network::1220...".to_string(),
+///         id: "CBTC".to_string(),
+///     },
+///     target_utxo_count: 5,
+///     chunk_size: None,
+///     max_concurrency: None,
+///     ledger_host: "https://participant.example.com".to_string(),
+///     access_token: "eyJ...".to_string(),
+///     registry_url: "https://api.utilities.digitalasset-dev.com".to_string(),
+///     decentralized_party_id: "cbtc-network::1220...".to_string(),
+///     on_round_complete: Some(Box::new(|p| {
+///         log::debug!("Round {}: {} -> {} UTXOs", p.round, p.utxos_before, p.utxos_after);
+///     })),
+/// };
+///
+/// let result = consolidate::consolidate_chunked(params).await?;
+/// ```
+pub async fn consolidate_chunked(
+    params: ChunkedConsolidateParams,
+) -> Result<ConsolidationResult, String> {
+    let chunk_size = params.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let max_concurrency = params
+        .max_concurrency
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+        .max(1);
+
+    let mut holdings =
+        fetch_unlocked_holdings(&params.party, &params.ledger_host, &params.access_token).await?;
+    let utxos_before = holdings.len();
+    let mut round = 0;
+
+    loop {
+        let plan = plan_round(holdings.len(), chunk_size, params.target_utxo_count);
+        if plan.is_empty() {
+            break;
+        }
+        round += 1;
+        let round_start = holdings.len();
+
+        log::debug!(
+            "Consolidation round {}: merging {} chunk(s) of {} UTXOs",
+            round,
+            plan.len(),
+            round_start
+        );
+
+        let mut chunks = Vec::with_capacity(plan.len());
+        let mut rest = holdings.as_slice();
+        for size in plan {
+            let (chunk, tail) = rest.split_at(size);
+            chunks.push(chunk.to_vec());
+            rest = tail;
+        }
+
+        let outcomes: Vec<Result<Vec<String>, String>> = futures::stream::iter(chunks)
+            .map(|chunk| {
+                let total_amount: DamlDecimal = chunk.iter().map(|h| h.amount).sum();
+                let cids = chunk.into_iter().map(|h| h.contract_id).collect();
+                merge_holdings(
+                    &params.party,
+                    params.instrument_id.clone(),
+                    cids,
+                    total_amount,
+                    &params.ledger_host,
+                    &params.access_token,
+                    &params.registry_url,
+                    &params.decentralized_party_id,
+                )
+            })
+            .buffer_unordered(max_concurrency)
+            .collect()
+            .await;
+
+        let merges_succeeded = outcomes.iter().filter(|o| o.is_ok()).count();
+        let merge_errors: Vec<String> = outcomes.into_iter().filter_map(Result::err).collect();
+
+        holdings =
+            fetch_unlocked_holdings(&params.party, &params.ledger_host, &params.access_token)
+                .await?;
+
+        let progress = RoundProgress {
+            round,
+            utxos_before: round_start,
+            utxos_after: holdings.len(),
+            merges_succeeded,
+            merge_errors,
+        };
+        log::debug!(
+            "Round {} complete: {} -> {} UTXOs ({} merge(s) failed)",
+            round,
+            progress.utxos_before,
+            progress.utxos_after,
+            progress.merge_errors.len()
+        );
+        if let Some(ref callback) = params.on_round_complete {
+            callback(&progress);
+        }
+
+        if merges_succeeded == 0 {
+            return Err(format!(
+                "Consolidation round {} made no progress: {}",
+                round,
+                progress.merge_errors.join("; ")
+            ));
+        }
+    }
+
+    Ok(ConsolidationResult {
+        consolidated: round > 0,
+        utxos_after: holdings.len(),
+        holding_cids: holdings.into_iter().map(|h| h.contract_id).collect(),
+        utxos_before,
+    })
+}
+
 /// Check the UTXO count for a party and consolidate if it meets or exceeds the threshold.
 ///
 /// This is the main function teams should use to ensure they don't exceed Canton's
-/// soft limit of 10 UTXOs per party per token type.
+/// soft limit of 10 UTXOs per party per token type. Parties with more than
+/// [`DEFAULT_CHUNK_SIZE`] UTXOs are merged with [`consolidate_chunked`].
 ///
 /// # Example
 /// ```ignore
@@ -352,6 +611,26 @@ pub async fn check_and_consolidate(
 
     log::debug!("Threshold met or exceeded. Consolidating UTXOs...");
 
+    // Too many holdings for one transaction: merge in chunks instead
+    if utxo_count > DEFAULT_CHUNK_SIZE {
+        return consolidate_chunked(ChunkedConsolidateParams {
+            party: params.party,
+            instrument_id: common::transfer::InstrumentId {
+                admin: params.decentralized_party_id.clone(),
+                id: "CBTC".to_string(),
+            },
+            target_utxo_count: 1,
+            chunk_size: None,
+            max_concurrency: None,
+            ledger_host: params.ledger_host,
+            access_token: params.access_token,
+            registry_url: params.registry_url,
+            decentralized_party_id: params.decentralized_party_id,
+            on_round_complete: None,
+        })
+        .await;
+    }
+
     // Perform consolidation
     let result_cids = consolidate_utxos(ConsolidateParams {
         party: params.party,
@@ -375,31 +654,331 @@ pub async fn check_and_consolidate(
     })
 }
 
+/// Holding-amount bucket boundaries used by [`UtxoReport::distribution`]
+const DISTRIBUTION_BOUNDS: [&str; 7] = ["0.00001", "0.0001", "0.001", "0.01", "0.1", "1", "10"];
+
+/// Parameters for building a UTXO health report
+pub struct UtxoReportParams {
+    /// The party ID whose UTXOs to inspect
+    pub party: String,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Access token for the party
+    pub access_token: String,
+    /// Holdings below this amount are reported as dust
+    pub dust_threshold: DamlDecimal,
+    /// Recommend consolidation at or above this many UTXOs (Canton's soft limit is 10)
+    pub max_utxos: usize,
+    /// Recommend splitting below this many UTXOs (e.g. the number of parallel
+    /// payment lanes needed); `None` never recommends a split
+    pub min_utxos: Option<usize>,
+    /// Package ID of the current holding template. If `None`, the package of the
+    /// most recently created holding is assumed to be current.
+    pub current_package_id: Option<String>,
+}
+
+/// A single holding as seen by the UTXO report
+#[derive(Debug, Clone)]
+pub struct HoldingInfo {
+    pub contract_id: String,
+    pub amount: DamlDecimal,
+    /// Package ID of the holding's template
+    pub package_id: String,
+    pub created_at: String,
+}
+
+/// Number and total of holdings whose amount falls in `[lower, upper)`
+#[derive(Debug, Clone)]
+pub struct AmountBucket {
+    /// Inclusive lower bound (`None` = unbounded)
+    pub lower: Option<DamlDecimal>,
+    /// Exclusive upper bound (`None` = unbounded)
+    pub upper: Option<DamlDecimal>,
+    pub count: usize,
+    pub total: DamlDecimal,
+}
+
+/// Action recommended by a UTXO report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtxoAction {
+    /// Holdings are healthy
+    Nothing,
+    /// Too many UTXOs, dust or outdated holdings: merge them
+    Consolidate,
+    /// Too few UTXOs for the requested parallelism: split
+    Split,
+}
+
+/// UTXO health report for a party
+#[derive(Debug, Clone)]
+pub struct UtxoReport {
+    pub holding_count: usize,
+    pub total_amount: DamlDecimal,
+    /// Holdings grouped by order of magnitude of their amount
+    pub distribution: Vec<AmountBucket>,
+    /// Holdings below the dust threshold, smallest first
+    pub dust: Vec<HoldingInfo>,
+    pub largest: Option<HoldingInfo>,
+    pub smallest: Option<HoldingInfo>,
+    /// Holdings created under a package other than the current one
+    pub outdated: Vec<HoldingInfo>,
+    pub recommendation: UtxoAction,
+    /// Human-readable reason for the recommendation
+    pub reason: String,
+}
+
+impl HoldingInfo {
+    fn from_active_contract(contract: &ledger::models::JsActiveContract) -> Result<Self, String> {
+        let holding = Holding::from_active_contract(contract)?;
+        let package_id = contract
+            .created_event
+            .template_id
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self {
+            contract_id: holding.contract_id,
+            amount: holding.amount,
+            package_id,
+            created_at: contract.created_event.created_at.clone(),
+        })
+    }
+}
+
+/// Build a report from already-fetched holdings.
+fn build_report(mut holdings: Vec<HoldingInfo>, params: &UtxoReportParams) -> UtxoReport {
+    holdings.sort_by(|a, b| {
+        a.amount
+            .partial_cmp(&b.amount)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let total_amount = holdings
+        .iter()
+        .fold(DamlDecimal::ZERO, |acc, h| acc + h.amount);
+
+    let bounds: Vec<DamlDecimal> = DISTRIBUTION_BOUNDS
+        .iter()
+        .filter_map(|b| DamlDecimal::parse(b).ok())
+        .collect();
+    let mut distribution: Vec<AmountBucket> = (0..=bounds.len())
+        .map(|i| AmountBucket {
+            lower: i.checked_sub(1).map(|j| bounds[j]),
+            upper: bounds.get(i).copied(),
+            count: 0,
+            total: DamlDecimal::ZERO,
+        })
+        .collect();
+    for holding in &holdings {
+        let idx = bounds.iter().take_while(|b| holding.amount >= **b).count();
+        distribution[idx].count += 1;
+        distribution[idx].total += holding.amount;
+    }
+
+    let dust: Vec<HoldingInfo> = holdings
+        .iter()
+        .filter(|h| h.amount < params.dust_threshold)
+        .cloned()
+        .collect();
+
+    let current_package_id = params.current_package_id.clone().or_else(|| {
+        holdings
+            .iter()
+            // Compare parsed timestamps: RFC3339 strings with and without
+            // fractional seconds don't sort lexically.
+            .max_by_key(|h| {
+                chrono::DateTime::parse_from_rfc3339(&h.created_at)
+                    .ok()
+                    .map(|t| t.with_timezone(&chrono::Utc))
+            })
+            .map(|h| h.package_id.clone())
+    });
+    let outdated: Vec<HoldingInfo> = match &current_package_id {
+        Some(current) => holdings
+            .iter()
+            .filter(|h| &h.package_id != current)
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let holding_count = holdings.len();
+    let (recommendation, reason) = if holding_count >= params.max_utxos {
+        (
+            UtxoAction::Consolidate,
+            format!(
+                "{} UTXOs meets or exceeds the limit of {}",
+                holding_count, params.max_utxos
+            ),
+        )
+    } else if holding_count > 1 && !dust.is_empty() {
+        (
+            UtxoAction::Consolidate,
+            format!(
+                "{} dust holding(s) below {}",
+                dust.len(),
+                params.dust_threshold
+            ),
+        )
+    } else if !outdated.is_empty() {
+        (
+            UtxoAction::Consolidate,
+            format!("{} holding(s) under an old package version", outdated.len()),
+        )
+    } else if let Some(min) = params.min_utxos.filter(|min| holding_count < *min) {
+        (
+            UtxoAction::Split,
+            format!("{} UTXO(s), fewer than the {} wanted", holding_count, min),
+        )
+    } else {
+        (UtxoAction::Nothing, "Holdings are healthy".to_string())
+    };
+
+    UtxoReport {
+        holding_count,
+        total_amount,
+        distribution,
+        dust,
+        largest: holdings.last().cloned(),
+        smallest: holdings.first().cloned(),
+        outdated,
+        recommendation,
+        reason,
+    }
+}
+
+/// Build a UTXO health report for a party's CBTC holdings.
+///
+/// Reports the holding count and amount distribution, dust below
+/// `params.dust_threshold`, the largest and smallest holdings, holdings under
+/// old package versions, and a recommended [`UtxoAction`].
+///
+/// # Errors
+/// Returns an error if the holdings can't be fetched or parsed.
+pub async fn utxo_report(params: UtxoReportParams) -> Result<UtxoReport, String> {
+    let contracts = active_contracts::get(active_contracts::Params {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+
+    let holdings = contracts
+        .iter()
+        .map(HoldingInfo::from_active_contract)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(build_report(holdings, &params))
+}
+
+/// Parameters for sweeping dust holdings into the largest holding
+pub struct SweepDustParams {
+    /// The party ID whose dust to sweep
+    pub party: String,
+    /// The instrument ID (typically CBTC)
+    pub instrument_id: common::transfer::InstrumentId,
+    /// Holdings below this amount are swept
+    pub dust_threshold: DamlDecimal,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Access token for the party
+    pub access_token: String,
+    /// Registry URL
+    pub registry_url: String,
+    /// Decentralized party ID for CBTC
+    pub decentralized_party_id: String,
+}
+
+/// Merge only the dust holdings (below `params.dust_threshold`) into the largest holding.
+///
+/// Other holdings are left untouched. Dust is merged in transactions of at most
+/// [`DEFAULT_CHUNK_SIZE`] inputs, each folding into the result of the previous one.
+/// `holding_cids` in the result is the final largest holding.
+///
+/// # Errors
+/// Returns an error if the holdings can't be fetched or a merge fails.
+pub async fn sweep_dust(params: SweepDustParams) -> Result<ConsolidationResult, String> {
+    let holdings =
+        fetch_unlocked_holdings(&params.party, &params.ledger_host, &params.access_token).await?;
+    let utxos_before = holdings.len();
+
+    // Sorted smallest first, so the largest holding is last
+    let Some((largest, rest)) = holdings.split_last() else {
+        return Ok(ConsolidationResult {
+            consolidated: false,
+            holding_cids: vec![],
+            utxos_before,
+            utxos_after: utxos_before,
+        });
+    };
+    let dust: Vec<&Holding> = rest
+        .iter()
+        .filter(|h| h.amount < params.dust_threshold)
+        .collect();
+
+    if dust.is_empty() {
+        return Ok(ConsolidationResult {
+            consolidated: false,
+            holding_cids: vec![largest.contract_id.clone()],
+            utxos_before,
+            utxos_after: utxos_before,
+        });
+    }
+
+    log::debug!(
+        "Sweeping {} dust holding(s) into {}",
+        dust.len(),
+        largest.contract_id
+    );
+
+    let mut target_cid = largest.contract_id.clone();
+    let mut target_amount = largest.amount;
+    for chunk in dust.chunks(DEFAULT_CHUNK_SIZE - 1) {
+        let total_amount = chunk.iter().fold(target_amount, |acc, h| acc + h.amount);
+        let mut cids = vec![target_cid];
+        cids.extend(chunk.iter().map(|h| h.contract_id.clone()));
+
+        let result_cids = merge_holdings(
+            &params.party,
+            params.instrument_id.clone(),
+            cids,
+            total_amount,
+            &params.ledger_host,
+            &params.access_token,
+            &params.registry_url,
+            &params.decentralized_party_id,
+        )
+        .await?;
+
+        target_cid = result_cids
+            .into_iter()
+            .next()
+            .ok_or("Dust sweep returned no holding")?;
+        target_amount = total_amount;
+    }
+
+    Ok(ConsolidationResult {
+        consolidated: true,
+        holding_cids: vec![target_cid],
+        utxos_before,
+        utxos_after: utxos_before - dust.len(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
     #[tokio::test]
     async fn test_get_utxo_count() {
-        dotenvy::dotenv().ok();
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
 
         let count_params = GetUtxoCountParams {
-            party: env::var("PARTY_ID").expect("PARTY_ID must be set"),
-            ledger_host: env::var("LEDGER_HOST").expect("LEDGER_HOST must be set"),
-            access_token: login_response.access_token,
+            party: env.party(),
+            ledger_host: env.ledger_host(),
+            access_token: env.access_token().await,
         };
 
         let count = get_utxo_count(count_params).await.unwrap();
@@ -409,32 +988,165 @@ mod tests {
 
     #[tokio::test]
     async fn test_check_and_consolidate() {
-        dotenvy::dotenv().ok();
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
 
         let consolidate_params = CheckConsolidateParams {
-            party: env::var("PARTY_ID").expect("PARTY_ID must be set"),
+            party: env.party(),
             threshold: 10, // Canton's soft limit
-            ledger_host: env::var("LEDGER_HOST").expect("LEDGER_HOST must be set"),
-            access_token: login_response.access_token,
-            registry_url: env::var("REGISTRY_URL").expect("REGISTRY_URL must be set"),
-            decentralized_party_id: env::var("DECENTRALIZED_PARTY_ID")
-                .expect("DECENTRALIZED_PARTY_ID must be set"),
+            ledger_host: env.ledger_host(),
+            access_token: env.access_token().await,
+            registry_url: env.registry_url(),
+            decentralized_party_id: env.decentralized_party_id(),
         };
 
         let result = check_and_consolidate(consolidate_params).await.unwrap();
         assert!(result.utxos_before < 10000); // Sanity check
     }
+
+    #[test]
+    fn nothing_to_do_at_or_below_target() {
+        assert!(plan_round(5, 25, 5).is_empty());
+        assert!(plan_round(1, 25, 1).is_empty());
+        assert!(plan_round(0, 25, 1).is_empty());
+    }
+
+    #[test]
+    fn hundreds_of_utxos_split_into_bounded_chunks() {
+        let plan = plan_round(300, 25, 1);
+        assert_eq!(plan, vec![25; 12]);
+        // Next round: 12 merged holdings
+        assert_eq!(plan_round(12, 25, 1), vec![12]);
+    }
+
+    #[test]
+    fn last_chunk_trimmed_to_reach_target() {
+        // 30 -> 10 needs 20 fewer UTXOs: one chunk of 21
+        assert_eq!(plan_round(30, 25, 10), vec![21]);
+        // 60 -> 10 needs 50 fewer: 25 (-24), then 25 (-24), then 3 (-2)
+        assert_eq!(plan_round(60, 25, 10), vec![25, 25, 3]);
+    }
+
+    #[test]
+    fn zero_target_treated_as_one() {
+        assert_eq!(plan_round(3, 25, 0), vec![3]);
+    }
+
+    #[test]
+    fn degenerate_chunk_size_still_merges_pairs() {
+        assert_eq!(plan_round(4, 1, 1), vec![2, 2]);
+    }
+
+    fn d(s: &str) -> DamlDecimal {
+        DamlDecimal::parse(s).unwrap()
+    }
+
+    fn holding(cid: &str, amount: &str, package_id: &str, created_at: &str) -> HoldingInfo {
+        HoldingInfo {
+            contract_id: cid.to_string(),
+            amount: d(amount),
+            package_id: package_id.to_string(),
+            created_at: created_at.to_string(),
+        }
+    }
+
+    fn report_params() -> UtxoReportParams {
+        UtxoReportParams {
+            party: "party::1220".to_string(),
+            ledger_host: String::new(),
+            access_token: String::new(),
+            dust_threshold: d("0.0001"),
+            max_utxos: 10,
+            min_utxos: None,
+            current_package_id: None,
+        }
+    }
+
+    #[test]
+    fn reports_largest_smallest_dust_and_distribution() {
+        let report = build_report(
+            vec![
+                holding("00big", "2.5", "pkg1", "2026-01-02T00:00:00Z"),
+                holding("00dust", "0.00005", "pkg1", "2026-01-01T00:00:00Z"),
+                holding("00mid", "0.05", "pkg1", "2026-01-01T00:00:00Z"),
+            ],
+            &report_params(),
+        );
+
+        assert_eq!(report.holding_count, 3);
+        assert_eq!(report.total_amount, d("2.55005"));
+        assert_eq!(report.largest.unwrap().contract_id, "00big");
+        assert_eq!(report.smallest.unwrap().contract_id, "00dust");
+        assert_eq!(report.dust.len(), 1);
+        assert_eq!(report.distribution.len(), DISTRIBUTION_BOUNDS.len() + 1);
+        let counts: Vec<usize> = report.distribution.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![0, 1, 0, 0, 1, 0, 1, 0]);
+        assert_eq!(report.recommendation, UtxoAction::Consolidate);
+    }
+
+    #[test]
+    fn outdated_package_inferred_from_newest_holding() {
+        let report = build_report(
+            vec![
+                holding("00old", "1", "pkg-old", "2025-06-01T00:00:00Z"),
+                holding("00new", "1", "pkg-new", "2026-01-01T00:00:00Z"),
+            ],
+            &report_params(),
+        );
+
+        assert_eq!(report.outdated.len(), 1);
+        assert_eq!(report.outdated[0].contract_id, "00old");
+        assert_eq!(report.recommendation, UtxoAction::Consolidate);
+    }
+
+    #[test]
+    fn newest_holding_compared_by_time_not_string() {
+        // Lexically "…00:00:00Z" > "…00:00:00.5Z", but the latter is newer.
+        let report = build_report(
+            vec![
+                holding("00old", "1", "pkg-old", "2026-01-01T00:00:00Z"),
+                holding("00new", "1", "pkg-new", "2026-01-01T00:00:00.5Z"),
+            ],
+            &report_params(),
+        );
+
+        assert_eq!(report.outdated.len(), 1);
+        assert_eq!(report.outdated[0].contract_id, "00old");
+    }
+
+    #[test]
+    fn too_many_utxos_recommends_consolidation() {
+        let holdings = (0..10)
+            .map(|i| holding(&format!("00h{i}"), "1", "pkg1", "2026-01-01T00:00:00Z"))
+            .collect();
+        let report = build_report(holdings, &report_params());
+        assert_eq!(report.recommendation, UtxoAction::Consolidate);
+    }
+
+    #[test]
+    fn too_few_utxos_recommends_split() {
+        let report = build_report(
+            vec![holding("00only", "5", "pkg1", "2026-01-01T00:00:00Z")],
+            &UtxoReportParams {
+                min_utxos: Some(4),
+                ..report_params()
+            },
+        );
+        assert_eq!(report.recommendation, UtxoAction::Split);
+    }
+
+    #[test]
+    fn healthy_holdings_need_nothing() {
+        let report = build_report(
+            vec![
+                holding("00a", "1", "pkg1", "2026-01-01T00:00:00Z"),
+                holding("00b", "2", "pkg1", "2026-01-02T00:00:00Z"),
+            ],
+            &report_params(),
+        );
+        assert_eq!(report.recommendation, UtxoAction::Nothing);
+        assert!(report.dust.is_empty());
+        assert!(report.outdated.is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -479,11 +1191,7 @@ mod parser_tests {
         // Only a CreatedEvent present — no ExercisedEvent at all.
         let response = transaction_response(
             "tx-x",
-            json!([created_event_value(
-                "pkg:Some:Template",
-                "00x",
-                json!(null),
-            )]),
+            json!([created_event_value("pkg:Some:Template", "00x", json!(null),)]),
         );
 
         let err = parse_consolidate_response(&response).unwrap_err();
@@ -15,6 +15,11 @@ const CREDENTIAL_TEMPLATE_ID: &str =
 const USER_SERVICE_TEMPLATE_ID: &str =
     "#utility-credential-app-v0:Utility.Credential.App.V0.Service.User:UserService";
 
+// UserService choices for the issuer side
+const OFFER_FREE_CREDENTIAL_CHOICE: &str = "UserService_OfferFreeCredential";
+const CANCEL_CREDENTIAL_OFFER_CHOICE: &str = "UserService_CancelCredentialOffer";
+const REVOKE_CREDENTIAL_CHOICE: &str = "UserService_RevokeCredential";
+
 /// A claim within a credential (matches Daml Claim type)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claim {
@@ -107,6 +112,11 @@ pub struct UserCredential {
     pub id: String,
     pub description: String,
     pub claims: Vec<Claim>,
+    /// Start of the validity period, if the credential has one
+    pub valid_from: Option<chrono::DateTime<chrono::Utc>>,
+    /// End of the validity period; the credential is no longer accepted
+    /// from this time on
+    pub valid_until: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl UserCredential {
@@ -160,8 +170,168 @@ impl UserCredential {
             id,
             description,
             claims,
+            valid_from: optional_time(args, "validFrom")?,
+            valid_until: optional_time(args, "validUntil")?,
         })
     }
+
+    /// Whether the credential is within its validity period at `at`
+    pub fn is_valid_at(&self, at: chrono::DateTime<chrono::Utc>) -> bool {
+        self.valid_from.is_none_or(|from| from <= at)
+            && self.valid_until.is_none_or(|until| at < until)
+    }
+}
+
+/// Parse an optional Daml `Time` field, absent or `null` when unset
+fn optional_time(
+    args: &serde_json::Map<String, serde_json::Value>,
+    field: &str,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    match args.get(field) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(value) => value
+            .as_str()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| Some(t.with_timezone(&chrono::Utc)))
+            .ok_or_else(|| format!("Invalid '{}' field: {}", field, value)),
+    }
+}
+
+/// A claim a required credential must make about its holder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimRequirement {
+    pub property: String,
+    pub value: String,
+}
+
+/// A credential a party must hold: issued by `issuer`, with every claim in
+/// `claims` made about the holder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialRequirement {
+    pub issuer: String,
+    pub claims: Vec<ClaimRequirement>,
+}
+
+impl CredentialRequirement {
+    /// Whether `credential` is held by `holder` and satisfies the requirement
+    pub fn is_met_by(&self, credential: &UserCredential, holder: &str) -> bool {
+        credential.issuer == self.issuer
+            && credential.holder == holder
+            && self.claims_hold(&credential.claims, holder)
+    }
+
+    /// Whether accepting `offer` would give `holder` a credential satisfying
+    /// the requirement
+    pub fn is_offered_by(&self, offer: &CredentialOffer, holder: &str) -> bool {
+        offer.issuer == self.issuer
+            && offer.holder == holder
+            && self.claims_hold(&offer.claims, holder)
+    }
+
+    fn claims_hold(&self, claims: &[Claim], holder: &str) -> bool {
+        self.claims.iter().all(|required| {
+            claims.iter().any(|claim| {
+                claim.subject == holder
+                    && claim.property == required.property
+                    && claim.value == required.value
+            })
+        })
+    }
+}
+
+impl std::fmt::Display for CredentialRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "credential from {}", self.issuer)?;
+        if !self.claims.is_empty() {
+            let claims: Vec<String> = self
+                .claims
+                .iter()
+                .map(|c| format!("{}={}", c.property, c.value))
+                .collect();
+            write!(f, " claiming {}", claims.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Matches credentials making a claim with `property` (and `value`, if set)
+/// about their holder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimFilter {
+    pub property: String,
+    pub value: Option<String>,
+}
+
+impl ClaimFilter {
+    /// Whether any of `claims` about `holder` matches the filter
+    pub fn matches(&self, claims: &[Claim], holder: &str) -> bool {
+        claims.iter().any(|claim| {
+            claim.subject == holder
+                && claim.property == self.property
+                && self.value.as_ref().is_none_or(|v| &claim.value == v)
+        })
+    }
+}
+
+/// Criteria for selecting credentials.
+///
+/// Every field that is set must match; the default filter matches all
+/// credentials.
+#[derive(Debug, Clone, Default)]
+pub struct CredentialFilter {
+    /// Only credentials from this issuer
+    pub issuer: Option<String>,
+    /// Only credentials with a matching claim about their holder
+    pub claim: Option<ClaimFilter>,
+    /// Only credentials within their validity period now
+    pub valid_only: bool,
+}
+
+impl CredentialFilter {
+    /// Check whether a credential matches this filter.
+    pub fn matches(&self, credential: &UserCredential) -> bool {
+        self.matches_at(credential, chrono::Utc::now())
+    }
+
+    fn matches_at(&self, credential: &UserCredential, now: chrono::DateTime<chrono::Utc>) -> bool {
+        if self
+            .issuer
+            .as_ref()
+            .is_some_and(|issuer| &credential.issuer != issuer)
+        {
+            return false;
+        }
+        if self
+            .claim
+            .as_ref()
+            .is_some_and(|claim| !claim.matches(&credential.claims, &credential.holder))
+        {
+            return false;
+        }
+        !self.valid_only || credential.is_valid_at(now)
+    }
+}
+
+/// A required credential that expires soon, with no credential valid for
+/// longer to take its place. Mint and redeem fail once it expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryWarning {
+    pub requirement: CredentialRequirement,
+    /// The credential meeting the requirement that stays valid longest
+    pub contract_id: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl std::fmt::Display for ExpiryWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Required {} ({}) expires at {}",
+            self.requirement,
+            self.contract_id,
+            self.expires_at.to_rfc3339()
+        )
+    }
 }
 
 /// Information about a UserService contract
@@ -198,6 +368,73 @@ pub struct AcceptCredentialOfferParams {
     pub credential_offer_cid: String,
 }
 
+/// Parameters for listing the credentials, or credential offers, a party has
+/// issued
+pub struct ListIssuedCredentialsParams {
+    pub ledger_host: String,
+    /// The issuer
+    pub party: String,
+    pub access_token: String,
+    /// Only return credentials with a matching claim
+    pub claim: Option<ClaimFilter>,
+}
+
+/// Parameters for offering a free credential to a holder
+pub struct OfferCredentialParams {
+    pub ledger_host: String,
+    /// The issuer
+    pub party: String,
+    pub access_token: String,
+    /// The issuer's own UserService (see [`find_user_service`])
+    pub user_service_contract_id: String,
+    pub user_service_template_id: String,
+    pub holder: String,
+    pub id: String,
+    pub description: String,
+    pub claims: Vec<Claim>,
+}
+
+/// Parameters for cancelling a credential offer the holder has not accepted
+pub struct CancelCredentialOfferParams {
+    pub ledger_host: String,
+    /// The issuer
+    pub party: String,
+    pub access_token: String,
+    pub user_service_contract_id: String,
+    pub user_service_template_id: String,
+    pub credential_offer_cid: String,
+}
+
+/// Parameters for revoking an issued credential
+pub struct RevokeCredentialParams {
+    pub ledger_host: String,
+    /// The issuer
+    pub party: String,
+    pub access_token: String,
+    pub user_service_contract_id: String,
+    pub user_service_template_id: String,
+    pub credential_cid: String,
+}
+
+/// Parameters for finding a party's credentials matching a filter
+pub struct FindCredentialsParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    pub filter: CredentialFilter,
+}
+
+/// Parameters for checking a party's required credentials for upcoming
+/// expiry
+pub struct CheckCredentialExpiryParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    pub requirements: Vec<CredentialRequirement>,
+    /// How far ahead to warn
+    pub within: chrono::Duration,
+}
+
 /// Parameters for finding a user's UserService contract
 pub struct FindUserServiceParams {
     pub ledger_host: String,
@@ -205,13 +442,16 @@ pub struct FindUserServiceParams {
     pub access_token: String,
 }
 
-/// List all credential offers for a party
-pub async fn list_credential_offers(
-    params: ListCredentialOffersParams,
-) -> Result<Vec<CredentialOffer>, String> {
+/// Active contracts of `template_id` visible to `party`
+async fn active_contracts_of(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    template_id: &str,
+) -> Result<Vec<JsActiveContract>, String> {
     let ledger_end_response = ledger_end::get(ledger_end::Params {
-        access_token: params.access_token.clone(),
-        ledger_host: params.ledger_host.clone(),
+        access_token: access_token.to_string(),
+        ledger_host: ledger_host.to_string(),
     })
     .await?;
 
@@ -219,88 +459,393 @@ pub async fn list_credential_offers(
         ledger::common::IdentifierFilter::TemplateIdentifierFilter(TemplateIdentifierFilter {
             template_filter: TemplateFilter {
                 value: TemplateFilterValue {
-                    template_id: Some(CREDENTIAL_OFFER_TEMPLATE_ID.to_string()),
+                    template_id: Some(template_id.to_string()),
                     include_created_event_blob: true,
                 },
             },
         });
 
-    let contracts = active_contracts::get_by_party(active_contracts::Params {
-        ledger_host: params.ledger_host,
-        party: params.party.clone(),
+    active_contracts::get_by_party(active_contracts::Params {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
         filter,
-        access_token: params.access_token,
+        access_token: access_token.to_string(),
         ledger_end: ledger_end_response.offset,
         unknown_contract_entry_handler: None,
     })
+    .await
+}
+
+/// Whether the contract's `field` party is `party`
+fn has_party(contract: &JsActiveContract, field: &str, party: &str) -> bool {
+    contract
+        .created_event
+        .create_argument
+        .as_ref()
+        .and_then(|v| v.as_object())
+        .and_then(|args| args.get(field))
+        .and_then(|v| v.as_str())
+        .map(|p| p == party)
+        .unwrap_or(false)
+}
+
+/// List all credential offers for a party
+pub async fn list_credential_offers(
+    params: ListCredentialOffersParams,
+) -> Result<Vec<CredentialOffer>, String> {
+    let contracts = active_contracts_of(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        CREDENTIAL_OFFER_TEMPLATE_ID,
+    )
     .await?;
 
-    let offers: Result<Vec<CredentialOffer>, String> = contracts
+    contracts
         .iter()
-        .filter(|contract| {
-            contract
-                .created_event
-                .create_argument
-                .as_ref()
-                .and_then(|v| v.as_object())
-                .and_then(|args| args.get("holder"))
-                .and_then(|v| v.as_str())
-                .map(|holder| holder == params.party)
-                .unwrap_or(false)
-        })
+        .filter(|contract| has_party(contract, "holder", &params.party))
         .map(CredentialOffer::from_active_contract)
-        .collect();
-
-    offers
+        .collect()
 }
 
 /// List all credentials for a party
 pub async fn list_credentials(
     params: ListCredentialsParams,
 ) -> Result<Vec<UserCredential>, String> {
-    let ledger_end_response = ledger_end::get(ledger_end::Params {
-        access_token: params.access_token.clone(),
-        ledger_host: params.ledger_host.clone(),
+    let contracts = active_contracts_of(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        CREDENTIAL_TEMPLATE_ID,
+    )
+    .await?;
+
+    contracts
+        .iter()
+        .filter(|contract| has_party(contract, "holder", &params.party))
+        .map(UserCredential::from_active_contract)
+        .collect()
+}
+
+/// Find the party's credentials matching `filter`.
+///
+/// # Example
+/// ```ignore
+/// let kyc = credentials::find_credentials(FindCredentialsParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     filter: CredentialFilter {
+///         issuer: Some("compliance::1220...".to_string()),
+///         claim: Some(ClaimFilter {
+///             property: "hasKYC".to_string(),
+///             value: Some("true".to_string()),
+///         }),
+///         valid_only: true,
+///     },
+/// }).await?;
+/// ```
+pub async fn find_credentials(
+    params: FindCredentialsParams,
+) -> Result<Vec<UserCredential>, String> {
+    let credentials = list_credentials(ListCredentialsParams {
+        ledger_host: params.ledger_host,
+        party: params.party,
+        access_token: params.access_token,
     })
     .await?;
 
-    let filter =
-        ledger::common::IdentifierFilter::TemplateIdentifierFilter(TemplateIdentifierFilter {
-            template_filter: TemplateFilter {
-                value: TemplateFilterValue {
-                    template_id: Some(CREDENTIAL_TEMPLATE_ID.to_string()),
-                    include_created_event_blob: true,
-                },
-            },
-        });
+    Ok(credentials
+        .into_iter()
+        .filter(|c| params.filter.matches(c))
+        .collect())
+}
 
-    let contracts = active_contracts::get_by_party(active_contracts::Params {
+/// List the active credentials a party has issued, optionally only those
+/// with a matching claim (e.g. to see who holds a given attestation).
+///
+/// # Example
+/// ```ignore
+/// let minters = credentials::list_issued_credentials(ListIssuedCredentialsParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "issuer::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     claim: Some(ClaimFilter {
+///         property: "hasCBTCRole".to_string(),
+///         value: Some("Minter".to_string()),
+///     }),
+/// }).await?;
+/// for credential in &minters {
+///     println!("{} holds {}", credential.holder, credential.id);
+/// }
+/// ```
+pub async fn list_issued_credentials(
+    params: ListIssuedCredentialsParams,
+) -> Result<Vec<UserCredential>, String> {
+    let contracts = active_contracts_of(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        CREDENTIAL_TEMPLATE_ID,
+    )
+    .await?;
+
+    let credentials = contracts
+        .iter()
+        .filter(|contract| has_party(contract, "issuer", &params.party))
+        .map(UserCredential::from_active_contract)
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(filter_by_claim(credentials, params.claim.as_ref(), |c| {
+        (&c.claims, &c.holder)
+    }))
+}
+
+/// List the credential offers a party has issued that are still pending
+/// acceptance, optionally only those with a matching claim
+pub async fn list_issued_credential_offers(
+    params: ListIssuedCredentialsParams,
+) -> Result<Vec<CredentialOffer>, String> {
+    let contracts = active_contracts_of(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        CREDENTIAL_OFFER_TEMPLATE_ID,
+    )
+    .await?;
+
+    let offers = contracts
+        .iter()
+        .filter(|contract| has_party(contract, "issuer", &params.party))
+        .map(CredentialOffer::from_active_contract)
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(filter_by_claim(offers, params.claim.as_ref(), |o| {
+        (&o.claims, &o.holder)
+    }))
+}
+
+/// Keep the items whose claims about their holder match `filter`, if any
+fn filter_by_claim<T>(
+    items: Vec<T>,
+    filter: Option<&ClaimFilter>,
+    claims: impl Fn(&T) -> (&Vec<Claim>, &String),
+) -> Vec<T> {
+    match filter {
+        None => items,
+        Some(filter) => items
+            .into_iter()
+            .filter(|item| {
+                let (claims, holder) = claims(item);
+                filter.matches(claims, holder)
+            })
+            .collect(),
+    }
+}
+
+/// Parameters for discovering the credentials a party should present
+pub struct DiscoverCredentialsParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    pub requirements: Vec<CredentialRequirement>,
+}
+
+/// Select the credentials among `credentials` that `holder` should present
+/// to meet `requirements`: every credential meeting any requirement that is
+/// within its validity period.
+///
+/// # Errors
+///
+/// Returns an error string naming every requirement no valid credential
+/// meets, and any credential that would meet it but has expired.
+pub fn select_credentials(
+    credentials: &[UserCredential],
+    holder: &str,
+    requirements: &[CredentialRequirement],
+) -> Result<Vec<String>, String> {
+    select_credentials_at(credentials, holder, requirements, chrono::Utc::now())
+}
+
+fn select_credentials_at(
+    credentials: &[UserCredential],
+    holder: &str,
+    requirements: &[CredentialRequirement],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<String>, String> {
+    let meets = |r: &CredentialRequirement, c: &UserCredential| {
+        r.is_met_by(c, holder) && c.is_valid_at(now)
+    };
+
+    let missing: Vec<String> = requirements
+        .iter()
+        .filter(|r| !credentials.iter().any(|c| meets(r, c)))
+        .map(|r| {
+            let invalid: Vec<String> = credentials
+                .iter()
+                .filter(|c| r.is_met_by(c, holder))
+                .map(|c| match c.valid_until.filter(|until| *until <= now) {
+                    Some(until) => format!("{} expired at {}", c.contract_id, until.to_rfc3339()),
+                    None => format!("{} is not valid yet", c.contract_id),
+                })
+                .collect();
+            if invalid.is_empty() {
+                r.to_string()
+            } else {
+                format!("{} ({})", r, invalid.join(", "))
+            }
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Party {} is missing a required {}",
+            holder,
+            missing.join("; and a ")
+        ));
+    }
+
+    Ok(credentials
+        .iter()
+        .filter(|c| requirements.iter().any(|r| meets(r, c)))
+        .map(|c| c.contract_id.clone())
+        .collect())
+}
+
+/// Warn about each requirement whose credentials all expire within `within`:
+/// once they do, the party can no longer meet it. Requirements the party
+/// does not meet at all are left to [`select_credentials`].
+pub fn expiry_warnings(
+    credentials: &[UserCredential],
+    holder: &str,
+    requirements: &[CredentialRequirement],
+    within: chrono::Duration,
+) -> Vec<ExpiryWarning> {
+    expiry_warnings_at(
+        credentials,
+        holder,
+        requirements,
+        within,
+        chrono::Utc::now(),
+    )
+}
+
+fn expiry_warnings_at(
+    credentials: &[UserCredential],
+    holder: &str,
+    requirements: &[CredentialRequirement],
+    within: chrono::Duration,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<ExpiryWarning> {
+    requirements
+        .iter()
+        .filter_map(|r| {
+            let valid: Vec<&UserCredential> = credentials
+                .iter()
+                .filter(|c| r.is_met_by(c, holder) && c.is_valid_at(now))
+                .collect();
+            if valid.iter().any(|c| c.valid_until.is_none()) {
+                return None;
+            }
+            let longest = valid.into_iter().max_by_key(|c| c.valid_until)?;
+            let expires_at = longest.valid_until?;
+            (expires_at <= now + within).then(|| ExpiryWarning {
+                requirement: r.clone(),
+                contract_id: longest.contract_id.clone(),
+                expires_at,
+            })
+        })
+        .collect()
+}
+
+/// Find the party's credentials that meet `requirements`.
+///
+/// # Example
+/// ```ignore
+/// let credential_cids = credentials::discover_credentials(DiscoverCredentialsParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     requirements: mint_redeem::credentials::account_requirements("cbtc-network::1220..."),
+/// }).await?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error string naming each requirement the party holds no
+/// credential for, and any pending offer that would meet it, or if a ledger
+/// query fails.
+pub async fn discover_credentials(
+    params: DiscoverCredentialsParams,
+) -> Result<Vec<String>, String> {
+    let credentials = list_credentials(ListCredentialsParams {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+    let error = match select_credentials(&credentials, &params.party, &params.requirements) {
+        Ok(cids) => return Ok(cids),
+        Err(e) => e,
+    };
+
+    // Point at pending offers that would fix it
+    let offers = list_credential_offers(ListCredentialOffersParams {
         ledger_host: params.ledger_host,
         party: params.party.clone(),
-        filter,
         access_token: params.access_token,
-        ledger_end: ledger_end_response.offset,
-        unknown_contract_entry_handler: None,
     })
     .await?;
-
-    let credentials: Result<Vec<UserCredential>, String> = contracts
+    let pending: Vec<&str> = offers
         .iter()
-        .filter(|contract| {
-            contract
-                .created_event
-                .create_argument
-                .as_ref()
-                .and_then(|v| v.as_object())
-                .and_then(|args| args.get("holder"))
-                .and_then(|v| v.as_str())
-                .map(|holder| holder == params.party)
-                .unwrap_or(false)
+        .filter(|o| {
+            params
+                .requirements
+                .iter()
+                .any(|r| r.is_offered_by(o, &params.party))
         })
-        .map(UserCredential::from_active_contract)
+        .map(|o| o.contract_id.as_str())
         .collect();
+    if pending.is_empty() {
+        Err(error)
+    } else {
+        Err(format!(
+            "{} (matching credential offer pending acceptance: {})",
+            error,
+            pending.join(", ")
+        ))
+    }
+}
 
-    credentials
+/// Check the party's credentials meeting `requirements` for expiry within
+/// `params.within`.
+///
+/// # Example
+/// ```ignore
+/// let warnings = credentials::check_credential_expiry(CheckCredentialExpiryParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     requirements: mint_redeem::credentials::account_requirements("cbtc-network::1220..."),
+///     within: chrono::Duration::days(7),
+/// }).await?;
+/// for warning in &warnings {
+///     log::warn!("{}", warning);
+/// }
+/// ```
+pub async fn check_credential_expiry(
+    params: CheckCredentialExpiryParams,
+) -> Result<Vec<ExpiryWarning>, String> {
+    let credentials = list_credentials(ListCredentialsParams {
+        ledger_host: params.ledger_host,
+        party: params.party.clone(),
+        access_token: params.access_token,
+    })
+    .await?;
+
+    Ok(expiry_warnings(
+        &credentials,
+        &params.party,
+        &params.requirements,
+        params.within,
+    ))
 }
 
 /// Find the UserService contract for a party
@@ -379,27 +924,29 @@ pub async fn find_user_service(params: FindUserServiceParams) -> Result<UserServ
     ))
 }
 
-/// Accept a credential offer by exercising the UserService_AcceptFreeCredentialOffer choice
-pub async fn accept_credential_offer(
-    params: AcceptCredentialOfferParams,
-) -> Result<UserCredential, String> {
+/// Exercise `choice` on a UserService as `party`
+async fn exercise_user_service(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    user_service_contract_id: &str,
+    user_service_template_id: &str,
+    choice: &str,
+    choice_argument: serde_json::Value,
+) -> Result<JsSubmitAndWaitForTransactionResponse, String> {
     let command_id = format!("cmd-{}", uuid::Uuid::new_v4());
 
-    let choice_argument = json!({
-        "credentialOfferCid": params.credential_offer_cid
-    });
-
     let exercise_command = submission::ExerciseCommand {
         exercise_command: submission::ExerciseCommandData {
-            template_id: params.user_service_template_id.clone(),
-            contract_id: params.user_service_contract_id.clone(),
-            choice: "UserService_AcceptFreeCredentialOffer".to_string(),
+            template_id: user_service_template_id.to_string(),
+            contract_id: user_service_contract_id.to_string(),
+            choice: choice.to_string(),
             choice_argument: submission::ChoiceArgumentsVariations::Generic(choice_argument),
         },
     };
 
     let submission_request = submission::Submission {
-        act_as: vec![params.party.clone()],
+        act_as: vec![party.to_string()],
         read_as: None,
         command_id,
         disclosed_contracts: vec![],
@@ -408,18 +955,128 @@ pub async fn accept_credential_offer(
     };
 
     let response_raw = submit::wait_for_transaction(submit::Params {
-        ledger_host: params.ledger_host.clone(),
-        access_token: params.access_token.clone(),
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
         request: submission_request,
     })
     .await?;
 
-    let response: JsSubmitAndWaitForTransactionResponse = serde_json::from_str(&response_raw)
-        .map_err(|e| format!("Failed to parse submit response: {}", e))?;
+    serde_json::from_str(&response_raw)
+        .map_err(|e| format!("Failed to parse submit response: {}", e))
+}
+
+/// Accept a credential offer by exercising the UserService_AcceptFreeCredentialOffer choice
+pub async fn accept_credential_offer(
+    params: AcceptCredentialOfferParams,
+) -> Result<UserCredential, String> {
+    let response = exercise_user_service(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.user_service_contract_id,
+        &params.user_service_template_id,
+        "UserService_AcceptFreeCredentialOffer",
+        json!({
+            "credentialOfferCid": params.credential_offer_cid
+        }),
+    )
+    .await?;
 
     parse_accept_credential_offer_response(&response)
 }
 
+/// Offer a free credential making `claims` to `holder`, as the issuer
+/// (`UserService_OfferFreeCredential` on the issuer's UserService). The holder
+/// accepts it with [`accept_credential_offer`].
+///
+/// # Example
+/// ```ignore
+/// let issuer_service = credentials::find_user_service(FindUserServiceParams {
+///     ledger_host: ledger_host.clone(),
+///     party: issuer.clone(),
+///     access_token: token.clone(),
+/// }).await?;
+/// let offer = credentials::offer_credential(OfferCredentialParams {
+///     ledger_host,
+///     party: issuer,
+///     access_token: token,
+///     user_service_contract_id: issuer_service.contract_id,
+///     user_service_template_id: issuer_service.template_id,
+///     holder: "sub-account::1220...".to_string(),
+///     id: "kyc-2026".to_string(),
+///     description: "KYC attestation".to_string(),
+///     claims: vec![Claim {
+///         subject: "sub-account::1220...".to_string(),
+///         property: "hasKYC".to_string(),
+///         value: "true".to_string(),
+///     }],
+/// }).await?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error string if `claims` is empty or the submission fails.
+pub async fn offer_credential(params: OfferCredentialParams) -> Result<CredentialOffer, String> {
+    if params.claims.is_empty() {
+        return Err("A credential offer needs at least one claim".to_string());
+    }
+
+    let response = exercise_user_service(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.user_service_contract_id,
+        &params.user_service_template_id,
+        OFFER_FREE_CREDENTIAL_CHOICE,
+        json!({
+            "holder": params.holder,
+            "id": params.id,
+            "description": params.description,
+            "claims": params.claims
+        }),
+    )
+    .await?;
+
+    parse_offer_credential_response(&response)
+}
+
+/// Cancel a credential offer the holder has not yet accepted, archiving it
+pub async fn cancel_credential_offer(params: CancelCredentialOfferParams) -> Result<(), String> {
+    exercise_user_service(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.user_service_contract_id,
+        &params.user_service_template_id,
+        CANCEL_CREDENTIAL_OFFER_CHOICE,
+        json!({
+            "credentialOfferCid": params.credential_offer_cid
+        }),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke a credential the party issued, archiving it. The holder can no
+/// longer present it, e.g. as `credentialCids` to the CBTC account rules.
+pub async fn revoke_credential(params: RevokeCredentialParams) -> Result<(), String> {
+    exercise_user_service(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.user_service_contract_id,
+        &params.user_service_template_id,
+        REVOKE_CREDENTIAL_CHOICE,
+        json!({
+            "credentialCid": params.credential_cid
+        }),
+    )
+    .await?;
+
+    Ok(())
+}
+
 /// Extract the newly created `UserCredential` from a flat-shaped submit
 /// response for `UserService_AcceptFreeCredentialOffer`.
 ///
@@ -451,34 +1108,43 @@ fn parse_accept_credential_offer_response(
     Err("No Credential contract was created in the transaction".to_string())
 }
 
+/// Extract the newly created `CredentialOffer` from a flat-shaped submit
+/// response for `UserService_OfferFreeCredential`.
+fn parse_offer_credential_response(
+    response: &JsSubmitAndWaitForTransactionResponse,
+) -> Result<CredentialOffer, String> {
+    let created = response
+        .transaction
+        .events
+        .iter()
+        .filter_map(crate::event_helpers::as_created_event)
+        .find(|c| {
+            c.template_id
+                .ends_with(":Utility.Credential.App.V0.Model.Offer:CredentialOffer")
+        })
+        .ok_or("No CredentialOffer contract was created in the transaction")?;
+
+    let active_contract = JsActiveContract {
+        created_event: Box::new(created.clone()),
+        reassignment_counter: 0,
+        synchronizer_id: String::new(),
+    };
+    CredentialOffer::from_active_contract(&active_contract)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
     #[tokio::test]
     async fn test_list_credentials() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
 
         let credentials = list_credentials(ListCredentialsParams {
-            ledger_host,
-            party: party_id,
-            access_token: login_response.access_token,
+            ledger_host: env.ledger_host(),
+            party: env.party(),
+            access_token: env.access_token().await,
         })
         .await
         .expect("Failed to list credentials");
@@ -488,26 +1154,12 @@ mod tests {
 
     #[tokio::test]
     async fn test_list_credential_offers() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
 
         let offers = list_credential_offers(ListCredentialOffersParams {
-            ledger_host,
-            party: party_id,
-            access_token: login_response.access_token,
+            ledger_host: env.ledger_host(),
+            party: env.party(),
+            access_token: env.access_token().await,
         })
         .await
         .expect("Failed to list credential offers");
@@ -517,26 +1169,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_find_user_service() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
+        let party_id = env.party();
 
         let user_service = find_user_service(FindUserServiceParams {
-            ledger_host,
+            ledger_host: env.ledger_host(),
             party: party_id.clone(),
-            access_token: login_response.access_token,
+            access_token: env.access_token().await,
         })
         .await
         .expect("Failed to find UserService");
@@ -545,6 +1184,261 @@ mod tests {
         assert!(!user_service.contract_id.is_empty());
         assert!(!user_service.operator.is_empty());
     }
+
+    const ISSUER: &str = "cbtc-network::1220";
+    const HOLDER: &str = "alice::1220";
+
+    fn claim(subject: &str, property: &str, value: &str) -> Claim {
+        Claim {
+            subject: subject.to_string(),
+            property: property.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn credential(cid: &str, issuer: &str, claims: Vec<Claim>) -> UserCredential {
+        UserCredential {
+            contract_id: cid.to_string(),
+            template_id: CREDENTIAL_TEMPLATE_ID.to_string(),
+            issuer: issuer.to_string(),
+            holder: HOLDER.to_string(),
+            id: cid.to_string(),
+            description: String::new(),
+            claims,
+            valid_from: None,
+            valid_until: None,
+        }
+    }
+
+    fn minter() -> CredentialRequirement {
+        CredentialRequirement {
+            issuer: ISSUER.to_string(),
+            claims: vec![ClaimRequirement {
+                property: "hasCBTCRole".to_string(),
+                value: "Minter".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn selects_credentials_meeting_requirements() {
+        let credentials = vec![
+            credential("00a", ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Minter")]),
+            credential(
+                "00b",
+                "other::1220",
+                vec![claim(HOLDER, "hasCBTCRole", "Minter")],
+            ),
+            // Claims about someone else don't count
+            credential(
+                "00c",
+                ISSUER,
+                vec![claim("bob::1220", "hasCBTCRole", "Minter")],
+            ),
+            credential("00d", ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Viewer")]),
+        ];
+
+        assert_eq!(
+            select_credentials(&credentials, HOLDER, &[minter()]).unwrap(),
+            vec!["00a".to_string()]
+        );
+        assert!(
+            select_credentials(&credentials, HOLDER, &[])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn names_the_missing_credential() {
+        let credentials = vec![credential(
+            "00d",
+            ISSUER,
+            vec![claim(HOLDER, "hasCBTCRole", "Viewer")],
+        )];
+
+        let err = select_credentials(&credentials, HOLDER, &[minter()]).unwrap_err();
+        assert_eq!(
+            err,
+            "Party alice::1220 is missing a required credential from cbtc-network::1220 \
+             claiming hasCBTCRole=Minter"
+        );
+    }
+
+    fn at(s: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    fn expiring(cid: &str, valid_until: &str) -> UserCredential {
+        let mut c = credential(cid, ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Minter")]);
+        c.valid_until = Some(at(valid_until));
+        c
+    }
+
+    #[test]
+    fn skips_credentials_outside_their_validity_period() {
+        let now = at("2026-06-01T00:00:00Z");
+        let mut future = credential("00f", ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Minter")]);
+        future.valid_from = Some(at("2026-07-01T00:00:00Z"));
+        let expired = expiring("00e", "2026-05-01T00:00:00Z");
+        let current = expiring("00c", "2026-06-02T00:00:00Z");
+
+        assert_eq!(
+            select_credentials_at(
+                &[expired.clone(), future.clone(), current],
+                HOLDER,
+                &[minter()],
+                now
+            )
+            .unwrap(),
+            vec!["00c".to_string()]
+        );
+
+        let err = select_credentials_at(&[expired, future], HOLDER, &[minter()], now).unwrap_err();
+        assert_eq!(
+            err,
+            "Party alice::1220 is missing a required credential from cbtc-network::1220 \
+             claiming hasCBTCRole=Minter (00e expired at 2026-05-01T00:00:00+00:00, \
+             00f is not valid yet)"
+        );
+    }
+
+    #[test]
+    fn warns_when_every_required_credential_expires_soon() {
+        let now = at("2026-06-01T00:00:00Z");
+        let week = chrono::Duration::days(7);
+        let soon = expiring("00s", "2026-06-03T00:00:00Z");
+        let later = expiring("00l", "2026-06-05T00:00:00Z");
+
+        let warnings = expiry_warnings_at(
+            &[soon.clone(), later.clone()],
+            HOLDER,
+            &[minter()],
+            week,
+            now,
+        );
+        assert_eq!(
+            warnings,
+            vec![ExpiryWarning {
+                requirement: minter(),
+                contract_id: "00l".to_string(),
+                expires_at: at("2026-06-05T00:00:00Z"),
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Required credential from cbtc-network::1220 claiming hasCBTCRole=Minter (00l) \
+             expires at 2026-06-05T00:00:00+00:00"
+        );
+
+        // A credential valid for longer, or without expiry, covers the requirement
+        let renewed = expiring("00r", "2026-09-01T00:00:00Z");
+        let permanent = credential("00p", ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Minter")]);
+        for replacement in [renewed, permanent] {
+            assert!(
+                expiry_warnings_at(&[soon.clone(), replacement], HOLDER, &[minter()], week, now)
+                    .is_empty()
+            );
+        }
+        // Nothing to warn about if the requirement isn't met at all
+        assert!(expiry_warnings_at(&[], HOLDER, &[minter()], week, now).is_empty());
+    }
+
+    #[test]
+    fn credential_filter_matches_issuer_claim_and_validity() {
+        let now = at("2026-06-01T00:00:00Z");
+        let current = expiring("00c", "2026-06-02T00:00:00Z");
+        let expired = expiring("00e", "2026-05-01T00:00:00Z");
+        let other = credential("00o", "other::1220", vec![claim(HOLDER, "hasKYC", "true")]);
+        let matching = |filter: &CredentialFilter| -> Vec<String> {
+            [&current, &expired, &other]
+                .into_iter()
+                .filter(|c| filter.matches_at(c, now))
+                .map(|c| c.contract_id.clone())
+                .collect()
+        };
+
+        assert_eq!(matching(&CredentialFilter::default()).len(), 3);
+        assert_eq!(
+            matching(&CredentialFilter {
+                issuer: Some(ISSUER.to_string()),
+                ..Default::default()
+            }),
+            vec!["00c", "00e"]
+        );
+        assert_eq!(
+            matching(&CredentialFilter {
+                claim: Some(ClaimFilter {
+                    property: "hasKYC".to_string(),
+                    value: None,
+                }),
+                ..Default::default()
+            }),
+            vec!["00o"]
+        );
+        assert_eq!(
+            matching(&CredentialFilter {
+                issuer: Some(ISSUER.to_string()),
+                valid_only: true,
+                ..Default::default()
+            }),
+            vec!["00c"]
+        );
+    }
+
+    #[test]
+    fn filters_issued_credentials_by_claim() {
+        let mut bobs = credential(
+            "00c",
+            ISSUER,
+            vec![claim("bob::1220", "hasCBTCRole", "Minter")],
+        );
+        bobs.holder = "bob::1220".to_string();
+        let credentials = vec![
+            credential("00a", ISSUER, vec![claim(HOLDER, "hasCBTCRole", "Minter")]),
+            credential("00b", ISSUER, vec![claim(HOLDER, "hasKYC", "true")]),
+            bobs,
+            // A claim about someone other than the holder doesn't count
+            credential(
+                "00d",
+                ISSUER,
+                vec![claim("bob::1220", "hasCBTCRole", "Minter")],
+            ),
+        ];
+        let cids = |filter: Option<ClaimFilter>| -> Vec<String> {
+            filter_by_claim(credentials.clone(), filter.as_ref(), |c| {
+                (&c.claims, &c.holder)
+            })
+            .into_iter()
+            .map(|c| c.contract_id)
+            .collect()
+        };
+
+        assert_eq!(cids(None).len(), 4);
+        assert_eq!(
+            cids(Some(ClaimFilter {
+                property: "hasCBTCRole".to_string(),
+                value: Some("Minter".to_string()),
+            })),
+            vec!["00a", "00c"]
+        );
+        assert_eq!(
+            cids(Some(ClaimFilter {
+                property: "hasKYC".to_string(),
+                value: None,
+            })),
+            vec!["00b"]
+        );
+        assert!(
+            cids(Some(ClaimFilter {
+                property: "hasCBTCRole".to_string(),
+                value: Some("Viewer".to_string()),
+            }))
+            .is_empty()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -559,8 +1453,7 @@ mod parser_tests {
     };
     use serde_json::json;
 
-    const CRED_TID: &str =
-        "pkg-hash:Utility.Credential.V0.Credential:Credential";
+    const CRED_TID: &str = "pkg-hash:Utility.Credential.V0.Credential:Credential";
 
     fn credential_create_argument() -> serde_json::Value {
         json!({
@@ -609,6 +1502,83 @@ mod parser_tests {
         assert_eq!(cred.claims[0].value, "Minter");
     }
 
+    #[test]
+    fn parses_validity_period() {
+        let parse = |args: serde_json::Value| {
+            parse_accept_credential_offer_response(&transaction_response(
+                "tx-v",
+                json!([created_event_value(CRED_TID, "00cred", args)]),
+            ))
+        };
+
+        let cred = parse(credential_create_argument()).unwrap();
+        assert_eq!(cred.valid_from, None);
+        assert_eq!(cred.valid_until, None);
+
+        let mut args = credential_create_argument();
+        args["validFrom"] = json!("2026-01-01T00:00:00Z");
+        args["validUntil"] = json!("2027-01-01T00:00:00.000000Z");
+        let cred = parse(args.clone()).unwrap();
+        assert_eq!(
+            cred.valid_from.unwrap().to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            cred.valid_until.unwrap().to_rfc3339(),
+            "2027-01-01T00:00:00+00:00"
+        );
+
+        args["validUntil"] = json!("next year");
+        let err = parse(args).unwrap_err();
+        assert!(
+            err.contains("Invalid 'validUntil' field"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn offer_response_builds_credential_offer() {
+        let response = transaction_response(
+            "tx-2",
+            json!([
+                exercised_event_value(
+                    "pkg:Utility.Credential.App.V0.Service.User:UserService",
+                    "UserService_OfferFreeCredential",
+                    json!(null),
+                ),
+                created_event_value_with_blob(
+                    "pkg-hash:Utility.Credential.App.V0.Model.Offer:CredentialOffer",
+                    "00offer-cid",
+                    credential_create_argument(),
+                    "offer-blob",
+                ),
+            ]),
+        );
+
+        let offer = parse_offer_credential_response(&response).unwrap();
+        assert_eq!(offer.contract_id, "00offer-cid");
+        assert_eq!(offer.created_event_blob, "offer-blob");
+        assert_eq!(offer.issuer, "issuer::1220aaaa");
+        assert_eq!(offer.holder, "holder::1220bbbb");
+        assert_eq!(offer.claims.len(), 1);
+        assert_eq!(offer.claims[0].value, "Minter");
+
+        // A Credential is not an offer
+        let response = transaction_response(
+            "tx-3",
+            json!([created_event_value(
+                CRED_TID,
+                "00cred",
+                credential_create_argument(),
+            )]),
+        );
+        let err = parse_offer_credential_response(&response).unwrap_err();
+        assert!(
+            err.contains("No CredentialOffer contract was created"),
+            "unexpected error: {err}"
+        );
+    }
+
     #[test]
     fn missing_match_returns_err() {
         // CreatedEvent present but for a different template.
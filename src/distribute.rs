@@ -1,4 +1,5 @@
-use crate::{active_contracts, transfer};
+use crate::{active_contracts, consolidate, transfer};
+use common::decimal::DamlDecimal;
 
 pub struct Recipient {
     pub receiver: String,
@@ -21,13 +22,185 @@ pub struct Params {
     pub reference_base: Option<String>,
     // Optional callback for handling each transfer result
     pub on_transfer_complete: Option<Box<transfer::TransferResultCallback>>,
+    // Optional pre-flight check; when set, the run refuses to start unless it can complete
+    pub preflight: Option<PreflightOptions>,
+}
+
+/// Options for the pre-flight check run before any transfer is submitted.
+#[derive(Debug, Clone)]
+pub struct PreflightOptions {
+    /// Maximum number of UTXOs the run may start from. Every chained transfer
+    /// references all remaining holdings as inputs, so this bounds the input
+    /// count of each transaction. Canton has a soft limit of 10 UTXOs per party
+    /// per token type.
+    pub max_utxos: usize,
+    /// If set, run [`consolidate::check_and_consolidate`] with this threshold
+    /// before checking, so a fragmented party is merged instead of rejected.
+    pub consolidate_threshold: Option<usize>,
+}
+
+/// Parameters for a standalone pre-flight check.
+pub struct PreflightParams {
+    pub sender: String,
+    /// Total amount the run will send (sum of all recipient amounts)
+    pub required_amount: DamlDecimal,
+    pub options: PreflightOptions,
+    pub ledger_host: String,
+    pub access_token: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+}
+
+/// Outcome of a successful pre-flight check.
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    /// The holdings the run will start from (after any consolidation)
+    pub holding_cids: Vec<String>,
+    /// Sum of the selected holdings
+    pub available_amount: DamlDecimal,
+    /// Sum of all recipient amounts
+    pub required_amount: DamlDecimal,
+    /// Number of UTXOs before any consolidation
+    pub utxos_before: usize,
+    /// Whether holdings were consolidated during the check
+    pub consolidated: bool,
+}
+
+/// Sum the recipient amounts, rejecting non-positive entries.
+pub fn total_amount(recipients: &[Recipient]) -> Result<DamlDecimal, String> {
+    let mut total = DamlDecimal::ZERO;
+    for (idx, recipient) in recipients.iter().enumerate() {
+        if recipient.amount <= DamlDecimal::ZERO {
+            return Err(format!(
+                "Recipient {} ({}) has non-positive amount {}",
+                idx + 1,
+                recipient.receiver,
+                recipient.amount
+            ));
+        }
+        total += recipient.amount;
+    }
+    Ok(total)
+}
+
+/// Check that `available` across `utxo_count` holdings can fund `required`
+/// without exceeding `max_utxos` inputs.
+fn check_sufficiency(
+    available: DamlDecimal,
+    required: DamlDecimal,
+    utxo_count: usize,
+    max_utxos: usize,
+) -> Result<(), String> {
+    if utxo_count == 0 {
+        return Err("No UTXOs available for transfers".to_string());
+    }
+    if utxo_count > max_utxos {
+        return Err(format!(
+            "Party has {} UTXOs, exceeding the limit of {}; consolidate before distributing",
+            utxo_count, max_utxos
+        ));
+    }
+    if available < required {
+        return Err(format!(
+            "Insufficient funds: {} CBTC available across {} UTXOs, {} CBTC required",
+            available, utxo_count, required
+        ));
+    }
+    Ok(())
+}
+
+/// Fetch the sender's holdings and return their contract IDs and total amount.
+async fn fetch_holdings(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+) -> Result<(Vec<String>, DamlDecimal), String> {
+    let contracts = active_contracts::get(active_contracts::Params {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        access_token: access_token.to_string(),
+    })
+    .await?;
+
+    let available = contracts
+        .iter()
+        .filter_map(crate::utils::extract_amount)
+        .fold(DamlDecimal::ZERO, |acc, amount| acc + amount);
+    let holding_cids = contracts
+        .into_iter()
+        .map(|c| c.created_event.contract_id)
+        .collect();
+
+    Ok((holding_cids, available))
+}
+
+/// Verify that a distribution can complete before anything is submitted.
+///
+/// Sums the sender's holdings against `required_amount` and checks the UTXO
+/// count against `options.max_utxos`. If `options.consolidate_threshold` is
+/// set, holdings are consolidated first and re-fetched.
+///
+/// # Errors
+/// Returns an error string if the run cannot be completed (insufficient funds,
+/// too many UTXOs, no UTXOs) or if fetching/consolidating holdings fails.
+pub async fn preflight(params: PreflightParams) -> Result<PreflightReport, String> {
+    let (mut holding_cids, mut available_amount) =
+        fetch_holdings(&params.ledger_host, &params.sender, &params.access_token).await?;
+    let utxos_before = holding_cids.len();
+    let mut consolidated = false;
+
+    if let Some(threshold) = params.options.consolidate_threshold {
+        let result = consolidate::check_and_consolidate(consolidate::CheckConsolidateParams {
+            party: params.sender.clone(),
+            threshold,
+            ledger_host: params.ledger_host.clone(),
+            access_token: params.access_token.clone(),
+            registry_url: params.registry_url,
+            decentralized_party_id: params.decentralized_party_id,
+        })
+        .await?;
+
+        if result.consolidated {
+            log::debug!(
+                "Pre-flight consolidated {} UTXOs into {}",
+                result.utxos_before,
+                result.utxos_after
+            );
+            consolidated = true;
+            (holding_cids, available_amount) =
+                fetch_holdings(&params.ledger_host, &params.sender, &params.access_token).await?;
+        }
+    }
+
+    check_sufficiency(
+        available_amount,
+        params.required_amount,
+        holding_cids.len(),
+        params.options.max_utxos,
+    )?;
+
+    log::debug!(
+        "Pre-flight passed: {} available across {} UTXOs, {} required",
+        available_amount,
+        holding_cids.len(),
+        params.required_amount
+    );
+
+    Ok(PreflightReport {
+        holding_cids,
+        available_amount,
+        required_amount: params.required_amount,
+        utxos_before,
+        consolidated,
+    })
 }
 
 /// Distribute tokens to multiple recipients using sequential chained transfers.
 ///
 /// This function:
 /// 1. Authenticates with Keycloak
-/// 2. Fetches all available UTXOs once
+/// 2. Fetches all available UTXOs once (running the pre-flight check if
+///    `preflight` is set, which may consolidate first)
 /// 3. Creates transfers for each recipient
 /// 4. Submits transfers sequentially with JWT auto-refresh, chaining change outputs
 ///
@@ -51,23 +224,31 @@ pub async fn submit(params: Params) -> Result<transfer::SequentialChainedResult,
 
     let access_token = token_state.get_fresh_token().await?;
 
-    // Fetch all active contracts once
-    let contracts = active_contracts::get(active_contracts::Params {
-        ledger_host: params.ledger_host.clone(),
-        party: params.sender.clone(),
-        access_token: access_token.clone(),
-    })
-    .await?;
-
-    if contracts.is_empty() {
-        return Err("No UTXOs available for transfers".to_string());
-    }
-
-    // Collect all UTXO contract IDs as initial holdings
-    let initial_holding_cids: Vec<String> = contracts
-        .iter()
-        .map(|c| c.created_event.contract_id.clone())
-        .collect();
+    // Fetch all UTXOs once, verifying up front that the run can complete if requested
+    let initial_holding_cids = match params.preflight {
+        Some(options) => {
+            let report = preflight(PreflightParams {
+                sender: params.sender.clone(),
+                required_amount: total_amount(&params.recipients)?,
+                options,
+                ledger_host: params.ledger_host.clone(),
+                access_token: access_token.clone(),
+                registry_url: params.registry_url.clone(),
+                decentralized_party_id: params.decentralized_party_id.clone(),
+            })
+            .await
+            .map_err(|e| format!("Pre-flight check failed: {}", e))?;
+            report.holding_cids
+        }
+        None => {
+            let (holding_cids, _) =
+                fetch_holdings(&params.ledger_host, &params.sender, &access_token).await?;
+            if holding_cids.is_empty() {
+                return Err("No UTXOs available for transfers".to_string());
+            }
+            holding_cids
+        }
+    };
 
     log::debug!("Using {} initial UTXOs", initial_holding_cids.len());
 
@@ -109,49 +290,40 @@ pub async fn submit(params: Params) -> Result<transfer::SequentialChainedResult,
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::password_url;
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
     #[tokio::test]
     async fn test_distribute() {
-        dotenvy::dotenv().ok();
+        let env = TestEnv::load().await;
 
         let recipients = vec![
             Recipient {
-                receiver: env::var("LIB_TEST_RECEIVER_PARTY_ID")
-                    .expect("LIB_TEST_RECEIVER_PARTY_ID must be set"),
+                receiver: env.receiver(),
                 amount: common::decimal::DamlDecimal::parse("0.01").unwrap(),
             },
             Recipient {
-                receiver: env::var("LIB_TEST_RECEIVER_PARTY_ID")
-                    .expect("LIB_TEST_RECEIVER_PARTY_ID must be set"),
+                receiver: env.receiver(),
                 amount: common::decimal::DamlDecimal::parse("0.01").unwrap(),
             },
         ];
 
         let params = Params {
             recipients,
-            sender: env::var("PARTY_ID").expect("PARTY_ID must be set"),
+            sender: env.party(),
             instrument_id: common::transfer::InstrumentId {
-                admin: common::consts::DEVNET_DECENTRALIZED_PARTY_ID.to_string(),
+                admin: env.decentralized_party_id(),
                 id: "CBTC".to_string(),
             },
-            ledger_host: env::var("LEDGER_HOST").expect("LEDGER_HOST must be set"),
-            registry_url: env::var("REGISTRY_URL").expect("REGISTRY_URL must be set"),
-            decentralized_party_id: env::var("DECENTRALIZED_PARTY_ID")
-                .expect("DECENTRALIZED_PARTY_ID must be set"),
-            keycloak_client_id: env::var("KEYCLOAK_CLIENT_ID")
-                .expect("KEYCLOAK_CLIENT_ID must be set"),
-            keycloak_username: env::var("KEYCLOAK_USERNAME")
-                .expect("KEYCLOAK_USERNAME must be set"),
-            keycloak_password: env::var("KEYCLOAK_PASSWORD")
-                .expect("KEYCLOAK_PASSWORD must be set"),
-            keycloak_url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
+            ledger_host: env.ledger_host(),
+            registry_url: env.registry_url(),
+            decentralized_party_id: env.decentralized_party_id(),
+            keycloak_client_id: env.keycloak_client_id(),
+            keycloak_username: env.keycloak_username(),
+            keycloak_password: env.keycloak_password(),
+            keycloak_url: env.keycloak_url(),
             reference_base: Some("test-distribute-run-001".to_string()),
             on_transfer_complete: None,
+            preflight: None,
         };
 
         let result = submit(params).await.unwrap();
@@ -165,4 +337,56 @@ mod tests {
             "At least one transfer should succeed"
         );
     }
+
+    fn d(s: &str) -> DamlDecimal {
+        DamlDecimal::parse(s).unwrap()
+    }
+
+    fn recipient(amount: &str) -> Recipient {
+        Recipient {
+            receiver: "receiver::1220".to_string(),
+            amount: d(amount),
+        }
+    }
+
+    #[test]
+    fn total_amount_sums_recipients() {
+        let total = total_amount(&[recipient("0.1"), recipient("0.25")]).unwrap();
+        assert_eq!(total, d("0.35"));
+    }
+
+    #[test]
+    fn total_amount_rejects_zero_amount() {
+        let err = total_amount(&[recipient("0.1"), recipient("0")]).unwrap_err();
+        assert!(err.contains("Recipient 2"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn sufficiency_passes_when_covered() {
+        assert!(check_sufficiency(d("1"), d("1"), 3, 10).is_ok());
+    }
+
+    #[test]
+    fn sufficiency_rejects_insufficient_funds() {
+        let err = check_sufficiency(d("0.5"), d("1"), 3, 10).unwrap_err();
+        assert!(
+            err.contains("Insufficient funds"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn sufficiency_rejects_too_many_utxos() {
+        let err = check_sufficiency(d("5"), d("1"), 11, 10).unwrap_err();
+        assert!(
+            err.contains("exceeding the limit of 10"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn sufficiency_rejects_no_utxos() {
+        let err = check_sufficiency(DamlDecimal::ZERO, d("1"), 0, 10).unwrap_err();
+        assert!(err.contains("No UTXOs"), "unexpected error: {err}");
+    }
 }
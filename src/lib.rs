@@ -3,15 +3,21 @@ pub use common::decimal::DamlDecimal;
 pub mod accept;
 pub mod active_contracts;
 pub mod allocation;
+pub mod allocation_request;
 pub mod batch;
 pub mod cancel_offers;
+pub mod collateral;
 pub mod consolidate;
 pub mod credentials;
 pub mod dar_check;
 pub mod distribute;
 mod event_helpers;
 pub mod mint_redeem;
+#[cfg(any(test, feature = "mock-server"))]
+pub mod mock_server;
 pub mod reject;
+pub mod settlement;
 pub mod split;
+pub mod swap;
 pub mod transfer;
 pub mod utils;
@@ -0,0 +1,170 @@
+//! Local, human-readable labels for withdraw accounts.
+//!
+//! Labels never go on the ledger; they live in a JSON file next to the
+//! caller's own configuration. They are keyed by the account's stable
+//! `account_id`, so a label follows the account across withdrawals and
+//! destination address updates, which both recreate the contract.
+
+use crate::mint_redeem::models::WithdrawAccount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Labels for withdraw accounts, keyed by `account_id`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccountLabels {
+    labels: BTreeMap<String, String>,
+}
+
+impl AccountLabels {
+    /// Read labels from `path`. A missing file yields no labels.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    /// Write labels to `path`, creating its parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize labels: {}", e))?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Label an account, replacing any previous label. Labels must be
+    /// non-empty and unique, so they can be used to look accounts up.
+    pub fn set(&mut self, account: &WithdrawAccount, label: &str) -> Result<(), String> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err("Label must not be empty".to_string());
+        }
+        if let Some((other, _)) = self
+            .labels
+            .iter()
+            .find(|(id, l)| l.as_str() == label && **id != account.account_id)
+        {
+            return Err(format!(
+                "Label '{}' is already used by account {}",
+                label, other
+            ));
+        }
+        self.labels
+            .insert(account.account_id.clone(), label.to_string());
+        Ok(())
+    }
+
+    /// Remove an account's label, returning it
+    pub fn remove(&mut self, account: &WithdrawAccount) -> Option<String> {
+        self.labels.remove(&account.account_id)
+    }
+
+    /// The account's label, if it has one
+    pub fn get(&self, account: &WithdrawAccount) -> Option<&str> {
+        self.labels.get(&account.account_id).map(String::as_str)
+    }
+
+    /// The account in `accounts` carrying `label`
+    pub fn find<'a>(
+        &self,
+        accounts: &'a [WithdrawAccount],
+        label: &str,
+    ) -> Option<&'a WithdrawAccount> {
+        accounts.iter().find(|a| self.get(a) == Some(label.trim()))
+    }
+
+    /// Drop labels of accounts no longer in `accounts` (e.g. closed ones)
+    pub fn retain_active(&mut self, accounts: &[WithdrawAccount]) {
+        self.labels
+            .retain(|id, _| accounts.iter().any(|a| a.account_id == *id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::decimal::DamlDecimal;
+
+    fn account(contract_id: &str, account_id: &str) -> WithdrawAccount {
+        WithdrawAccount {
+            contract_id: contract_id.to_string(),
+            account_id: account_id.to_string(),
+            template_id: String::new(),
+            owner: "alice::1220deadbeef".to_string(),
+            operator: "operator::1220ababab".to_string(),
+            registrar: "registrar::1220cdcdcd".to_string(),
+            destination_btc_address: "bcrt1qdest".to_string(),
+            pending_balance: DamlDecimal::ZERO,
+            created_event_blob: String::new(),
+            limits: None,
+        }
+    }
+
+    #[test]
+    fn label_follows_recreated_account() {
+        let mut labels = AccountLabels::default();
+        labels
+            .set(&account("00v1", "00acct"), " cold wallet ")
+            .unwrap();
+
+        let recreated = account("00v2", "00acct");
+        assert_eq!(labels.get(&recreated), Some("cold wallet"));
+        let accounts = [account("00other", "00other"), recreated];
+        assert_eq!(
+            labels.find(&accounts, "cold wallet").unwrap().contract_id,
+            "00v2"
+        );
+    }
+
+    #[test]
+    fn set_rejects_empty_and_duplicate_labels() {
+        let mut labels = AccountLabels::default();
+        assert!(labels.set(&account("00a", "00a"), "  ").is_err());
+
+        labels.set(&account("00a", "00a"), "treasury").unwrap();
+        let err = labels.set(&account("00b", "00b"), "treasury").unwrap_err();
+        assert!(
+            err.contains("already used by account 00a"),
+            "unexpected error: {err}"
+        );
+        // Relabelling the same account is fine
+        labels.set(&account("00a", "00a"), "treasury").unwrap();
+    }
+
+    #[test]
+    fn retain_active_drops_closed_accounts() {
+        let mut labels = AccountLabels::default();
+        labels.set(&account("00a", "00a"), "old").unwrap();
+        labels.set(&account("00b", "00b"), "new").unwrap();
+
+        labels.retain_active(&[account("00b", "00b")]);
+        assert_eq!(labels.get(&account("00a", "00a")), None);
+        assert_eq!(labels.get(&account("00b", "00b")), Some("new"));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("cbtc-labels-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("labels.json");
+        assert_eq!(
+            AccountLabels::load(&path).unwrap(),
+            AccountLabels::default()
+        );
+
+        let mut labels = AccountLabels::default();
+        labels.set(&account("00a", "00a"), "treasury").unwrap();
+        labels.save(&path).unwrap();
+        assert_eq!(AccountLabels::load(&path).unwrap(), labels);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
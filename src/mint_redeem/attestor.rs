@@ -1,6 +1,470 @@
+//! Client for the Bitsafe (attestor network) API, which serves the CBTC
+//! contracts needed for minting and redeeming and the Bitcoin addresses of
+//! deposit accounts under `/cbtc/v1/`.
+//!
+//! [`BitsafeClient`] reuses one HTTP client across calls, applies timeouts,
+//! retries transient failures and decodes error responses into
+//! [`BitsafeError`]. The free functions are shorthands that use a shared
+//! client with the default configuration.
+
 use crate::mint_redeem::models::{
     AccountContractRuleSet, BitcoinAddressResponse, TokenStandardContracts,
 };
+use serde::de::DeserializeOwned;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Default timeout for a whole Bitsafe API request
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout for connecting to the Bitsafe API
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest error body kept in a [`BitsafeError::Status`] message
+const MAX_ERROR_BODY_LEN: usize = 512;
+
+/// When to retry a failed Bitsafe API request
+///
+/// Only failures that may succeed on a second attempt are retried: timeouts,
+/// connection errors, `429 Too Many Requests` and `5xx` responses. Every
+/// endpoint is a read, so retrying is always safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Wait before the first retry; doubled for each further retry
+    pub initial_backoff: Duration,
+    /// Upper bound on the wait between attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `retry` (starting at 0)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+/// How many attestors must have returned a deposit account's Bitcoin address
+/// before it is handed out
+///
+/// The Bitsafe API reports how many attestors it asked for an address and how
+/// many answered. An address backed by fewer than `min_fraction` of them is
+/// not returned; instead the address is re-requested every `poll_interval`
+/// until the quorum is reached or `max_wait` has passed.
+///
+/// Where the params take an `Option<QuorumPolicy>`, the address is only
+/// returned once this quorum is met; `None` returns it as is. The attestor
+/// counts are in the status either way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuorumPolicy {
+    /// Fraction of requested attestors that must respond, in `0.0..=1.0`
+    pub min_fraction: f64,
+    /// How long to keep polling; zero checks once without waiting
+    pub max_wait: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for QuorumPolicy {
+    /// A two-thirds majority, waiting up to a minute
+    fn default() -> Self {
+        Self {
+            min_fraction: 2.0 / 3.0,
+            max_wait: Duration::from_secs(60),
+            poll_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl QuorumPolicy {
+    /// Whether `response` is backed by enough attestors
+    pub fn is_met(&self, response: &BitcoinAddressResponse) -> bool {
+        response.attestors_requested > 0
+            && f64::from(response.attestors_responded)
+                >= self.min_fraction * f64::from(response.attestors_requested)
+    }
+}
+
+/// Configuration for a [`BitsafeClient`]
+#[derive(Debug, Clone)]
+pub struct BitsafeClientConfig {
+    /// Base URL of the Bitsafe API (e.g., "https://api.mainnet.bitsafe.finance")
+    pub api_url: String,
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl BitsafeClientConfig {
+    /// Default timeouts and retry policy for `api_url`
+    pub fn new(api_url: impl Into<String>) -> Self {
+        Self {
+            api_url: api_url.into(),
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// A failed Bitsafe API request
+#[derive(Debug, Clone, PartialEq)]
+pub enum BitsafeError {
+    /// The request could not be sent or no response arrived in time
+    Request {
+        url: String,
+        message: String,
+        timed_out: bool,
+    },
+    /// The API answered with a non-success status. `code` and `message` are
+    /// taken from the JSON error body when there is one, otherwise `message`
+    /// is the (truncated) body text.
+    Status {
+        url: String,
+        status: u16,
+        code: Option<String>,
+        message: String,
+    },
+    /// The response body was not the expected JSON
+    Decode { url: String, message: String },
+    /// Too few attestors agreed on an account's Bitcoin address within the
+    /// [`QuorumPolicy`]'s wait
+    QuorumNotReached {
+        account_id: String,
+        attestors_requested: u32,
+        attestors_responded: u32,
+        min_fraction: f64,
+    },
+}
+
+impl BitsafeError {
+    /// Whether the request may succeed if retried
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Request { .. } => true,
+            Self::Status { status, .. } => *status == 429 || *status >= 500,
+            Self::Decode { .. } | Self::QuorumNotReached { .. } => false,
+        }
+    }
+
+    /// Whether the API reported that the resource doesn't exist, e.g. an
+    /// account it hasn't indexed yet
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Self::Status { status: 404, .. })
+    }
+
+    /// HTTP status of the response, if one was received
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BitsafeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Request {
+                url,
+                message,
+                timed_out: true,
+            } => write!(f, "Bitsafe API request to {} timed out: {}", url, message),
+            Self::Request { url, message, .. } => {
+                write!(
+                    f,
+                    "Failed to send request to Bitsafe API ({}): {}",
+                    url, message
+                )
+            }
+            Self::Status {
+                url,
+                status,
+                code: Some(code),
+                message,
+            } => write!(
+                f,
+                "Bitsafe API returned error status {} for {}: {} ({})",
+                status, url, message, code
+            ),
+            Self::Status {
+                url,
+                status,
+                code: None,
+                message,
+            } => write!(
+                f,
+                "Bitsafe API returned error status {} for {}: {}",
+                status, url, message
+            ),
+            Self::Decode { url, message } => {
+                write!(
+                    f,
+                    "Failed to parse Bitsafe API response from {}: {}",
+                    url, message
+                )
+            }
+            Self::QuorumNotReached {
+                account_id,
+                attestors_requested,
+                attestors_responded,
+                min_fraction,
+            } => write!(
+                f,
+                "Only {} of {} attestors returned the Bitcoin address for account {} \
+                 ({:.0}% required)",
+                attestors_responded,
+                attestors_requested,
+                account_id,
+                min_fraction * 100.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BitsafeError {}
+
+impl From<BitsafeError> for String {
+    fn from(error: BitsafeError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Extract the error code and message from an error response body.
+///
+/// Understands `{"error": "..."}`, `{"message": "..."}`, `{"detail": "..."}`
+/// and `{"error": {"code": "...", "message": "..."}}`, with an optional
+/// top-level `code`; anything else is returned as (truncated) text.
+fn parse_error_body(body: &str) -> (Option<String>, String) {
+    fn text(value: Option<&serde_json::Value>) -> Option<String> {
+        match value? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    if let Ok(serde_json::Value::Object(obj)) = serde_json::from_str::<serde_json::Value>(body) {
+        let nested = obj.get("error").and_then(|e| e.as_object());
+        let message = ["message", "error", "detail"]
+            .iter()
+            .find_map(|key| text(obj.get(*key)))
+            .or_else(|| nested.and_then(|e| text(e.get("message"))));
+        let code = text(obj.get("code")).or_else(|| nested.and_then(|e| text(e.get("code"))));
+        if let Some(message) = message {
+            return (code, message);
+        }
+    }
+
+    let body = body.trim();
+    let message = match body.char_indices().nth(MAX_ERROR_BODY_LEN) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None if body.is_empty() => "(empty response body)".to_string(),
+        None => body.to_string(),
+    };
+    (None, message)
+}
+
+/// HTTP client shared by [`BitsafeClient::new`] and the free functions
+fn shared_http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+            .build()
+            .expect("Failed to build Bitsafe HTTP client")
+    })
+}
+
+/// Typed client for the Bitsafe API `/cbtc/v1/` endpoints
+///
+/// Cloning is cheap and shares the underlying connection pool.
+///
+/// # Example
+/// ```ignore
+/// let client = BitsafeClient::with_config(BitsafeClientConfig {
+///     timeout: Duration::from_secs(10),
+///     ..BitsafeClientConfig::new("https://api.mainnet.bitsafe.finance")
+/// })?;
+/// let contracts = client.get_token_standard_contracts().await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitsafeClient {
+    api_url: String,
+    http: reqwest::Client,
+    retry: RetryPolicy,
+}
+
+impl BitsafeClient {
+    /// Client for `api_url` with the default timeouts and retry policy
+    pub fn new(api_url: &str) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http: shared_http_client().clone(),
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Client with custom timeouts and retry policy
+    pub fn with_config(config: BitsafeClientConfig) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .build()
+            .map_err(|e| format!("Failed to build Bitsafe HTTP client: {}", e))?;
+        Ok(Self {
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            http,
+            retry: config.retry,
+        })
+    }
+
+    /// Base URL of the API this client talks to
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Get the Bitcoin address for a deposit or withdraw account, with how
+    /// many attestors were asked and answered
+    ///
+    /// `account_id` is the account ID (UUID when present, otherwise contract
+    /// ID) of the deposit/withdraw account.
+    pub async fn get_bitcoin_address(
+        &self,
+        account_id: &str,
+    ) -> Result<BitcoinAddressResponse, BitsafeError> {
+        self.get_json(&format!("/cbtc/v1/bitcoin-address/{}", account_id))
+            .await
+    }
+
+    /// Get the Bitcoin address for an account once `quorum` is met
+    ///
+    /// Polls [`get_bitcoin_address`](Self::get_bitcoin_address) until enough
+    /// attestors have responded. While waiting, a `404` (an account the
+    /// attestors haven't seen yet) is treated like a missing quorum.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BitsafeError::QuorumNotReached`] if the quorum isn't met
+    /// within `quorum.max_wait`, or the last request error.
+    pub async fn get_bitcoin_address_with_quorum(
+        &self,
+        account_id: &str,
+        quorum: &QuorumPolicy,
+    ) -> Result<BitcoinAddressResponse, BitsafeError> {
+        let deadline = tokio::time::Instant::now() + quorum.max_wait;
+        loop {
+            let error = match self.get_bitcoin_address(account_id).await {
+                Ok(response) if quorum.is_met(&response) => return Ok(response),
+                Ok(response) => BitsafeError::QuorumNotReached {
+                    account_id: account_id.to_string(),
+                    attestors_requested: response.attestors_requested,
+                    attestors_responded: response.attestors_responded,
+                    min_fraction: quorum.min_fraction,
+                },
+                Err(e) if e.is_not_found() => e,
+                Err(e) => return Err(e),
+            };
+            if tokio::time::Instant::now() + quorum.poll_interval > deadline {
+                return Err(error);
+            }
+            log::debug!("Waiting for attestor quorum on {}: {}", account_id, error);
+            tokio::time::sleep(quorum.poll_interval).await;
+        }
+    }
+
+    /// Get the DepositAccountRules and WithdrawAccountRules contracts
+    pub async fn get_account_contract_rules(&self) -> Result<AccountContractRuleSet, BitsafeError> {
+        self.get_json("/cbtc/v1/account-contract-rules").await
+    }
+
+    /// Get the token standard contracts (burn_mint_factory,
+    /// instrument_configuration, issuer_credential)
+    pub async fn get_token_standard_contracts(
+        &self,
+    ) -> Result<TokenStandardContracts, BitsafeError> {
+        self.get_json("/cbtc/v1/token-standard-contracts").await
+    }
+
+    /// GET `path` and decode the JSON response, retrying transient failures
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, BitsafeError> {
+        let url = format!("{}{}", self.api_url, path);
+        let mut retry = 0;
+        loop {
+            match self.get_json_once(&url).await {
+                Err(e) if e.is_retryable() && retry < self.retry.max_retries => {
+                    let backoff = self.retry.backoff(retry);
+                    log::debug!(
+                        "Retrying Bitsafe API request in {:?} ({}/{}): {}",
+                        backoff,
+                        retry + 1,
+                        self.retry.max_retries,
+                        e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn get_json_once<T: DeserializeOwned>(&self, url: &str) -> Result<T, BitsafeError> {
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| BitsafeError::Request {
+                url: url.to_string(),
+                message: e.to_string(),
+                timed_out: e.is_timeout(),
+            })?;
+
+        let status = response.status();
+        let body = response.text().await.map_err(|e| BitsafeError::Request {
+            url: url.to_string(),
+            message: e.to_string(),
+            timed_out: e.is_timeout(),
+        })?;
+
+        if !status.is_success() {
+            let (code, message) = parse_error_body(&body);
+            return Err(BitsafeError::Status {
+                url: url.to_string(),
+                status: status.as_u16(),
+                code,
+                message,
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|e| BitsafeError::Decode {
+            url: url.to_string(),
+            message: e.to_string(),
+        })
+    }
+}
 
 /// Get the Bitcoin address for a deposit or withdraw account
 ///
@@ -20,28 +484,35 @@ use crate::mint_redeem::models::{
 /// println!("BTC address: {}", bitcoin_address);
 /// ```
 pub async fn get_bitcoin_address(api_url: &str, account_id: &str) -> Result<String, String> {
-    let url = format!("{}/cbtc/v1/bitcoin-address/{}", api_url, account_id);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Bitsafe API: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Bitsafe API returned error status: {}",
-            response.status()
-        ));
-    }
-
-    let bitcoin_address_response: BitcoinAddressResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    let response = BitsafeClient::new(api_url)
+        .get_bitcoin_address(account_id)
+        .await?;
+    Ok(response.bitcoin_address)
+}
 
-    Ok(bitcoin_address_response.bitcoin_address)
+/// Get the Bitcoin address for an account, with the attestor counts, once at
+/// least `quorum.min_fraction` of the attestors have returned it
+///
+/// # Example
+/// ```ignore
+/// let response = get_bitcoin_address_with_quorum(
+///     "https://api.mainnet.bitsafe.finance",
+///     "00febb6b97f5d214bb...",
+///     &QuorumPolicy::default(),
+/// ).await?;
+/// println!(
+///     "BTC address: {} ({}/{} attestors)",
+///     response.bitcoin_address, response.attestors_responded, response.attestors_requested
+/// );
+/// ```
+pub async fn get_bitcoin_address_with_quorum(
+    api_url: &str,
+    account_id: &str,
+    quorum: &QuorumPolicy,
+) -> Result<BitcoinAddressResponse, String> {
+    Ok(BitsafeClient::new(api_url)
+        .get_bitcoin_address_with_quorum(account_id, quorum)
+        .await?)
 }
 
 /// Get the account contract rules from the Bitsafe API
@@ -59,28 +530,9 @@ pub async fn get_bitcoin_address(api_url: &str, account_id: &str) -> Result<Stri
 /// ).await?;
 /// ```
 pub async fn get_account_contract_rules(api_url: &str) -> Result<AccountContractRuleSet, String> {
-    let url = format!("{}/cbtc/v1/account-contract-rules", api_url);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Bitsafe API: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Bitsafe API returned error status: {}",
-            response.status()
-        ));
-    }
-
-    let rules: AccountContractRuleSet = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    Ok(rules)
+    Ok(BitsafeClient::new(api_url)
+        .get_account_contract_rules()
+        .await?)
 }
 
 /// Get the token standard contracts from the Bitsafe API
@@ -98,40 +550,33 @@ pub async fn get_account_contract_rules(api_url: &str) -> Result<AccountContract
 /// ).await?;
 /// ```
 pub async fn get_token_standard_contracts(api_url: &str) -> Result<TokenStandardContracts, String> {
-    let url = format!("{}/cbtc/v1/token-standard-contracts", api_url);
-
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to send request to Bitsafe API: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Bitsafe API returned error status: {}",
-            response.status()
-        ));
-    }
-
-    let contracts: TokenStandardContracts = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    Ok(contracts)
+    Ok(BitsafeClient::new(api_url)
+        .get_token_standard_contracts()
+        .await?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock_server::MockServer;
     use std::env;
 
-    #[tokio::test]
-    async fn test_get_account_contract_rules() {
+    /// `BITSAFE_API_URL` when set, otherwise a local mock server (kept alive
+    /// by the returned handle)
+    async fn bitsafe_api_url() -> (String, Option<MockServer>) {
         dotenvy::dotenv().ok();
+        match env::var("BITSAFE_API_URL") {
+            Ok(api_url) => (api_url, None),
+            Err(_) => {
+                let server = MockServer::start().await.unwrap();
+                (server.bitsafe_api_url(), Some(server))
+            }
+        }
+    }
 
-        let api_url = env::var("BITSAFE_API_URL").expect("BITSAFE_API_URL must be set");
+    #[tokio::test]
+    async fn test_get_account_contract_rules() {
+        let (api_url, _server) = bitsafe_api_url().await;
 
         let rules = get_account_contract_rules(&api_url)
             .await
@@ -143,9 +588,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_token_standard_contracts() {
-        dotenvy::dotenv().ok();
-
-        let api_url = env::var("BITSAFE_API_URL").expect("BITSAFE_API_URL must be set");
+        let (api_url, _server) = bitsafe_api_url().await;
 
         let contracts = get_token_standard_contracts(&api_url)
             .await
@@ -155,4 +598,144 @@ mod tests {
         assert!(!contracts.instrument_configuration.contract_id.is_empty());
         assert!(!contracts.issuer_credential.contract_id.is_empty());
     }
+
+    #[test]
+    fn parse_error_body_reads_json_messages() {
+        assert_eq!(
+            parse_error_body(r#"{"error": "account not found"}"#),
+            (None, "account not found".to_string())
+        );
+        assert_eq!(
+            parse_error_body(r#"{"code": "QUORUM", "message": "not enough attestors"}"#),
+            (
+                Some("QUORUM".to_string()),
+                "not enough attestors".to_string()
+            )
+        );
+        assert_eq!(
+            parse_error_body(r#"{"error": {"code": 503, "message": "unavailable"}}"#),
+            (Some("503".to_string()), "unavailable".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_error_body_falls_back_to_text() {
+        assert_eq!(
+            parse_error_body("  Bad Gateway\n"),
+            (None, "Bad Gateway".to_string())
+        );
+        assert_eq!(
+            parse_error_body(""),
+            (None, "(empty response body)".to_string())
+        );
+
+        let long = "x".repeat(MAX_ERROR_BODY_LEN + 10);
+        let (_, message) = parse_error_body(&long);
+        assert_eq!(message.len(), MAX_ERROR_BODY_LEN + 3);
+        assert!(message.ends_with("..."));
+    }
+
+    #[test]
+    fn retry_policy_backs_off_exponentially_up_to_max() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(350),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(350));
+        assert_eq!(policy.backoff(40), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn only_transient_errors_are_retryable() {
+        let status = |status| BitsafeError::Status {
+            url: "u".to_string(),
+            status,
+            code: None,
+            message: String::new(),
+        };
+        assert!(status(503).is_retryable());
+        assert!(status(429).is_retryable());
+        assert!(!status(404).is_retryable());
+        assert!(status(404).is_not_found());
+        assert!(
+            BitsafeError::Request {
+                url: "u".to_string(),
+                message: String::new(),
+                timed_out: true,
+            }
+            .is_retryable()
+        );
+        assert!(
+            !BitsafeError::Decode {
+                url: "u".to_string(),
+                message: String::new(),
+            }
+            .is_retryable()
+        );
+    }
+
+    #[test]
+    fn status_error_message_includes_body() {
+        let error = BitsafeError::Status {
+            url: "https://api/cbtc/v1/bitcoin-address/00abc".to_string(),
+            status: 404,
+            code: Some("NOT_FOUND".to_string()),
+            message: "account not found".to_string(),
+        };
+        assert_eq!(
+            String::from(error),
+            "Bitsafe API returned error status 404 for \
+             https://api/cbtc/v1/bitcoin-address/00abc: account not found (NOT_FOUND)"
+        );
+    }
+
+    fn address_response(requested: u32, responded: u32) -> BitcoinAddressResponse {
+        BitcoinAddressResponse {
+            bitcoin_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            attestors_requested: requested,
+            attestors_responded: responded,
+        }
+    }
+
+    #[test]
+    fn quorum_policy_requires_fraction_of_requested_attestors() {
+        let quorum = QuorumPolicy::default();
+        assert!(quorum.is_met(&address_response(3, 2)));
+        assert!(quorum.is_met(&address_response(9, 9)));
+        assert!(!quorum.is_met(&address_response(3, 1)));
+        assert!(!quorum.is_met(&address_response(10, 6)));
+        // No attestors asked means no agreement at all
+        assert!(!quorum.is_met(&address_response(0, 0)));
+
+        let all = QuorumPolicy {
+            min_fraction: 1.0,
+            ..quorum
+        };
+        assert!(!all.is_met(&address_response(5, 4)));
+        assert!(all.is_met(&address_response(5, 5)));
+    }
+
+    #[test]
+    fn quorum_not_reached_message() {
+        let error = BitsafeError::QuorumNotReached {
+            account_id: "00abc".to_string(),
+            attestors_requested: 5,
+            attestors_responded: 2,
+            min_fraction: 2.0 / 3.0,
+        };
+        assert!(!error.is_retryable());
+        assert_eq!(
+            error.to_string(),
+            "Only 2 of 5 attestors returned the Bitcoin address for account 00abc (67% required)"
+        );
+    }
+
+    #[test]
+    fn client_trims_trailing_slash() {
+        let client = BitsafeClient::new("https://api.devnet.bitsafe.finance/");
+        assert_eq!(client.api_url(), "https://api.devnet.bitsafe.finance");
+    }
 }
@@ -0,0 +1,432 @@
+//! Bitcoin address validation for withdraw destinations.
+//!
+//! A destination on the wrong network, or with a typo, means the BTC payout
+//! is lost, so addresses are fully decoded and checksummed before a withdraw
+//! account is created: base58check for P2PKH/P2SH, bech32 (BIP 173) for
+//! witness v0 (P2WPKH/P2WSH) and bech32m (BIP 350) for v1 (P2TR).
+//!
+//! The network is set explicitly where possible (`BITCOIN_NETWORK`), since a
+//! custom or proxied Bitsafe API URL says nothing about it; see
+//! [`BitcoinNetwork::resolve`].
+
+use std::str::FromStr;
+
+use bech32::primitives::decode::{
+    CharError, SegwitHrpstring, UncheckedHrpstring, UncheckedHrpstringError,
+};
+use bech32::primitives::segwit::{MAX_STRING_LENGTH, VERSION_0, VERSION_1};
+use bech32::{Bech32, Bech32m};
+use sha2::{Digest, Sha256};
+
+/// Bitcoin network a CBTC environment pays out on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitcoinNetwork {
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl BitcoinNetwork {
+    /// The network named by `setting` (e.g. the `BITCOIN_NETWORK` env var),
+    /// falling back to [`Self::from_api_url`] when it is unset or empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `setting` is not a known network name, or if it is
+    /// unset and the network cannot be inferred from `api_url`.
+    pub fn resolve(setting: Option<&str>, api_url: &str) -> Result<Self, String> {
+        match setting.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(name) => name.parse(),
+            None => Self::from_api_url(api_url).ok_or_else(|| {
+                format!(
+                    "Cannot infer the Bitcoin network from {}; set BITCOIN_NETWORK to mainnet, testnet or regtest",
+                    api_url
+                )
+            }),
+        }
+    }
+
+    /// Infer the network from the Bitsafe API URL of the environment
+    /// (`api.mainnet...` → mainnet, `api.testnet...` → testnet, `api.devnet...`
+    /// or a local URL → regtest).
+    pub fn from_api_url(api_url: &str) -> Option<Self> {
+        let url = api_url.to_ascii_lowercase();
+        if url.contains("mainnet") {
+            Some(Self::Mainnet)
+        } else if url.contains("testnet") {
+            Some(Self::Testnet)
+        } else if url.contains("devnet") || url.contains("localhost") || url.contains("127.0.0.1") {
+            Some(Self::Regtest)
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable part of this network's segwit addresses
+    pub fn bech32_hrp(self) -> &'static str {
+        match self {
+            Self::Mainnet => "bc",
+            Self::Testnet => "tb",
+            Self::Regtest => "bcrt",
+        }
+    }
+
+    /// Base58check version bytes for (P2PKH, P2SH). Testnet and regtest share them.
+    fn base58_versions(self) -> (u8, u8) {
+        match self {
+            Self::Mainnet => (0x00, 0x05),
+            Self::Testnet | Self::Regtest => (0x6f, 0xc4),
+        }
+    }
+}
+
+impl FromStr for BitcoinNetwork {
+    type Err = String;
+
+    /// Parse `mainnet`, `testnet` or `regtest` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(format!(
+                "Unknown Bitcoin network '{}', expected mainnet, testnet or regtest",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for BitcoinNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Regtest => "regtest",
+        };
+        write!(f, "{} ({})", name, self.bech32_hrp())
+    }
+}
+
+/// Kind of output script an address pays to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    P2pkh,
+    P2sh,
+    P2wpkh,
+    P2wsh,
+    P2tr,
+}
+
+/// Validate `address` as a destination on `network`.
+///
+/// # Example
+/// ```ignore
+/// let network: BitcoinNetwork = "mainnet".parse()?;
+/// let kind = bitcoin_address::validate("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", network)?;
+/// assert_eq!(kind, AddressType::P2wpkh);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error string saying precisely why the address was rejected:
+/// bad characters, a checksum mismatch, a wrong length or witness version,
+/// or an address for another network.
+pub fn validate(address: &str, network: BitcoinNetwork) -> Result<AddressType, String> {
+    if address.is_empty() {
+        return Err("Bitcoin address is empty".to_string());
+    }
+    if address.trim() != address {
+        return Err("Bitcoin address has leading or trailing whitespace".to_string());
+    }
+
+    // Segwit addresses start with a known HRP followed by the separator '1'.
+    // "bcrt" must be checked before "bc".
+    let lower = address.to_ascii_lowercase();
+    let segwit_hrp = ["bcrt", "bc", "tb"]
+        .into_iter()
+        .find(|hrp| lower.starts_with(&format!("{}1", hrp)));
+
+    match segwit_hrp {
+        Some(_) => validate_segwit(address, network),
+        None => validate_base58(address, network),
+    }
+}
+
+// ---------- base58check (P2PKH / P2SH) ----------
+
+fn validate_base58(address: &str, network: BitcoinNetwork) -> Result<AddressType, String> {
+    let bytes = bs58::decode(address).into_vec().map_err(|e| match e {
+        bs58::decode::Error::InvalidCharacter { character, index } => format!(
+            "Invalid base58 character '{}' at position {}",
+            character, index
+        ),
+        other => format!("Invalid base58 address: {}", other),
+    })?;
+    if bytes.len() != 25 {
+        return Err(format!(
+            "Invalid base58 address length: {} bytes, expected 25",
+            bytes.len()
+        ));
+    }
+
+    let (payload, checksum) = bytes.split_at(21);
+    if Sha256::digest(Sha256::digest(payload))[..4] != *checksum {
+        return Err("Invalid base58check checksum".to_string());
+    }
+
+    let version = payload[0];
+    let (p2pkh, p2sh) = network.base58_versions();
+    if version == p2pkh {
+        return Ok(AddressType::P2pkh);
+    }
+    if version == p2sh {
+        return Ok(AddressType::P2sh);
+    }
+
+    let address_network = match version {
+        0x00 | 0x05 => "mainnet",
+        0x6f | 0xc4 => "testnet/regtest",
+        _ => {
+            return Err(format!(
+                "Unknown base58 address version byte 0x{:02x}",
+                version
+            ));
+        }
+    };
+    Err(format!(
+        "Address is for {}, expected {}",
+        address_network, network
+    ))
+}
+
+// ---------- bech32 / bech32m (segwit) ----------
+
+fn validate_segwit(address: &str, network: BitcoinNetwork) -> Result<AddressType, String> {
+    let unchecked = UncheckedHrpstring::new(address).map_err(|e| match e {
+        UncheckedHrpstringError::Char(CharError::MixedCase) => {
+            "Segwit address mixes upper and lower case".to_string()
+        }
+        UncheckedHrpstringError::Char(CharError::InvalidChar(c)) => {
+            format!("Invalid bech32 character '{}'", c)
+        }
+        other => format!("Invalid segwit address: {}", other),
+    })?;
+
+    let hrp = unchecked.hrp().to_lowercase();
+    if hrp != network.bech32_hrp() {
+        return Err(format!(
+            "Address is for the '{}' network, expected {}",
+            hrp, network
+        ));
+    }
+    if address.len() > MAX_STRING_LENGTH {
+        return Err(format!(
+            "Segwit address is {} characters, at most {} allowed",
+            address.len(),
+            MAX_STRING_LENGTH
+        ));
+    }
+
+    // Tell a wrong checksum variant apart from a plain typo before handing
+    // the address to the segwit decoder, which only reports "invalid checksum".
+    let is_bech32 = unchecked.has_valid_checksum::<Bech32>();
+    let is_bech32m = unchecked.has_valid_checksum::<Bech32m>();
+    if !is_bech32 && !is_bech32m {
+        return Err("Invalid bech32 checksum".to_string());
+    }
+    match unchecked.witness_version() {
+        Some(VERSION_0) if !is_bech32 => {
+            return Err("Witness v0 address must use bech32, not bech32m".to_string());
+        }
+        Some(VERSION_1) if !is_bech32m => {
+            return Err("Witness v1 address must use bech32m, not bech32".to_string());
+        }
+        _ => {}
+    }
+
+    let decoded =
+        SegwitHrpstring::new(address).map_err(|e| format!("Invalid segwit address: {}", e))?;
+    let witness_version = decoded.witness_version().to_u8();
+    let program_len = decoded.byte_iter().len();
+
+    match (witness_version, program_len) {
+        (0, 20) => Ok(AddressType::P2wpkh),
+        (0, 32) => Ok(AddressType::P2wsh),
+        (0, n) => Err(format!(
+            "Invalid witness v0 program length {} bytes, expected 20 (P2WPKH) or 32 (P2WSH)",
+            n
+        )),
+        (1, 32) => Ok(AddressType::P2tr),
+        (1, n) => Err(format!(
+            "Invalid witness v1 program length {} bytes, expected 32 (P2TR)",
+            n
+        )),
+        (v, _) => Err(format!("Witness v{} addresses are not supported", v)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_from_api_url() {
+        assert_eq!(
+            BitcoinNetwork::from_api_url("https://api.mainnet.bitsafe.finance"),
+            Some(BitcoinNetwork::Mainnet)
+        );
+        assert_eq!(
+            BitcoinNetwork::from_api_url("https://api.testnet.bitsafe.finance"),
+            Some(BitcoinNetwork::Testnet)
+        );
+        assert_eq!(
+            BitcoinNetwork::from_api_url("https://api.devnet.bitsafe.finance"),
+            Some(BitcoinNetwork::Regtest)
+        );
+        assert_eq!(BitcoinNetwork::from_api_url("https://example.com"), None);
+    }
+
+    #[test]
+    fn explicit_network_overrides_api_url() {
+        let proxy = "https://bitsafe-proxy.internal";
+        assert_eq!(
+            BitcoinNetwork::resolve(Some("Testnet"), proxy),
+            Ok(BitcoinNetwork::Testnet)
+        );
+        // The setting wins even when the URL names another network
+        assert_eq!(
+            BitcoinNetwork::resolve(Some("regtest"), "https://api.mainnet.bitsafe.finance"),
+            Ok(BitcoinNetwork::Regtest)
+        );
+        // Unset or empty falls back to inference
+        assert_eq!(
+            BitcoinNetwork::resolve(Some(""), "https://api.mainnet.bitsafe.finance"),
+            Ok(BitcoinNetwork::Mainnet)
+        );
+        assert!(
+            BitcoinNetwork::resolve(None, proxy)
+                .unwrap_err()
+                .contains("set BITCOIN_NETWORK")
+        );
+        assert!(BitcoinNetwork::resolve(Some("signet"), proxy).is_err());
+    }
+
+    #[test]
+    fn accepts_valid_addresses() {
+        use AddressType::*;
+        use BitcoinNetwork::*;
+
+        let cases = [
+            ("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2", Mainnet, P2pkh),
+            ("3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy", Mainnet, P2sh),
+            (
+                "BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4",
+                Mainnet,
+                P2wpkh,
+            ),
+            (
+                "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3",
+                Mainnet,
+                P2wsh,
+            ),
+            (
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0",
+                Mainnet,
+                P2tr,
+            ),
+            ("mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn", Testnet, P2pkh),
+            ("2MzQwSSnBHWHqSAqtTVQ6v47XtaisrJa1Vc", Regtest, P2sh),
+            (
+                "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7",
+                Testnet,
+                P2wsh,
+            ),
+            (
+                "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080",
+                Regtest,
+                P2wpkh,
+            ),
+        ];
+
+        for (address, network, expected) in cases {
+            assert_eq!(validate(address, network), Ok(expected), "{address}");
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_network() {
+        let err = validate(
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx",
+            BitcoinNetwork::Mainnet,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Address is for the 'tb' network, expected mainnet (bc)"
+        );
+
+        let err = validate(
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            BitcoinNetwork::Testnet,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Address is for mainnet, expected testnet (tb)");
+    }
+
+    #[test]
+    fn rejects_bad_checksums() {
+        let err = validate(
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5",
+            BitcoinNetwork::Mainnet,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Invalid bech32 checksum");
+
+        let err = validate(
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3",
+            BitcoinNetwork::Mainnet,
+        )
+        .unwrap_err();
+        assert_eq!(err, "Invalid base58check checksum");
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        let mainnet = BitcoinNetwork::Mainnet;
+
+        // Witness v0 encoded with the bech32m checksum
+        assert_eq!(
+            validate("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kemeawh", mainnet).unwrap_err(),
+            "Witness v0 address must use bech32, not bech32m"
+        );
+        // Witness v1 encoded with the bech32 checksum
+        assert_eq!(
+            validate(
+                "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqh2y7hd",
+                mainnet
+            )
+            .unwrap_err(),
+            "Witness v1 address must use bech32m, not bech32"
+        );
+        // Mixed case
+        assert_eq!(
+            validate("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3T4", mainnet).unwrap_err(),
+            "Segwit address mixes upper and lower case"
+        );
+        // 'b' is not in the bech32 charset
+        assert!(
+            validate("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3tb", mainnet)
+                .unwrap_err()
+                .starts_with("Invalid bech32 character 'b'")
+        );
+        // '0' is not in the base58 alphabet
+        assert!(
+            validate("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN0", mainnet)
+                .unwrap_err()
+                .starts_with("Invalid base58 character '0' at position 33")
+        );
+        assert!(validate("", mainnet).is_err());
+        assert!(validate(" bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4", mainnet).is_err());
+    }
+}
@@ -24,6 +24,15 @@ pub const CREATE_WITHDRAW_ACCOUNT_CHOICE: &str = "CBTCWithdrawAccountRules_Creat
 /// Choice name for withdrawing (burning) CBTC
 pub const WITHDRAW_CHOICE: &str = "CBTCWithdrawAccount_Withdraw";
 
+/// Choice name for changing a withdraw account's destination BTC address
+pub const UPDATE_DESTINATION_ADDRESS_CHOICE: &str = "CBTCWithdrawAccount_UpdateDestinationAddress";
+
+/// Choice name for the owner closing (archiving) their withdraw account
+pub const CLOSE_WITHDRAW_ACCOUNT_CHOICE: &str = "CBTCWithdrawAccount_CloseOwnAccount";
+
+/// Instrument ID of CBTC in the token standard
+pub const CBTC_INSTRUMENT_ID: &str = "CBTC";
+
 /// Template ID for Holding contracts (CBTC tokens)
 pub const HOLDING_TEMPLATE_ID: &str =
     "#utility-registry-holding-v0:Utility.Registry.Holding.V0.Holding:Holding";
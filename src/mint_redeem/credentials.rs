@@ -0,0 +1,155 @@
+//! Credentials the CBTC account rules require.
+//!
+//! Creating a deposit or withdraw account and burning CBTC take the owner's
+//! credentials as `credentialCids`. The rules accept credentials issued by the
+//! CBTC registrar (the decentralized party) granting the owner the Minter
+//! role. [`discover`] finds them, so callers no longer have to pick them out
+//! of [`crate::credentials::list_credentials`] by hand.
+
+use crate::credentials::{
+    CheckCredentialExpiryParams, ClaimRequirement, CredentialOffer, CredentialRequirement,
+    DiscoverCredentialsParams, ExpiryWarning,
+};
+
+/// Claim property carrying a party's CBTC role
+pub const ROLE_PROPERTY: &str = "hasCBTCRole";
+
+/// Role the account rules require
+pub const MINTER_ROLE: &str = "Minter";
+
+/// Credentials the CBTC account rules of `registrar` require of an account
+/// owner
+pub fn account_requirements(registrar: &str) -> Vec<CredentialRequirement> {
+    vec![CredentialRequirement {
+        issuer: registrar.to_string(),
+        claims: vec![ClaimRequirement {
+            property: ROLE_PROPERTY.to_string(),
+            value: MINTER_ROLE.to_string(),
+        }],
+    }]
+}
+
+/// Find `party`'s credentials that meet the account requirements of
+/// `registrar`.
+///
+/// # Errors
+///
+/// Returns an error string naming the missing credential (and any pending
+/// offer for it) if the party holds none, or if a ledger query fails.
+pub async fn discover(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    registrar: &str,
+) -> Result<Vec<String>, String> {
+    crate::credentials::discover_credentials(DiscoverCredentialsParams {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        access_token: access_token.to_string(),
+        requirements: account_requirements(registrar),
+    })
+    .await
+}
+
+/// Warn if every credential `party` holds that meets the account
+/// requirements of `registrar` expires within `within`, after which account
+/// creation, minting and redeeming fail until it is renewed.
+pub async fn expiry_warnings(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    registrar: &str,
+    within: chrono::Duration,
+) -> Result<Vec<ExpiryWarning>, String> {
+    crate::credentials::check_credential_expiry(CheckCredentialExpiryParams {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        access_token: access_token.to_string(),
+        requirements: account_requirements(registrar),
+        within,
+    })
+    .await
+}
+
+/// A pending offer from `registrar` that would meet the account
+/// requirements, if any
+pub fn pending_offer<'a>(
+    offers: &'a [CredentialOffer],
+    party: &str,
+    registrar: &str,
+) -> Option<&'a CredentialOffer> {
+    let requirements = account_requirements(registrar);
+    offers
+        .iter()
+        .find(|o| requirements.iter().all(|r| r.is_offered_by(o, party)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{Claim, UserCredential, select_credentials};
+
+    fn credential(issuer: &str, holder: &str, property: &str, value: &str) -> UserCredential {
+        UserCredential {
+            contract_id: format!("00cred-{}-{}", issuer, value),
+            template_id: String::new(),
+            issuer: issuer.to_string(),
+            holder: holder.to_string(),
+            id: "cred".to_string(),
+            description: String::new(),
+            claims: vec![Claim {
+                subject: holder.to_string(),
+                property: property.to_string(),
+                value: value.to_string(),
+            }],
+            valid_from: None,
+            valid_until: None,
+        }
+    }
+
+    #[test]
+    fn picks_minter_credentials_from_the_registrar() {
+        let credentials = vec![
+            credential("cbtc-network::1220", "alice::1220", "hasCBTCRole", "Minter"),
+            credential("someone::1220", "alice::1220", "hasCBTCRole", "Minter"),
+            credential("cbtc-network::1220", "alice::1220", "hasCBTCRole", "Viewer"),
+        ];
+        let requirements = account_requirements("cbtc-network::1220");
+        assert_eq!(
+            select_credentials(&credentials, "alice::1220", &requirements).unwrap(),
+            vec!["00cred-cbtc-network::1220-Minter"]
+        );
+        let err = select_credentials(&credentials, "bob::1220", &requirements).unwrap_err();
+        assert!(
+            err.contains("credential from cbtc-network::1220 claiming hasCBTCRole=Minter"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn picks_minter_offer_from_the_registrar() {
+        let offer = |issuer: &str, value: &str| CredentialOffer {
+            contract_id: format!("00offer-{}", value),
+            template_id: String::new(),
+            created_event_blob: String::new(),
+            issuer: issuer.to_string(),
+            holder: "alice::1220".to_string(),
+            id: "offer".to_string(),
+            description: String::new(),
+            claims: vec![Claim {
+                subject: "alice::1220".to_string(),
+                property: "hasCBTCRole".to_string(),
+                value: value.to_string(),
+            }],
+        };
+        let offers = vec![
+            offer("someone::1220", "Minter"),
+            offer("cbtc-network::1220", "Viewer"),
+            offer("cbtc-network::1220", "Minter"),
+        ];
+        let picked = pending_offer(&offers, "alice::1220", "cbtc-network::1220").unwrap();
+        assert_eq!(picked.contract_id, "00offer-Minter");
+        assert_eq!(picked.issuer, "cbtc-network::1220");
+        assert!(pending_offer(&offers, "bob::1220", "cbtc-network::1220").is_none());
+    }
+}
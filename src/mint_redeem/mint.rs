@@ -1,14 +1,18 @@
-use crate::mint_redeem::attestor;
+use crate::mint_redeem::attestor::{self, QuorumPolicy};
 use crate::mint_redeem::constants::{
     CREATE_DEPOSIT_ACCOUNT_CHOICE, DEPOSIT_ACCOUNT_RULES_TEMPLATE_ID, DEPOSIT_ACCOUNT_TEMPLATE_ID,
 };
-use crate::mint_redeem::models::{AccountContractRuleSet, DepositAccount, DepositAccountStatus};
+use crate::mint_redeem::models::{
+    AccountContractRuleSet, BitcoinAddressResponse, DepositAccount, DepositAccountStatus,
+    DepositAddress, DepositEvent, OnboardingStep,
+};
+use common::decimal::DamlDecimal;
 use common::submission;
 use common::transfer::DisclosedContract;
 use ledger::active_contracts;
 use ledger::common::{TemplateFilter, TemplateFilterValue, TemplateIdentifierFilter};
 use ledger::ledger_end;
-use ledger::models::JsSubmitAndWaitForTransactionResponse;
+use ledger::models::{JsSubmitAndWaitForTransactionResponse, JsTransaction};
 use ledger::submit;
 use serde_json::json;
 
@@ -26,7 +30,11 @@ pub struct CreateDepositAccountParams {
     pub user_name: String,
     pub access_token: String,
     pub account_rules: AccountContractRuleSet,
+    /// Credentials to present. Leave empty, with `decentralized_party_id`
+    /// set, to discover them (see [`crate::mint_redeem::credentials`]).
     pub credential_cids: Vec<String>,
+    /// CBTC registrar, whose account rules the credentials must meet
+    pub decentralized_party_id: Option<String>,
 }
 
 /// Parameters for getting a deposit account's Bitcoin address
@@ -42,6 +50,62 @@ pub struct GetDepositAccountStatusParams {
     pub access_token: String,
     pub api_url: String,
     pub account_contract_id: String,
+    /// Attestor quorum to wait for ([`QuorumPolicy`])
+    pub quorum: Option<QuorumPolicy>,
+}
+
+/// Maximum number of updates requested per page when scanning for deposits
+const UPDATES_PAGE_SIZE: usize = 100;
+
+/// Called for every detected deposit; return `false` to stop watching
+pub type DepositCallback = dyn Fn(&DepositEvent) -> bool + Send + Sync;
+
+/// Parameters for checking a deposit account for new deposits once
+pub struct PollDepositsParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    /// Deposit account to watch ([`DepositAccount::account_id`])
+    pub account_id: String,
+    /// Only deposits after this ledger offset are reported
+    pub after_offset: i64,
+}
+
+/// Result of [`poll_deposits`]
+#[derive(Debug, Clone)]
+pub struct DepositPoll {
+    /// Deposits completed after `after_offset`, oldest first
+    pub deposits: Vec<DepositEvent>,
+    /// Offset to pass as `after_offset` on the next poll
+    pub next_offset: i64,
+}
+
+/// Parameters for watching a deposit account for new deposits
+pub struct WatchDepositsParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    /// Deposit account to watch ([`DepositAccount::account_id`])
+    pub account_id: String,
+    /// Resume after this ledger offset. If `None`, only deposits completed
+    /// after the watch starts are reported.
+    pub from_offset: Option<i64>,
+    pub poll_interval: std::time::Duration,
+    pub on_deposit: Box<DepositCallback>,
+}
+
+/// Parameters for getting a deposit address, onboarding the party as needed
+pub struct EnsureDepositAddressParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub user_name: String,
+    pub access_token: String,
+    /// Bitsafe API base URL
+    pub api_url: String,
+    /// CBTC registrar (issuer of the required Minter credential)
+    pub decentralized_party_id: String,
+    /// Attestor quorum to wait for ([`QuorumPolicy`])
+    pub quorum: Option<QuorumPolicy>,
 }
 
 /// List all deposit accounts for a party
@@ -142,12 +206,27 @@ fn parse_created_deposit_account_cid(
 ///     user_name: "user@example.com".to_string(),
 ///     access_token: "your-token".to_string(),
 ///     account_rules: rules,
-///     credential_cids: vec!["00abc...".to_string()],
+///     // Discover the registrar's Minter credential
+///     credential_cids: vec![],
+///     decentralized_party_id: Some("cbtc-network::1220...".to_string()),
 /// }).await?;
 /// ```
 pub async fn create_deposit_account(
     params: CreateDepositAccountParams,
 ) -> Result<DepositAccount, String> {
+    let credential_cids = match &params.decentralized_party_id {
+        Some(registrar) if params.credential_cids.is_empty() => {
+            crate::mint_redeem::credentials::discover(
+                &params.ledger_host,
+                &params.party,
+                &params.access_token,
+                registrar,
+            )
+            .await?
+        }
+        _ => params.credential_cids.clone(),
+    };
+
     // Generate a random command ID
     let command_id = format!("cmd-{}", uuid::Uuid::new_v4());
 
@@ -162,7 +241,7 @@ pub async fn create_deposit_account(
     // Build the choice argument
     let choice_argument = json!({
         "owner": params.party,
-        "credentialCids": params.credential_cids
+        "credentialCids": credential_cids
     });
 
     // Build the exercise command
@@ -235,6 +314,24 @@ pub async fn get_bitcoin_address(params: GetBitcoinAddressParams) -> Result<Stri
     attestor::get_bitcoin_address(&params.api_url, &params.account_id).await
 }
 
+/// Fetch an account's Bitcoin address, enforcing `quorum` if given
+async fn fetch_bitcoin_address(
+    api_url: &str,
+    account_id: &str,
+    quorum: Option<&QuorumPolicy>,
+) -> Result<BitcoinAddressResponse, String> {
+    let client = attestor::BitsafeClient::new(api_url);
+    let response = match quorum {
+        Some(quorum) => {
+            client
+                .get_bitcoin_address_with_quorum(account_id, quorum)
+                .await?
+        }
+        None => client.get_bitcoin_address(account_id).await?,
+    };
+    Ok(response)
+}
+
 /// Get the full status of a deposit account including its Bitcoin address
 ///
 /// # Example
@@ -245,6 +342,7 @@ pub async fn get_bitcoin_address(params: GetBitcoinAddressParams) -> Result<Stri
 ///     access_token: "your-token".to_string(),
 ///     api_url: "https://api.mainnet.bitsafe.finance".to_string(),
 ///     account_contract_id: deposit_account.contract_id,
+///     quorum: Some(QuorumPolicy::default()),
 /// }).await?;
 ///
 /// log::debug!("Bitcoin address: {}", status.bitcoin_address);
@@ -272,52 +370,443 @@ pub async fn get_deposit_account_status(
         })?;
 
     // Get the Bitcoin address from Bitsafe API using the account's ID
-    let bitcoin_address =
-        attestor::get_bitcoin_address(&params.api_url, account.account_id()).await?;
+    let address = fetch_bitcoin_address(
+        &params.api_url,
+        account.account_id(),
+        params.quorum.as_ref(),
+    )
+    .await?;
 
     Ok(DepositAccountStatus {
         contract_id: account.contract_id,
         owner: account.owner,
         operator: account.operator,
         registrar: account.registrar,
-        bitcoin_address,
+        bitcoin_address: address.bitcoin_address,
+        attestors_requested: address.attestors_requested,
+        attestors_responded: address.attestors_responded,
         last_processed_bitcoin_block: account.last_processed_bitcoin_block,
         limits: account.limits,
     })
 }
 
+/// Extract the deposits into `account_id` completed by one transaction.
+///
+/// A deposit (`CBTCDepositAccount_CompleteDeposit`) recreates the deposit
+/// account with a new `lastProcessedBitcoinBlock` and mints CBTC holdings to
+/// the owner in the same transaction. Transactions that only recreate the
+/// account (e.g. a limits update) mint nothing and are ignored.
+fn deposit_in_transaction(
+    transaction: &JsTransaction,
+    party: &str,
+    account_id: &str,
+) -> Result<Option<DepositEvent>, String> {
+    let created: Vec<_> = transaction
+        .events
+        .iter()
+        .filter_map(crate::event_helpers::as_created_event)
+        .collect();
+
+    let account = created.iter().find(|event| {
+        event
+            .template_id
+            .ends_with(":CBTC.DepositAccount:CBTCDepositAccount")
+            && event.create_argument.as_ref().is_some_and(|args| {
+                args.get("id").and_then(|v| v.as_str()) == Some(account_id)
+                    && args.get("owner").and_then(|v| v.as_str()) == Some(party)
+            })
+    });
+    let Some(account) = account else {
+        return Ok(None);
+    };
+
+    let bitcoin_block = account
+        .create_argument
+        .as_ref()
+        .and_then(|args| args.get("lastProcessedBitcoinBlock"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse::<i64>().ok())
+        .ok_or("Missing or invalid 'lastProcessedBitcoinBlock' field")?;
+
+    let mut amount = DamlDecimal::ZERO;
+    let mut minted_holding_cids = Vec::new();
+    for event in &created {
+        if !event
+            .template_id
+            .ends_with(":Utility.Registry.Holding.V0.Holding:Holding")
+        {
+            continue;
+        }
+        let Some(args) = event.create_argument.as_ref() else {
+            continue;
+        };
+        if args.get("owner").and_then(|v| v.as_str()) != Some(party) {
+            continue;
+        }
+        let minted = args
+            .get("amount")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing 'amount' field on minted holding")?;
+        amount = amount
+            + DamlDecimal::parse(minted).map_err(|e| format!("Invalid 'amount' field: {}", e))?;
+        minted_holding_cids.push(event.contract_id.clone());
+    }
+
+    if minted_holding_cids.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(DepositEvent {
+        account_id: account_id.to_string(),
+        deposit_account_contract_id: account.contract_id.clone(),
+        amount,
+        bitcoin_block,
+        minted_holding_cids,
+        update_id: transaction.update_id.clone(),
+        offset: transaction.offset,
+    }))
+}
+
+/// Offset of an element of a `/v2/updates` response, whatever its update kind.
+fn update_offset(update: &serde_json::Value) -> Option<i64> {
+    update
+        .get("update")
+        .and_then(|u| u.as_object())
+        .and_then(|u| u.values().next())
+        .and_then(|kind| kind.pointer("/value/offset"))
+        .and_then(|v| v.as_i64())
+}
+
+/// Fetch one page of `party`'s transactions after `begin_exclusive` (up to
+/// `end_inclusive`) from the Ledger JSON API `/v2/updates` endpoint.
+async fn fetch_updates_page(
+    params: &PollDepositsParams,
+    begin_exclusive: i64,
+    end_inclusive: i64,
+) -> Result<Vec<serde_json::Value>, String> {
+    let url = format!(
+        "{}/v2/updates?limit={}",
+        params.ledger_host, UPDATES_PAGE_SIZE
+    );
+    let body = json!({
+        "beginExclusive": begin_exclusive,
+        "endInclusive": end_inclusive,
+        "verbose": false,
+        "updateFormat": {
+            "includeTransactions": {
+                "eventFormat": {
+                    "filtersByParty": { params.party.clone(): { "cumulative": [] } },
+                    "verbose": false
+                },
+                "transactionShape": "TRANSACTION_SHAPE_ACS_DELTA"
+            }
+        }
+    });
+
+    let response = crate::utils::http_client()
+        .post(&url)
+        .bearer_auth(&params.access_token)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch ledger updates: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!(
+            "Ledger updates request failed ({}): {}",
+            status, text
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse ledger updates: {}", e))
+}
+
+/// Check a deposit account once for deposits completed after `after_offset`.
+///
+/// Scans the party's transactions since `after_offset` for ones that advance
+/// the account's `lastProcessedBitcoinBlock` and mint CBTC to the party, so
+/// several deposits arriving at once are reported separately, each with its
+/// amount, Bitcoin block and update ID.
+///
+/// # Errors
+///
+/// Returns an error string if the ledger end or updates request fails, or a
+/// deposit transaction can't be parsed.
+pub async fn poll_deposits(params: PollDepositsParams) -> Result<DepositPoll, String> {
+    let end = ledger_end::get(ledger_end::Params {
+        access_token: params.access_token.clone(),
+        ledger_host: params.ledger_host.clone(),
+    })
+    .await?
+    .offset;
+
+    let mut deposits = Vec::new();
+    let mut begin = params.after_offset;
+    while begin < end {
+        let page = fetch_updates_page(&params, begin, end).await?;
+        for update in &page {
+            if let Some(value) = update.pointer("/update/Transaction/value") {
+                let transaction: JsTransaction = serde_json::from_value(value.clone())
+                    .map_err(|e| format!("Failed to parse transaction: {}", e))?;
+                if let Some(deposit) =
+                    deposit_in_transaction(&transaction, &params.party, &params.account_id)?
+                {
+                    deposits.push(deposit);
+                }
+            }
+        }
+
+        let last = page.iter().filter_map(update_offset).max();
+        match last {
+            Some(last) if page.len() >= UPDATES_PAGE_SIZE && last > begin => begin = last,
+            _ => break,
+        }
+    }
+
+    Ok(DepositPoll {
+        deposits,
+        next_offset: end.max(params.after_offset),
+    })
+}
+
+/// Watch a deposit account and report every completed deposit.
+///
+/// Polls [`poll_deposits`] every `poll_interval` and calls `on_deposit` for
+/// each deposit, oldest first, until it returns `false`. Returns the offset
+/// of the last deposit delivered to `on_deposit`, to pass as `from_offset`
+/// when resuming; deposits it has not seen yet are reported again then.
+///
+/// # Example
+/// ```ignore
+/// let offset = mint::watch_deposits(WatchDepositsParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     account_id: deposit_account.account_id().to_string(),
+///     from_offset: None,
+///     poll_interval: std::time::Duration::from_secs(30),
+///     on_deposit: Box::new(|deposit| {
+///         log::info!("Minted {} CBTC at block {}", deposit.amount, deposit.bitcoin_block);
+///         true
+///     }),
+/// }).await?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error string if a poll fails.
+pub async fn watch_deposits(params: WatchDepositsParams) -> Result<i64, String> {
+    let mut offset = match params.from_offset {
+        Some(offset) => offset,
+        None => {
+            ledger_end::get(ledger_end::Params {
+                access_token: params.access_token.clone(),
+                ledger_host: params.ledger_host.clone(),
+            })
+            .await?
+            .offset
+        }
+    };
+
+    loop {
+        let poll = poll_deposits(PollDepositsParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+            account_id: params.account_id.clone(),
+            after_offset: offset,
+        })
+        .await?;
+
+        for deposit in &poll.deposits {
+            log::debug!(
+                "Deposit of {} CBTC into {} at block {} (update {})",
+                deposit.amount,
+                deposit.account_id,
+                deposit.bitcoin_block,
+                deposit.update_id
+            );
+            if !(params.on_deposit)(deposit) {
+                return Ok(deposit.offset);
+            }
+        }
+        offset = poll.next_offset;
+
+        tokio::time::sleep(params.poll_interval).await;
+    }
+}
+
+/// Find Minter credentials for the party, accepting a pending offer if it has none.
+async fn ensure_minter_credentials(
+    params: &EnsureDepositAddressParams,
+    steps: &mut Vec<OnboardingStep>,
+) -> Result<Vec<String>, String> {
+    let credentials =
+        crate::credentials::list_credentials(crate::credentials::ListCredentialsParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+        })
+        .await?;
+    let requirements =
+        crate::mint_redeem::credentials::account_requirements(&params.decentralized_party_id);
+    let missing =
+        match crate::credentials::select_credentials(&credentials, &params.party, &requirements) {
+            Ok(cids) => return Ok(cids),
+            Err(e) => e,
+        };
+
+    let offers = crate::credentials::list_credential_offers(
+        crate::credentials::ListCredentialOffersParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+        },
+    )
+    .await?;
+    let offer = crate::mint_redeem::credentials::pending_offer(
+        &offers,
+        &params.party,
+        &params.decentralized_party_id,
+    )
+    .ok_or_else(|| format!("{}, and has no pending offer for it", missing))?;
+
+    let user_service =
+        crate::credentials::find_user_service(crate::credentials::FindUserServiceParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+        })
+        .await?;
+    let credential = crate::credentials::accept_credential_offer(
+        crate::credentials::AcceptCredentialOfferParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+            user_service_contract_id: user_service.contract_id,
+            user_service_template_id: user_service.template_id,
+            credential_offer_cid: offer.contract_id.clone(),
+        },
+    )
+    .await?;
+
+    steps.push(OnboardingStep::AcceptedCredentialOffer {
+        credential_contract_id: credential.contract_id.clone(),
+    });
+    Ok(vec![credential.contract_id])
+}
+
+/// Get a BTC deposit address for a party, onboarding it only as far as needed.
+///
+/// Idempotent: reuses the party's existing deposit account if it has one.
+/// Otherwise finds its Minter credentials (accepting a pending offer from the
+/// registrar if it has none), fetches the account rules and creates the
+/// account. Every step actually performed is listed in the result.
+///
+/// # Example
+/// ```ignore
+/// let deposit = mint::ensure_deposit_address(EnsureDepositAddressParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     user_name: "user@example.com".to_string(),
+///     access_token: "your-token".to_string(),
+///     api_url: "https://api.mainnet.bitsafe.finance".to_string(),
+///     decentralized_party_id: "cbtc-network::1220...".to_string(),
+///     quorum: Some(QuorumPolicy::default()),
+/// }).await?;
+///
+/// log::debug!("Send BTC to: {}", deposit.status.bitcoin_address);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error string if the party has no usable credential or offer,
+/// the attestor `quorum` isn't reached, or any ledger or Bitsafe API call
+/// fails.
+pub async fn ensure_deposit_address(
+    params: EnsureDepositAddressParams,
+) -> Result<DepositAddress, String> {
+    let mut steps = Vec::new();
+
+    let existing = list_deposit_accounts(ListDepositAccountsParams {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?
+    .into_iter()
+    .find(|a| a.owner == params.party);
+
+    let account = match existing {
+        Some(account) => {
+            log::debug!("Reusing deposit account {}", account.account_id());
+            account
+        }
+        None => {
+            let credential_cids = ensure_minter_credentials(&params, &mut steps).await?;
+            let account_rules = attestor::get_account_contract_rules(&params.api_url).await?;
+            let account = create_deposit_account(CreateDepositAccountParams {
+                ledger_host: params.ledger_host.clone(),
+                party: params.party.clone(),
+                user_name: params.user_name.clone(),
+                access_token: params.access_token.clone(),
+                account_rules,
+                credential_cids,
+                decentralized_party_id: None,
+            })
+            .await?;
+            steps.push(OnboardingStep::CreatedDepositAccount {
+                contract_id: account.contract_id.clone(),
+            });
+            account
+        }
+    };
+
+    let address = fetch_bitcoin_address(
+        &params.api_url,
+        account.account_id(),
+        params.quorum.as_ref(),
+    )
+    .await?;
+
+    Ok(DepositAddress {
+        account_id: account.account_id().to_string(),
+        status: DepositAccountStatus {
+            contract_id: account.contract_id,
+            owner: account.owner,
+            operator: account.operator,
+            registrar: account.registrar,
+            bitcoin_address: address.bitcoin_address,
+            attestors_requested: address.attestors_requested,
+            attestors_responded: address.attestors_responded,
+            last_processed_bitcoin_block: account.last_processed_bitcoin_block,
+            limits: account.limits,
+        },
+        steps,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
-    #[tokio::test]
-    async fn test_create_deposit_account_with_credentials() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-        let api_url = env::var("BITSAFE_API_URL").expect("BITSAFE_API_URL must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
-        let access_token = login_response.access_token;
+    /// Create a deposit account for the test party with its Minter credentials
+    async fn create_account_with_credentials(env: &TestEnv, access_token: &str) -> DepositAccount {
+        let ledger_host = env.ledger_host();
+        let party_id = env.party();
 
         // Fetch credentials
         let credentials =
             crate::credentials::list_credentials(crate::credentials::ListCredentialsParams {
                 ledger_host: ledger_host.clone(),
                 party: party_id.clone(),
-                access_token: access_token.clone(),
+                access_token: access_token.to_string(),
             })
             .await
             .expect("Failed to list credentials");
@@ -338,48 +827,50 @@ mod tests {
         );
 
         // Fetch account rules
-        let account_rules = crate::mint_redeem::attestor::get_account_contract_rules(&api_url)
-            .await
-            .expect("Failed to get account rules");
+        let account_rules =
+            crate::mint_redeem::attestor::get_account_contract_rules(&env.bitsafe_api_url())
+                .await
+                .expect("Failed to get account rules");
 
         // Create deposit account with credentials
-        let account = create_deposit_account(CreateDepositAccountParams {
+        create_deposit_account(CreateDepositAccountParams {
             ledger_host,
-            party: party_id.clone(),
-            user_name: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            access_token,
+            party: party_id,
+            user_name: env.keycloak_username(),
+            access_token: access_token.to_string(),
             account_rules,
             credential_cids: minter_credential_cids,
+            decentralized_party_id: None,
         })
         .await
-        .expect("Failed to create deposit account with credentials");
+        .expect("Failed to create deposit account with credentials")
+    }
+
+    #[tokio::test]
+    async fn test_create_deposit_account_with_credentials() {
+        let env = TestEnv::load().await;
+        let access_token = env.access_token().await;
+
+        let account = create_account_with_credentials(&env, &access_token).await;
 
-        assert_eq!(account.owner, party_id);
+        assert_eq!(account.owner, env.party());
         assert!(!account.contract_id.is_empty());
     }
 
     #[tokio::test]
     async fn test_list_deposit_accounts() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
+        let access_token = env.access_token().await;
+
+        // A devnet party already has accounts; the mock starts without any
+        if env.server.is_some() {
+            create_account_with_credentials(&env, &access_token).await;
+        }
 
         let accounts = list_deposit_accounts(ListDepositAccountsParams {
-            ledger_host,
-            party: party_id,
-            access_token: login_response.access_token,
+            ledger_host: env.ledger_host(),
+            party: env.party(),
+            access_token,
         })
         .await
         .expect("Failed to list deposit accounts");
@@ -461,4 +952,102 @@ mod parser_tests {
             "unexpected error message: {err}"
         );
     }
+
+    fn deposit_transaction(events: serde_json::Value) -> JsTransaction {
+        transaction_response("update-7", events).transaction
+    }
+
+    fn deposit_account_event(id: &str, block: &str) -> serde_json::Value {
+        created_event_value(
+            "pkg:CBTC.DepositAccount:CBTCDepositAccount",
+            "00acct-v2",
+            json!({
+                "owner": "alice::1220",
+                "registrar": "cbtc-network::1220",
+                "operator": "op::1220",
+                "id": id,
+                "lastProcessedBitcoinBlock": block,
+                "limits": null,
+            }),
+        )
+    }
+
+    fn holding_event(cid: &str, owner: &str, amount: &str) -> serde_json::Value {
+        created_event_value(
+            "pkg:Utility.Registry.Holding.V0.Holding:Holding",
+            cid,
+            json!({
+                "owner": owner,
+                "amount": amount,
+                "instrument": { "id": "CBTC" },
+            }),
+        )
+    }
+
+    #[test]
+    fn deposit_sums_minted_holdings() {
+        let transaction = deposit_transaction(json!([
+            deposit_account_event("00acct", "870001"),
+            holding_event("00h1", "alice::1220", "0.25"),
+            holding_event("00h2", "alice::1220", "0.5"),
+        ]));
+
+        let deposit = deposit_in_transaction(&transaction, "alice::1220", "00acct")
+            .unwrap()
+            .expect("deposit expected");
+        assert_eq!(deposit.amount, DamlDecimal::parse("0.75").unwrap());
+        assert_eq!(deposit.bitcoin_block, 870001);
+        assert_eq!(deposit.deposit_account_contract_id, "00acct-v2");
+        assert_eq!(deposit.minted_holding_cids, vec!["00h1", "00h2"]);
+        assert_eq!(deposit.update_id, "update-7");
+    }
+
+    #[test]
+    fn other_accounts_and_limit_updates_are_not_deposits() {
+        let other_account = deposit_transaction(json!([
+            deposit_account_event("00other", "870001"),
+            holding_event("00h1", "alice::1220", "0.25"),
+        ]));
+        assert!(
+            deposit_in_transaction(&other_account, "alice::1220", "00acct")
+                .unwrap()
+                .is_none()
+        );
+
+        let limits_update = deposit_transaction(json!([deposit_account_event("00acct", "0")]));
+        assert!(
+            deposit_in_transaction(&limits_update, "alice::1220", "00acct")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn holdings_of_other_parties_are_not_counted() {
+        let transaction = deposit_transaction(json!([
+            deposit_account_event("00acct", "870002"),
+            holding_event("00h1", "alice::1220", "1"),
+            holding_event("00fee", "cbtc-network::1220", "0.001"),
+        ]));
+        let deposit = deposit_in_transaction(&transaction, "alice::1220", "00acct")
+            .unwrap()
+            .unwrap();
+        assert_eq!(deposit.amount, DamlDecimal::parse("1").unwrap());
+        assert_eq!(deposit.minted_holding_cids, vec!["00h1"]);
+    }
+
+    #[test]
+    fn update_offset_reads_any_update_kind() {
+        assert_eq!(
+            update_offset(&json!({ "update": { "Transaction": { "value": { "offset": 42 } } } })),
+            Some(42)
+        );
+        assert_eq!(
+            update_offset(
+                &json!({ "update": { "OffsetCheckpoint": { "value": { "offset": 43 } } } })
+            ),
+            Some(43)
+        );
+        assert_eq!(update_offset(&json!({})), None);
+    }
 }
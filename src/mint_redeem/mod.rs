@@ -1,5 +1,8 @@
+pub mod account_labels;
 pub mod attestor;
+pub mod bitcoin_address;
 pub mod constants;
+pub mod credentials;
 pub mod mint;
 pub mod models;
 pub mod redeem;
@@ -11,6 +11,105 @@ pub struct Limits {
     pub max_amount: Option<DamlDecimal>,
 }
 
+/// Why an amount falls outside an account's limits
+#[derive(Debug, Clone, PartialEq)]
+pub enum LimitViolation {
+    BelowMinimum {
+        operation: String,
+        amount: DamlDecimal,
+        min: DamlDecimal,
+    },
+    AboveMaximum {
+        operation: String,
+        amount: DamlDecimal,
+        max: DamlDecimal,
+    },
+}
+
+impl std::fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BelowMinimum {
+                operation,
+                amount,
+                min,
+            } => write!(
+                f,
+                "{} amount {} is below minimum {}",
+                operation, amount, min
+            ),
+            Self::AboveMaximum {
+                operation,
+                amount,
+                max,
+            } => write!(f, "{} amount {} exceeds maximum {}", operation, amount, max),
+        }
+    }
+}
+
+impl std::error::Error for LimitViolation {}
+
+impl From<LimitViolation> for String {
+    fn from(violation: LimitViolation) -> Self {
+        violation.to_string()
+    }
+}
+
+/// The amount range an account accepts, for enforcing and displaying limits.
+/// A `None` bound is unbounded; both `None` means no limits.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EffectiveLimits {
+    pub min_amount: Option<DamlDecimal>,
+    pub max_amount: Option<DamlDecimal>,
+}
+
+impl EffectiveLimits {
+    /// Effective limits of an account's optional `limits` field
+    pub fn from_limits(limits: &Option<Limits>) -> Self {
+        match limits {
+            Some(lim) => Self {
+                min_amount: lim.min_amount,
+                max_amount: lim.max_amount,
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Check `amount` the way the ledger does: both bounds are inclusive.
+    pub fn check(&self, operation: &str, amount: DamlDecimal) -> Result<(), LimitViolation> {
+        if let Some(min) = self.min_amount {
+            if amount < min {
+                return Err(LimitViolation::BelowMinimum {
+                    operation: operation.to_string(),
+                    amount,
+                    min,
+                });
+            }
+        }
+        if let Some(max) = self.max_amount {
+            if amount > max {
+                return Err(LimitViolation::AboveMaximum {
+                    operation: operation.to_string(),
+                    amount,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for EffectiveLimits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.min_amount, self.max_amount) {
+            (None, None) => write!(f, "no limits"),
+            (Some(min), None) => write!(f, "min {}", min),
+            (None, Some(max)) => write!(f, "max {}", max),
+            (Some(min), Some(max)) => write!(f, "{} - {}", min, max),
+        }
+    }
+}
+
 /// Check if an amount is within the account's limits.
 /// Returns Ok(()) if within limits or no limits set,
 /// Err with a descriptive message otherwise.
@@ -19,25 +118,9 @@ pub fn check_limits(
     amount: DamlDecimal,
     limits: &Option<Limits>,
 ) -> Result<(), String> {
-    if let Some(lim) = limits {
-        if let Some(min) = &lim.min_amount {
-            if amount < *min {
-                return Err(format!(
-                    "{} amount {} is below minimum {}",
-                    operation, amount, min
-                ));
-            }
-        }
-        if let Some(max) = &lim.max_amount {
-            if amount > *max {
-                return Err(format!(
-                    "{} amount {} exceeds maximum {}",
-                    operation, amount, max
-                ));
-            }
-        }
-    }
-    Ok(())
+    EffectiveLimits::from_limits(limits)
+        .check(operation, amount)
+        .map_err(String::from)
 }
 
 /// Information about a contract (template ID, contract ID, and created event blob)
@@ -64,7 +147,10 @@ pub struct TokenStandardContracts {
 }
 
 /// Response from the Bitsafe API bitcoin-address endpoint
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// `attestors_responded` of the `attestors_requested` attestors returned the
+/// address; see [`crate::mint_redeem::attestor::QuorumPolicy`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BitcoinAddressResponse {
     pub bitcoin_address: String,
     pub attestors_requested: u32,
@@ -155,6 +241,11 @@ impl DepositAccount {
     pub fn account_id(&self) -> &str {
         self.id.as_deref().unwrap_or(&self.contract_id)
     }
+
+    /// Deposit amounts this account accepts
+    pub fn effective_limits(&self) -> EffectiveLimits {
+        EffectiveLimits::from_limits(&self.limits)
+    }
 }
 
 /// Status of a deposit account including Bitcoin address
@@ -165,14 +256,67 @@ pub struct DepositAccountStatus {
     pub operator: String,
     pub registrar: String,
     pub bitcoin_address: String,
+    /// Attestors asked for `bitcoin_address`, and how many returned it
+    pub attestors_requested: u32,
+    pub attestors_responded: u32,
     pub last_processed_bitcoin_block: i64,
     pub limits: Option<Limits>,
 }
 
+impl DepositAccountStatus {
+    /// Deposit amounts this account accepts
+    pub fn effective_limits(&self) -> EffectiveLimits {
+        EffectiveLimits::from_limits(&self.limits)
+    }
+}
+
+/// A step [`crate::mint_redeem::mint::ensure_deposit_address`] had to perform
+#[derive(Debug, Clone, PartialEq)]
+pub enum OnboardingStep {
+    /// Accepted a Minter credential offer from the registrar
+    AcceptedCredentialOffer { credential_contract_id: String },
+    /// Created a new deposit account
+    CreatedDepositAccount { contract_id: String },
+}
+
+/// Result of [`crate::mint_redeem::mint::ensure_deposit_address`]
+#[derive(Debug, Clone)]
+pub struct DepositAddress {
+    /// Stable ID of the deposit account (see [`DepositAccount::account_id`])
+    pub account_id: String,
+    /// Account status, including the BTC address to deposit to
+    pub status: DepositAccountStatus,
+    /// What had to be done; empty if everything was already in place
+    pub steps: Vec<OnboardingStep>,
+}
+
+/// A completed BTC deposit: CBTC minted to the owner of a deposit account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepositEvent {
+    /// Stable ID of the deposit account (see [`DepositAccount::account_id`])
+    pub account_id: String,
+    /// Contract ID of the deposit account version created by the deposit
+    pub deposit_account_contract_id: String,
+    /// Total CBTC minted by the deposit
+    pub amount: DamlDecimal,
+    /// Bitcoin block at which the deposit was confirmed (the account's new
+    /// `lastProcessedBitcoinBlock`)
+    pub bitcoin_block: i64,
+    /// Holdings minted to the owner
+    pub minted_holding_cids: Vec<String>,
+    /// Ledger update (transaction) that completed the deposit
+    pub update_id: String,
+    /// Ledger offset of that update
+    pub offset: i64,
+}
+
 /// A withdraw account contract with its details
 #[derive(Debug, Clone)]
 pub struct WithdrawAccount {
     pub contract_id: String,
+    /// Stable identifier of the account. Unlike `contract_id`, it survives
+    /// withdrawals and address updates (which recreate the contract).
+    pub account_id: String,
     pub template_id: String,
     pub owner: String,
     pub operator: String,
@@ -225,6 +369,14 @@ impl WithdrawAccount {
             .ok_or("Missing 'destinationBtcAddress' field")?
             .to_string();
 
+        // `id` is None until the account is first recreated; the contract id
+        // is then carried forward as the identifier
+        let account_id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&contract_id)
+            .to_string();
+
         let pending_balance = DamlDecimal::parse(
             args.get("pendingBalance").and_then(|v| v.as_str()).unwrap_or("0")
         ).map_err(|e| format!("Invalid 'pendingBalance' field: {}", e))?;
@@ -240,6 +392,7 @@ impl WithdrawAccount {
 
         Ok(Self {
             contract_id,
+            account_id,
             template_id,
             owner,
             operator,
@@ -250,6 +403,11 @@ impl WithdrawAccount {
             limits,
         })
     }
+
+    /// Withdraw amounts this account accepts
+    pub fn effective_limits(&self) -> EffectiveLimits {
+        EffectiveLimits::from_limits(&self.limits)
+    }
 }
 
 /// A withdraw request contract representing a CBTC burn and pending BTC withdrawal
@@ -388,6 +546,33 @@ impl Holding {
     }
 }
 
+/// Progress of [`crate::mint_redeem::redeem::redeem`], reported as each stage completes
+#[derive(Debug, Clone)]
+pub enum RedeemState {
+    /// Holdings chosen to cover the amount (largest first)
+    HoldingsSelected {
+        holding_cids: Vec<String>,
+        total: DamlDecimal,
+    },
+    /// Too many holdings were needed for one burn, so they were merged first
+    HoldingsConsolidated { holding_cids: Vec<String> },
+    /// CBTC burned; the account's pending balance now awaits the attestors
+    Burned { account: WithdrawAccount },
+    /// The attestor network created the WithdrawRequest with the BTC payout
+    PaidOut { request: WithdrawRequest },
+}
+
+/// Result of [`crate::mint_redeem::redeem::redeem`]
+#[derive(Debug, Clone)]
+pub struct RedeemResult {
+    /// Holdings passed to the burn (change is returned as a new holding)
+    pub burned_holding_cids: Vec<String>,
+    /// The withdraw account as updated by the burn
+    pub account: WithdrawAccount,
+    /// The WithdrawRequest carrying the Bitcoin transaction ID
+    pub request: WithdrawRequest,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -460,6 +645,41 @@ mod tests {
         assert!(check_limits("Withdraw", d("0.0001"), &limits).is_ok());
     }
 
+    #[test]
+    fn test_effective_limits_check_is_typed() {
+        let limits = EffectiveLimits::from_limits(&Some(Limits {
+            min_amount: Some(d("0.01")),
+            max_amount: Some(d("5")),
+        }));
+        assert_eq!(
+            limits.check("Withdraw", d("0.001")),
+            Err(LimitViolation::BelowMinimum {
+                operation: "Withdraw".to_string(),
+                amount: d("0.001"),
+                min: d("0.01"),
+            })
+        );
+        assert_eq!(
+            limits.check("Withdraw", d("6")),
+            Err(LimitViolation::AboveMaximum {
+                operation: "Withdraw".to_string(),
+                amount: d("6"),
+                max: d("5"),
+            })
+        );
+        assert!(limits.check("Withdraw", d("5")).is_ok());
+    }
+
+    #[test]
+    fn test_effective_limits_display() {
+        assert_eq!(EffectiveLimits::from_limits(&None).to_string(), "no limits");
+        let limits = EffectiveLimits {
+            min_amount: Some(d("0.01")),
+            max_amount: None,
+        };
+        assert_eq!(limits.to_string(), format!("min {}", d("0.01")));
+    }
+
     #[test]
     fn test_limits_deserialize_rejects_invalid_string() {
         let result: Result<Limits, _> = serde_json::from_value(serde_json::json!({
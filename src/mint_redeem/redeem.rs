@@ -1,10 +1,13 @@
 use crate::mint_redeem::attestor;
+use crate::mint_redeem::bitcoin_address::{self, BitcoinNetwork};
 use crate::mint_redeem::constants::{
-    CREATE_WITHDRAW_ACCOUNT_CHOICE, HOLDING_TEMPLATE_ID, WITHDRAW_ACCOUNT_RULES_TEMPLATE_ID,
+    CBTC_INSTRUMENT_ID, CLOSE_WITHDRAW_ACCOUNT_CHOICE, CREATE_WITHDRAW_ACCOUNT_CHOICE,
+    HOLDING_TEMPLATE_ID, UPDATE_DESTINATION_ADDRESS_CHOICE, WITHDRAW_ACCOUNT_RULES_TEMPLATE_ID,
     WITHDRAW_ACCOUNT_TEMPLATE_ID, WITHDRAW_CHOICE, WITHDRAW_REQUEST_TEMPLATE_ID,
 };
 use crate::mint_redeem::models::{
-    Holding, TokenStandardContracts, WithdrawAccount, WithdrawRequest,
+    Holding, LimitViolation, RedeemResult, RedeemState, TokenStandardContracts, WithdrawAccount,
+    WithdrawRequest,
 };
 use common::submission;
 use common::transfer::DisclosedContract;
@@ -32,7 +35,33 @@ pub struct CreateWithdrawAccountParams {
     pub account_rules_template_id: String,
     pub account_rules_created_event_blob: String,
     pub destination_btc_address: String,
+    /// Network `destination_btc_address` must be on; see
+    /// [`BitcoinNetwork::resolve`]
+    pub bitcoin_network: BitcoinNetwork,
+    /// Credentials to present. Leave empty, with `decentralized_party_id`
+    /// set, to discover them (see [`crate::mint_redeem::credentials`]).
     pub credential_cids: Vec<String>,
+    /// CBTC registrar, whose account rules the credentials must meet
+    pub decentralized_party_id: Option<String>,
+}
+
+/// Parameters for pointing a withdraw account at a new destination address
+pub struct UpdateDestinationAddressParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    pub withdraw_account_contract_id: String,
+    pub new_destination_btc_address: String,
+    /// Network the new address must be on
+    pub bitcoin_network: BitcoinNetwork,
+}
+
+/// Parameters for closing a withdraw account
+pub struct CloseWithdrawAccountParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub access_token: String,
+    pub withdraw_account_contract_id: String,
 }
 
 /// Parameters for listing CBTC holdings
@@ -53,9 +82,13 @@ pub struct SubmitWithdrawParams {
     pub user_name: String,
     pub access_token: String,
     pub api_url: String,
-    pub withdraw_account_contract_id: String,
+    /// Withdraw account to burn through, as last fetched (e.g. from
+    /// [`list_withdraw_accounts`]); its limits are checked before submitting
+    pub account: WithdrawAccount,
     pub amount: common::decimal::DamlDecimal,
     pub holding_contract_ids: Vec<String>,
+    /// Credentials to present. `None` discovers the ones the account's
+    /// registrar requires (see [`crate::mint_redeem::credentials`]).
     pub credential_cids: Option<Vec<String>>,
 }
 
@@ -66,6 +99,76 @@ pub struct ListWithdrawRequestsParams {
     pub access_token: String,
 }
 
+/// Default maximum number of holdings passed to a single burn. If more are
+/// needed to cover the amount, they are consolidated first.
+pub const DEFAULT_MAX_INPUT_HOLDINGS: usize = 25;
+
+/// Callback invoked as [`redeem`] moves through its stages
+pub type RedeemProgressCallback = dyn Fn(&RedeemState) + Send + Sync;
+
+/// Parameters for redeeming CBTC end to end (see [`redeem`])
+pub struct RedeemParams {
+    pub ledger_host: String,
+    pub party: String,
+    pub user_name: String,
+    pub access_token: String,
+    pub api_url: String,
+    /// Registry and CBTC instrument admin, used only to consolidate holdings
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+    /// Withdraw account to burn through; its current version is re-fetched
+    pub account: WithdrawAccount,
+    pub amount: common::decimal::DamlDecimal,
+    /// Credentials to present; `None` discovers them (see
+    /// [`SubmitWithdrawParams::credential_cids`])
+    pub credential_cids: Option<Vec<String>>,
+    /// Maximum holdings per burn (default [`DEFAULT_MAX_INPUT_HOLDINGS`])
+    pub max_input_holdings: Option<usize>,
+    /// How often to check for the WithdrawRequest after burning
+    pub poll_interval: std::time::Duration,
+    /// Give up waiting for the payout after this long. `None` waits forever.
+    pub payout_timeout: Option<std::time::Duration>,
+    pub on_progress: Option<Box<RedeemProgressCallback>>,
+}
+
+/// A failed withdrawal
+#[derive(Debug, Clone, PartialEq)]
+pub enum WithdrawError {
+    /// The amount is outside the account's limits; nothing was submitted
+    Limit(LimitViolation),
+    /// Any other failure, e.g. a lookup or submission error
+    Other(String),
+}
+
+impl std::fmt::Display for WithdrawError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Limit(violation) => write!(f, "{}", violation),
+            Self::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for WithdrawError {}
+
+impl From<LimitViolation> for WithdrawError {
+    fn from(violation: LimitViolation) -> Self {
+        Self::Limit(violation)
+    }
+}
+
+impl From<String> for WithdrawError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<WithdrawError> for String {
+    fn from(error: WithdrawError) -> Self {
+        error.to_string()
+    }
+}
+
 /// List all withdraw accounts for a party
 ///
 /// # Example
@@ -177,14 +280,19 @@ fn parse_submit_withdraw_response(
 /// This creates a WithdrawAccount contract on Canton that can be used to burn CBTC
 /// and receive BTC at the specified destination address.
 ///
+/// The destination address is validated first (encoding, checksum and
+/// network), so a typo or an address for another network is rejected before
+/// anything is submitted.
+///
 /// # Example
 /// ```ignore
 /// use mint_redeem::attestor;
+/// use mint_redeem::bitcoin_address::BitcoinNetwork;
+///
+/// let api_url = "https://api.mainnet.bitsafe.finance";
 ///
 /// // First get the account rules from the Bitsafe API
-/// let rules = attestor::get_account_contract_rules(
-///     "https://api.mainnet.bitsafe.finance"
-/// ).await?;
+/// let rules = attestor::get_account_contract_rules(api_url).await?;
 ///
 /// // Create the withdraw account with a BTC address
 /// let account = redeem::create_withdraw_account(CreateWithdrawAccountParams {
@@ -196,12 +304,32 @@ fn parse_submit_withdraw_response(
 ///     account_rules_template_id: rules.wa_rules.template_id,
 ///     account_rules_created_event_blob: rules.wa_rules.created_event_blob,
 ///     destination_btc_address: "bc1q...".to_string(),
-///     credential_cids: vec!["00abc...".to_string()],
+///     bitcoin_network: BitcoinNetwork::Mainnet,
+///     // Discover the registrar's Minter credential
+///     credential_cids: vec![],
+///     decentralized_party_id: Some("cbtc-network::1220...".to_string()),
 /// }).await?;
 /// ```
 pub async fn create_withdraw_account(
     params: CreateWithdrawAccountParams,
 ) -> Result<WithdrawAccount, String> {
+    let destination = &params.destination_btc_address;
+    bitcoin_address::validate(destination, params.bitcoin_network)
+        .map_err(|e| format!("Invalid destination BTC address {}: {}", destination, e))?;
+
+    let credential_cids = match &params.decentralized_party_id {
+        Some(registrar) if params.credential_cids.is_empty() => {
+            crate::mint_redeem::credentials::discover(
+                &params.ledger_host,
+                &params.party,
+                &params.access_token,
+                registrar,
+            )
+            .await?
+        }
+        _ => params.credential_cids.clone(),
+    };
+
     // Generate a random command ID
     let command_id = format!("cmd-{}", uuid::Uuid::new_v4());
 
@@ -217,7 +345,7 @@ pub async fn create_withdraw_account(
     let choice_argument = json!({
         "owner": params.party,
         "destinationBtcAddress": params.destination_btc_address,
-        "credentialCids": params.credential_cids
+        "credentialCids": credential_cids
     });
 
     // Build the exercise command
@@ -275,6 +403,213 @@ pub async fn create_withdraw_account(
         })
 }
 
+/// Look up the active withdraw account with the given contract ID
+async fn find_withdraw_account(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    contract_id: &str,
+) -> Result<WithdrawAccount, String> {
+    list_withdraw_accounts(ListWithdrawAccountsParams {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        access_token: access_token.to_string(),
+    })
+    .await?
+    .into_iter()
+    .find(|a| a.contract_id == contract_id)
+    .ok_or_else(|| {
+        format!(
+            "Withdraw account {} not found or no longer active",
+            contract_id
+        )
+    })
+}
+
+/// Exercise an owner-controlled choice on a withdraw account and return the
+/// raw transaction response
+async fn exercise_withdraw_account_choice(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    contract_id: &str,
+    choice: &str,
+    choice_argument: serde_json::Value,
+) -> Result<JsSubmitAndWaitForTransactionResponse, String> {
+    let exercise_command = submission::ExerciseCommand {
+        exercise_command: submission::ExerciseCommandData {
+            template_id: WITHDRAW_ACCOUNT_TEMPLATE_ID.to_string(),
+            contract_id: contract_id.to_string(),
+            choice: choice.to_string(),
+            choice_argument: submission::ChoiceArgumentsVariations::Generic(choice_argument),
+        },
+    };
+
+    let submission_request = submission::Submission {
+        act_as: vec![party.to_string()],
+        read_as: None,
+        command_id: format!("cmd-{}", uuid::Uuid::new_v4()),
+        disclosed_contracts: vec![],
+        commands: vec![submission::Command::ExerciseCommand(exercise_command)],
+        ..Default::default()
+    };
+
+    let response_raw = submit::wait_for_transaction(submit::Params {
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
+        request: submission_request,
+    })
+    .await?;
+
+    serde_json::from_str(&response_raw)
+        .map_err(|e| format!("Failed to parse submit response: {}", e))
+}
+
+/// Point a withdraw account at a new destination BTC address
+///
+/// Use this to rotate payout addresses without creating a new account. The
+/// account keeps its `account_id` (and any local label) but gets a new
+/// contract ID, so the updated account is returned.
+///
+/// The new address is validated for `bitcoin_network` first.
+///
+/// # Example
+/// ```ignore
+/// let account = redeem::update_destination_address(UpdateDestinationAddressParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     withdraw_account_contract_id: account.contract_id,
+///     new_destination_btc_address: "bc1q...".to_string(),
+///     bitcoin_network: BitcoinNetwork::Mainnet,
+/// }).await?;
+/// ```
+pub async fn update_destination_address(
+    params: UpdateDestinationAddressParams,
+) -> Result<WithdrawAccount, String> {
+    let destination = &params.new_destination_btc_address;
+    bitcoin_address::validate(destination, params.bitcoin_network)
+        .map_err(|e| format!("Invalid destination BTC address {}: {}", destination, e))?;
+
+    let account = find_withdraw_account(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.withdraw_account_contract_id,
+    )
+    .await?;
+    if account.destination_btc_address == *destination {
+        return Err(format!(
+            "Withdraw account {} already pays out to {}",
+            account.contract_id, destination
+        ));
+    }
+
+    let response = exercise_withdraw_account_choice(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &account.contract_id,
+        UPDATE_DESTINATION_ADDRESS_CHOICE,
+        json!({ "newDestinationBtcAddress": destination }),
+    )
+    .await?;
+
+    parse_submit_withdraw_response(&response)
+}
+
+/// Close (archive) a withdraw account
+///
+/// Only possible when no withdrawal is in flight, i.e. the account's
+/// `pending_balance` is zero; this is checked before submitting. Closing does
+/// not affect WithdrawRequests already created from the account.
+///
+/// # Example
+/// ```ignore
+/// redeem::close_withdraw_account(CloseWithdrawAccountParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     access_token: "your-token".to_string(),
+///     withdraw_account_contract_id: account.contract_id,
+/// }).await?;
+/// ```
+pub async fn close_withdraw_account(params: CloseWithdrawAccountParams) -> Result<(), String> {
+    let account = find_withdraw_account(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &params.withdraw_account_contract_id,
+    )
+    .await?;
+    if account.pending_balance > common::decimal::DamlDecimal::ZERO {
+        return Err(format!(
+            "Cannot close withdraw account {}: a withdrawal of {} is still pending",
+            account.contract_id, account.pending_balance
+        ));
+    }
+
+    exercise_withdraw_account_choice(
+        &params.ledger_host,
+        &params.party,
+        &params.access_token,
+        &account.contract_id,
+        CLOSE_WITHDRAW_ACCOUNT_CHOICE,
+        json!({}),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Choose the withdraw account to redeem `amount` through
+///
+/// An account qualifies when it has no withdrawal pending, its limits accept
+/// `amount`, and (if given) it pays out to `destination_btc_address`. The
+/// first qualifying account is returned, so order `accounts` by preference.
+///
+/// # Errors
+///
+/// Returns an error string listing why each account was passed over.
+pub fn select_withdraw_account<'a>(
+    accounts: &'a [WithdrawAccount],
+    amount: common::decimal::DamlDecimal,
+    destination_btc_address: Option<&str>,
+) -> Result<&'a WithdrawAccount, String> {
+    let mut rejections = Vec::new();
+    for account in accounts {
+        if let Some(destination) = destination_btc_address {
+            if account.destination_btc_address != destination {
+                rejections.push(format!(
+                    "{}: pays out to {}",
+                    account.contract_id, account.destination_btc_address
+                ));
+                continue;
+            }
+        }
+        if account.pending_balance > common::decimal::DamlDecimal::ZERO {
+            rejections.push(format!(
+                "{}: withdrawal of {} pending",
+                account.contract_id, account.pending_balance
+            ));
+            continue;
+        }
+        if let Err(violation) = account.effective_limits().check("Withdraw", amount) {
+            rejections.push(format!("{}: {}", account.contract_id, violation));
+            continue;
+        }
+        return Ok(account);
+    }
+
+    if rejections.is_empty() {
+        return Err("No withdraw accounts found".to_string());
+    }
+    Err(format!(
+        "No withdraw account can take {} CBTC ({})",
+        amount,
+        rejections.join("; ")
+    ))
+}
+
 /// List all CBTC holdings (token contracts) for a party
 ///
 /// # Example
@@ -341,6 +676,15 @@ pub async fn list_holdings(params: ListHoldingsParams) -> Result<Vec<Holding>, S
 /// Note: WithdrawRequests are NOT created atomically with this call. Use
 /// `list_withdraw_requests()` to periodically check for processed withdrawals.
 ///
+/// The amount is checked against the account's limits (see
+/// [`WithdrawAccount::effective_limits`]) before anything is submitted, so an
+/// out-of-range withdrawal fails with [`WithdrawError::Limit`] instead of
+/// on-ledger.
+///
+/// Without `credential_cids`, the party's credentials that the account's
+/// registrar requires are discovered; if it holds none, the error names the
+/// missing credential.
+///
 /// # Example
 /// ```ignore
 /// // First get your holdings
@@ -358,20 +702,33 @@ pub async fn list_holdings(params: ListHoldingsParams) -> Result<Vec<Holding>, S
 ///     .collect();
 ///
 /// // Submit withdrawal - burns tokens and increases pending_balance
-/// let updated_account = redeem::submit_withdraw(SubmitWithdrawParams {
+/// let result = redeem::submit_withdraw(SubmitWithdrawParams {
 ///     ledger_host: ledger_host.clone(),
 ///     party: party_id.clone(),
 ///     access_token: access_token.clone(),
 ///     api_url: "https://api.mainnet.bitsafe.finance".to_string(),
-///     withdraw_account_contract_id: withdraw_account.contract_id,
+///     account: withdraw_account,
 ///     amount: common::decimal::DamlDecimal::parse("0.001").unwrap(),
 ///     holding_contract_ids: holding_ids,
-/// }).await?;
+///     credential_cids: None,
+/// }).await;
 ///
-/// println!("Pending balance: {}", updated_account.pending_balance);
+/// match result {
+///     Ok(updated_account) => println!("Pending balance: {}", updated_account.pending_balance),
+///     Err(WithdrawError::Limit(violation)) => println!("Not submitted: {}", violation),
+///     Err(e) => return Err(e.into()),
+/// }
 /// // Later, check for WithdrawRequests using list_withdraw_requests()
 /// ```
-pub async fn submit_withdraw(params: SubmitWithdrawParams) -> Result<WithdrawAccount, String> {
+pub async fn submit_withdraw(
+    params: SubmitWithdrawParams,
+) -> Result<WithdrawAccount, WithdrawError> {
+    // Enforce the account's limits before submitting
+    let account = &params.account;
+    account
+        .effective_limits()
+        .check("Withdraw", params.amount)?;
+
     // Get token standard contracts from Bitsafe API
     let token_contracts: TokenStandardContracts =
         attestor::get_token_standard_contracts(&params.api_url).await?;
@@ -443,10 +800,19 @@ pub async fn submit_withdraw(params: SubmitWithdrawParams) -> Result<WithdrawAcc
     // Build choice argument JSON manually to preserve decimal format
     // serde_json can use scientific notation for small numbers, which Canton rejects
     // Keep amount as a JSON string (quoted) to ensure Canton receives it in decimal format
-    let credential_cids_json = match &params.credential_cids {
-        Some(cids) => serde_json::to_string(cids).unwrap(),
-        None => "null".to_string(),
+    let credential_cids = match &params.credential_cids {
+        Some(cids) => cids.clone(),
+        None => {
+            crate::mint_redeem::credentials::discover(
+                &params.ledger_host,
+                &params.party,
+                &params.access_token,
+                &account.registrar,
+            )
+            .await?
+        }
     };
+    let credential_cids_json = serde_json::to_string(&credential_cids).unwrap();
 
     let choice_argument_str = format!(
         r#"{{
@@ -478,7 +844,7 @@ pub async fn submit_withdraw(params: SubmitWithdrawParams) -> Result<WithdrawAcc
     let exercise_command = submission::ExerciseCommand {
         exercise_command: submission::ExerciseCommandData {
             template_id: WITHDRAW_ACCOUNT_TEMPLATE_ID.to_string(),
-            contract_id: params.withdraw_account_contract_id.clone(),
+            contract_id: account.contract_id.clone(),
             choice: WITHDRAW_CHOICE.to_string(),
             choice_argument: submission::ChoiceArgumentsVariations::Generic(choice_argument),
         },
@@ -506,7 +872,7 @@ pub async fn submit_withdraw(params: SubmitWithdrawParams) -> Result<WithdrawAcc
     let response: JsSubmitAndWaitForTransactionResponse = serde_json::from_str(&response_raw)
         .map_err(|e| format!("Failed to parse submit response: {}", e))?;
 
-    parse_submit_withdraw_response(&response)
+    Ok(parse_submit_withdraw_response(&response)?)
 }
 
 /// List all withdraw requests for a party
@@ -577,38 +943,289 @@ pub async fn list_withdraw_requests(
     withdraw_requests
 }
 
+/// Choose CBTC holdings to cover `amount`, largest first, so a burn needs as
+/// few inputs as possible.
+///
+/// # Errors
+///
+/// Returns an error string if the CBTC holdings don't add up to `amount`.
+fn select_holdings(
+    holdings: &[Holding],
+    amount: common::decimal::DamlDecimal,
+) -> Result<Vec<Holding>, String> {
+    let mut cbtc: Vec<&Holding> = holdings
+        .iter()
+        .filter(|h| h.instrument_id == CBTC_INSTRUMENT_ID)
+        .collect();
+    cbtc.sort_by(|a, b| {
+        b.amount
+            .partial_cmp(&a.amount)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut selected = Vec::new();
+    let mut total = common::decimal::DamlDecimal::ZERO;
+    for holding in cbtc {
+        if total >= amount {
+            break;
+        }
+        total += holding.amount;
+        selected.push(holding.clone());
+    }
+
+    if total < amount {
+        return Err(format!(
+            "Insufficient CBTC: {} available, {} requested",
+            total, amount
+        ));
+    }
+
+    Ok(selected)
+}
+
+/// Find the WithdrawRequest that pays out a burn of `amount` through `account`:
+/// a request not in `known_request_cids` (those that existed before the burn)
+/// to the account's destination address, for that amount, with a BTC
+/// transaction ID.
+fn find_payout<'a>(
+    requests: &'a [WithdrawRequest],
+    known_request_cids: &std::collections::HashSet<String>,
+    account: &WithdrawAccount,
+    amount: common::decimal::DamlDecimal,
+) -> Option<&'a WithdrawRequest> {
+    requests.iter().find(|r| {
+        !known_request_cids.contains(&r.contract_id)
+            && r.owner == account.owner
+            && r.destination_btc_address == account.destination_btc_address
+            && r.amount == amount
+            && !r.btc_tx_id.is_empty()
+    })
+}
+
+/// Merge `holding_cids` in chunks of `max_inputs` until at most `max_inputs` remain.
+async fn consolidate_for_burn(
+    params: &RedeemParams,
+    mut holding_cids: Vec<String>,
+    max_inputs: usize,
+) -> Result<Vec<String>, String> {
+    while holding_cids.len() > max_inputs {
+        let mut merged = Vec::new();
+        for chunk in holding_cids.chunks(max_inputs) {
+            merged.extend(
+                crate::consolidate::consolidate_utxos(crate::consolidate::ConsolidateParams {
+                    party: params.party.clone(),
+                    instrument_id: common::transfer::InstrumentId {
+                        admin: params.decentralized_party_id.clone(),
+                        id: CBTC_INSTRUMENT_ID.to_string(),
+                    },
+                    input_holding_cids: Some(chunk.to_vec()),
+                    ledger_host: params.ledger_host.clone(),
+                    access_token: params.access_token.clone(),
+                    registry_url: params.registry_url.clone(),
+                    decentralized_party_id: params.decentralized_party_id.clone(),
+                })
+                .await?,
+            );
+        }
+        holding_cids = merged;
+    }
+    Ok(holding_cids)
+}
+
+/// Redeem CBTC for BTC end to end.
+///
+/// Re-fetches the withdraw account and checks its limits and that no
+/// withdrawal is already pending, selects CBTC holdings to cover `amount`
+/// (consolidating them if more than `max_input_holdings` are needed), burns
+/// them with [`submit_withdraw`], then polls [`list_withdraw_requests`] until
+/// the attestor network creates the WithdrawRequest carrying the Bitcoin
+/// transaction ID. Each stage is reported to `on_progress`.
+///
+/// # Example
+/// ```ignore
+/// let result = redeem::redeem(RedeemParams {
+///     ledger_host: "https://participant.example.com".to_string(),
+///     party: "party::1220...".to_string(),
+///     user_name: "user".to_string(),
+///     access_token: "your-token".to_string(),
+///     api_url: "https://api.mainnet.bitsafe.finance".to_string(),
+///     registry_url: "https://api.utilities.digitalasset.com".to_string(),
+///     decentralized_party_id: "cbtc-network::1220...".to_string(),
+///     account: withdraw_account,
+///     amount: common::decimal::DamlDecimal::parse("0.001").unwrap(),
+///     credential_cids: None, // discovered
+///     max_input_holdings: None,
+///     poll_interval: std::time::Duration::from_secs(30),
+///     payout_timeout: Some(std::time::Duration::from_secs(6 * 60 * 60)),
+///     on_progress: Some(Box::new(|state| log::info!("{:?}", state))),
+/// }).await?;
+///
+/// println!("BTC sent in {}", result.request.btc_tx_id);
+/// ```
+///
+/// # Errors
+///
+/// Returns [`WithdrawError::Limit`] if the amount violates the account's
+/// limits, and [`WithdrawError::Other`] if the account is not found, a
+/// withdrawal is already pending, there isn't enough CBTC, a submission
+/// fails, or `payout_timeout` elapses. A timeout happens after the burn: the
+/// CBTC is gone and the payout is still pending on the account.
+pub async fn redeem(params: RedeemParams) -> Result<RedeemResult, WithdrawError> {
+    let report = |state: RedeemState| {
+        if let Some(on_progress) = &params.on_progress {
+            on_progress(&state);
+        }
+    };
+
+    // The account is recreated by every withdrawal, so look up its current version
+    let accounts = list_withdraw_accounts(ListWithdrawAccountsParams {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+    let account = accounts
+        .iter()
+        .find(|a| a.contract_id == params.account.contract_id)
+        .or_else(|| {
+            accounts
+                .iter()
+                .find(|a| a.account_id == params.account.account_id)
+        })
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Withdraw account {} is no longer active",
+                params.account.contract_id
+            )
+        })?;
+
+    account
+        .effective_limits()
+        .check("Withdraw", params.amount)?;
+    if account.pending_balance > common::decimal::DamlDecimal::ZERO {
+        return Err(format!(
+            "A withdrawal of {} is already pending on account {}",
+            account.pending_balance, account.contract_id
+        )
+        .into());
+    }
+
+    let holdings = list_holdings(ListHoldingsParams {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+    let selected = select_holdings(&holdings, params.amount)?;
+    let mut holding_cids: Vec<String> = selected.iter().map(|h| h.contract_id.clone()).collect();
+    report(RedeemState::HoldingsSelected {
+        holding_cids: holding_cids.clone(),
+        total: selected.iter().map(|h| h.amount).sum(),
+    });
+
+    let max_inputs = params
+        .max_input_holdings
+        .unwrap_or(DEFAULT_MAX_INPUT_HOLDINGS)
+        .max(2);
+    if holding_cids.len() > max_inputs {
+        holding_cids = consolidate_for_burn(&params, holding_cids, max_inputs).await?;
+        report(RedeemState::HoldingsConsolidated {
+            holding_cids: holding_cids.clone(),
+        });
+    }
+
+    // Requests that already exist can't be ours
+    let known_request_cids: std::collections::HashSet<String> =
+        list_withdraw_requests(ListWithdrawRequestsParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+        })
+        .await?
+        .into_iter()
+        .map(|r| r.contract_id)
+        .collect();
+
+    let burned_account = submit_withdraw(SubmitWithdrawParams {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        user_name: params.user_name.clone(),
+        access_token: params.access_token.clone(),
+        api_url: params.api_url.clone(),
+        account: account.clone(),
+        amount: params.amount,
+        holding_contract_ids: holding_cids.clone(),
+        credential_cids: params.credential_cids.clone(),
+    })
+    .await?;
+    report(RedeemState::Burned {
+        account: burned_account.clone(),
+    });
+
+    let started = std::time::Instant::now();
+    loop {
+        let requests = list_withdraw_requests(ListWithdrawRequestsParams {
+            ledger_host: params.ledger_host.clone(),
+            party: params.party.clone(),
+            access_token: params.access_token.clone(),
+        })
+        .await?;
+
+        if let Some(request) = find_payout(
+            &requests,
+            &known_request_cids,
+            &burned_account,
+            params.amount,
+        ) {
+            log::debug!(
+                "Redeemed {} CBTC to {} (tx: {})",
+                request.amount,
+                request.destination_btc_address,
+                request.btc_tx_id
+            );
+            report(RedeemState::PaidOut {
+                request: request.clone(),
+            });
+            return Ok(RedeemResult {
+                burned_holding_cids: holding_cids,
+                account: burned_account,
+                request: request.clone(),
+            });
+        }
+
+        if params
+            .payout_timeout
+            .is_some_and(|timeout| started.elapsed() >= timeout)
+        {
+            return Err(format!(
+                "Timed out waiting for the BTC payout of {} burned through account {}; \
+                 the withdrawal is still pending",
+                params.amount, burned_account.contract_id
+            )
+            .into());
+        }
+
+        tokio::time::sleep(params.poll_interval).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
-    #[tokio::test]
-    async fn test_create_withdraw_account_with_credentials() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-        let api_url = env::var("BITSAFE_API_URL").expect("BITSAFE_API_URL must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
-        let access_token = login_response.access_token;
+    /// Create a withdraw account for the test party with its Minter credentials
+    async fn create_account_with_credentials(env: &TestEnv, access_token: &str) -> WithdrawAccount {
+        let ledger_host = env.ledger_host();
+        let party_id = env.party();
 
         // Fetch credentials
         let credentials =
             crate::credentials::list_credentials(crate::credentials::ListCredentialsParams {
                 ledger_host: ledger_host.clone(),
                 party: party_id.clone(),
-                access_token: access_token.clone(),
+                access_token: access_token.to_string(),
             })
             .await
             .expect("Failed to list credentials");
@@ -629,51 +1246,54 @@ mod tests {
         );
 
         // Fetch account rules
-        let account_rules = crate::mint_redeem::attestor::get_account_contract_rules(&api_url)
-            .await
-            .expect("Failed to get account rules");
+        let account_rules =
+            crate::mint_redeem::attestor::get_account_contract_rules(&env.bitsafe_api_url())
+                .await
+                .expect("Failed to get account rules");
 
         // Create withdraw account with credentials
-        let account = create_withdraw_account(CreateWithdrawAccountParams {
+        create_withdraw_account(CreateWithdrawAccountParams {
             ledger_host,
-            party: party_id.clone(),
-            user_name: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            access_token,
+            party: party_id,
+            user_name: env.keycloak_username(),
+            access_token: access_token.to_string(),
             account_rules_contract_id: account_rules.wa_rules.contract_id,
             account_rules_template_id: account_rules.wa_rules.template_id,
             account_rules_created_event_blob: account_rules.wa_rules.created_event_blob,
             destination_btc_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            bitcoin_network: env.bitcoin_network(),
             credential_cids: minter_credential_cids,
+            decentralized_party_id: None,
         })
         .await
-        .expect("Failed to create withdraw account with credentials");
+        .expect("Failed to create withdraw account with credentials")
+    }
+
+    #[tokio::test]
+    async fn test_create_withdraw_account_with_credentials() {
+        let env = TestEnv::load().await;
+        let access_token = env.access_token().await;
 
-        assert_eq!(account.owner, party_id);
+        let account = create_account_with_credentials(&env, &access_token).await;
+
+        assert_eq!(account.owner, env.party());
         assert!(!account.contract_id.is_empty());
     }
 
     #[tokio::test]
     async fn test_list_withdraw_accounts() {
-        dotenvy::dotenv().ok();
-
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let party_id = env::var("PARTY_ID").expect("PARTY_ID must be set");
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
+        let access_token = env.access_token().await;
+
+        // A devnet party already has accounts; the mock starts without any
+        if env.server.is_some() {
+            create_account_with_credentials(&env, &access_token).await;
+        }
 
         let accounts = list_withdraw_accounts(ListWithdrawAccountsParams {
-            ledger_host,
-            party: party_id,
-            access_token: login_response.access_token,
+            ledger_host: env.ledger_host(),
+            party: env.party(),
+            access_token,
         })
         .await
         .expect("Failed to list withdraw accounts");
@@ -697,8 +1317,7 @@ mod parser_tests {
     };
     use serde_json::json;
 
-    const WITHDRAW_ACCOUNT_TID: &str =
-        "pkg-hash:CBTC.WithdrawAccount:CBTCWithdrawAccount";
+    const WITHDRAW_ACCOUNT_TID: &str = "pkg-hash:CBTC.WithdrawAccount:CBTCWithdrawAccount";
 
     fn withdraw_account_create_argument() -> serde_json::Value {
         // Mirrors the shape of `createArgument` produced by the JSON ledger
@@ -740,9 +1359,11 @@ mod parser_tests {
     fn parse_created_withdraw_account_cid_missing_match() {
         let response = transaction_response(
             "tx-x",
-            json!([
-                created_event_value("pkg:Some.Other:Template", "00other", json!(null)),
-            ]),
+            json!([created_event_value(
+                "pkg:Some.Other:Template",
+                "00other",
+                json!(null)
+            ),]),
         );
 
         let err = parse_created_withdraw_account_cid(&response).unwrap_err();
@@ -794,6 +1415,27 @@ mod parser_tests {
             account.destination_btc_address,
             "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080"
         );
+        // `id` is null on a fresh account, so the contract id identifies it
+        assert_eq!(account.account_id, "00new-withdraw-account");
+    }
+
+    #[test]
+    fn parse_submit_withdraw_response_keeps_account_id() {
+        let mut argument = withdraw_account_create_argument();
+        argument["id"] = json!("00original-account");
+        let response = transaction_response(
+            "tx-w2",
+            json!([created_event_value_with_blob(
+                WITHDRAW_ACCOUNT_TID,
+                "00recreated-account",
+                argument,
+                "blob-base64",
+            )]),
+        );
+
+        let account = parse_submit_withdraw_response(&response).unwrap();
+        assert_eq!(account.contract_id, "00recreated-account");
+        assert_eq!(account.account_id, "00original-account");
     }
 
     #[test]
@@ -801,9 +1443,11 @@ mod parser_tests {
         // Only an unrelated CreatedEvent — no CBTCWithdrawAccount.
         let response = transaction_response(
             "tx-x",
-            json!([
-                created_event_value("pkg:Some.Other:Template", "00other", json!({})),
-            ]),
+            json!([created_event_value(
+                "pkg:Some.Other:Template",
+                "00other",
+                json!({})
+            ),]),
         );
 
         let err = parse_submit_withdraw_response(&response).unwrap_err();
@@ -824,4 +1468,191 @@ mod parser_tests {
             "unexpected error: {err}"
         );
     }
+
+    // ---------- select_holdings / find_payout ----------
+
+    fn d(s: &str) -> common::decimal::DamlDecimal {
+        common::decimal::DamlDecimal::parse(s).unwrap()
+    }
+
+    fn holding(cid: &str, amount: &str, instrument_id: &str) -> Holding {
+        Holding {
+            contract_id: cid.to_string(),
+            amount: d(amount),
+            instrument_id: instrument_id.to_string(),
+            owner: "alice::1220deadbeef".to_string(),
+        }
+    }
+
+    fn account() -> WithdrawAccount {
+        WithdrawAccount {
+            contract_id: "00wac".to_string(),
+            account_id: "00wac".to_string(),
+            template_id: WITHDRAW_ACCOUNT_TID.to_string(),
+            owner: "alice::1220deadbeef".to_string(),
+            operator: "operator::1220ababab".to_string(),
+            registrar: "registrar::1220cdcdcd".to_string(),
+            destination_btc_address: "bcrt1qdest".to_string(),
+            pending_balance: d("0.5"),
+            created_event_blob: String::new(),
+            limits: None,
+        }
+    }
+
+    fn request(cid: &str, amount: &str, btc_tx_id: &str) -> WithdrawRequest {
+        WithdrawRequest {
+            contract_id: cid.to_string(),
+            owner: "alice::1220deadbeef".to_string(),
+            registrar: "registrar::1220cdcdcd".to_string(),
+            amount: d(amount),
+            destination_btc_address: "bcrt1qdest".to_string(),
+            btc_tx_id: btc_tx_id.to_string(),
+            source_account_id: None,
+        }
+    }
+
+    #[test]
+    fn select_holdings_takes_largest_cbtc_first() {
+        let holdings = vec![
+            holding("small", "0.1", "CBTC"),
+            holding("other", "10", "USDC"),
+            holding("large", "0.4", "CBTC"),
+            holding("medium", "0.2", "CBTC"),
+        ];
+
+        let selected = select_holdings(&holdings, d("0.5")).unwrap();
+        let cids: Vec<&str> = selected.iter().map(|h| h.contract_id.as_str()).collect();
+        assert_eq!(cids, vec!["large", "medium"]);
+    }
+
+    #[test]
+    fn select_holdings_insufficient_balance() {
+        let holdings = vec![holding("a", "0.1", "CBTC"), holding("b", "5", "USDC")];
+        let err = select_holdings(&holdings, d("0.5")).unwrap_err();
+        assert!(err.contains("Insufficient CBTC"), "unexpected error: {err}");
+    }
+
+    #[tokio::test]
+    async fn submit_withdraw_over_the_limit_fails_before_submitting() {
+        let mut account = account();
+        account.limits = Some(crate::mint_redeem::models::Limits {
+            min_amount: None,
+            max_amount: Some(d("0.1")),
+        });
+
+        // Nothing listens on the ledger host, so only the limit check can fail.
+        let err = submit_withdraw(SubmitWithdrawParams {
+            ledger_host: "http://127.0.0.1:1".to_string(),
+            party: "alice::1220deadbeef".to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            api_url: "http://127.0.0.1:1".to_string(),
+            account,
+            amount: d("0.4"),
+            holding_contract_ids: vec!["h1".to_string()],
+            credential_cids: None,
+        })
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                WithdrawError::Limit(LimitViolation::AboveMaximum { .. })
+            ),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn find_payout_ignores_known_and_mismatched_requests() {
+        let known = std::collections::HashSet::from(["old".to_string()]);
+        let requests = vec![
+            request("old", "0.5", "tx-old"),
+            request("other-amount", "0.2", "tx-2"),
+            request("ours", "0.5", "tx-ours"),
+        ];
+
+        let found = find_payout(&requests, &known, &account(), d("0.5")).unwrap();
+        assert_eq!(found.contract_id, "ours");
+        assert_eq!(found.btc_tx_id, "tx-ours");
+    }
+
+    // ---------- select_withdraw_account ----------
+
+    fn withdraw_account(
+        cid: &str,
+        destination: &str,
+        pending: &str,
+        max: Option<&str>,
+    ) -> WithdrawAccount {
+        WithdrawAccount {
+            contract_id: cid.to_string(),
+            account_id: cid.to_string(),
+            destination_btc_address: destination.to_string(),
+            pending_balance: d(pending),
+            limits: max.map(|max| crate::mint_redeem::models::Limits {
+                min_amount: None,
+                max_amount: Some(d(max)),
+            }),
+            ..account()
+        }
+    }
+
+    #[test]
+    fn select_withdraw_account_skips_pending_and_limited_accounts() {
+        let accounts = vec![
+            withdraw_account("pending", "bcrt1qdest", "0.1", None),
+            withdraw_account("small", "bcrt1qdest", "0", Some("0.2")),
+            withdraw_account("ok", "bcrt1qdest", "0", Some("1")),
+        ];
+
+        let selected = select_withdraw_account(&accounts, d("0.5"), None).unwrap();
+        assert_eq!(selected.contract_id, "ok");
+    }
+
+    #[test]
+    fn select_withdraw_account_matches_destination() {
+        let accounts = vec![
+            withdraw_account("a", "bcrt1qa", "0", None),
+            withdraw_account("b", "bcrt1qb", "0", None),
+        ];
+
+        let selected = select_withdraw_account(&accounts, d("0.5"), Some("bcrt1qb")).unwrap();
+        assert_eq!(selected.contract_id, "b");
+    }
+
+    #[test]
+    fn select_withdraw_account_explains_rejections() {
+        let accounts = vec![
+            withdraw_account("pending", "bcrt1qdest", "0.1", None),
+            withdraw_account("small", "bcrt1qdest", "0", Some("0.2")),
+        ];
+
+        let err = select_withdraw_account(&accounts, d("0.5"), None).unwrap_err();
+        assert!(
+            err.contains("pending: withdrawal of"),
+            "unexpected error: {err}"
+        );
+        assert!(
+            err.contains("small: Withdraw amount"),
+            "unexpected error: {err}"
+        );
+
+        let err = select_withdraw_account(&[], d("0.5"), None).unwrap_err();
+        assert_eq!(err, "No withdraw accounts found");
+    }
+
+    #[test]
+    fn find_payout_waits_for_btc_tx_id() {
+        let requests = vec![request("ours", "0.5", "")];
+        assert!(
+            find_payout(
+                &requests,
+                &std::collections::HashSet::new(),
+                &account(),
+                d("0.5")
+            )
+            .is_none()
+        );
+    }
 }
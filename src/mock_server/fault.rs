@@ -0,0 +1,92 @@
+//! Fault injection for the mock server.
+
+use serde_json::Value;
+use std::time::Duration;
+
+/// Group of routes a fault applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `GET /v2/state/ledger-end`
+    LedgerEnd,
+    /// Active contracts, over HTTP or websocket
+    ActiveContracts,
+    /// `submit-and-wait` and `submit-and-wait-for-transaction`
+    Submit,
+    /// `POST /v2/updates`
+    Updates,
+    /// Registry factory and choice-context routes
+    Registry,
+    /// Bitsafe `/cbtc/v1/` routes
+    Bitsafe,
+}
+
+/// What happens to a request hit by a fault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Fail with 409 `LOCAL_VERDICT_LOCKED_CONTRACTS`, as when a concurrent
+    /// transaction holds the same contracts. Nothing is applied.
+    Contention,
+    /// Answer 503 `UNAVAILABLE`. Nothing is applied.
+    Unavailable,
+    /// Hold the request this long, then handle it normally. Longer than the
+    /// client's timeout, this models a submission that times out on the
+    /// client but still commits.
+    Delay(Duration),
+}
+
+/// A fault to inject into the next `times` matching requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultRule {
+    pub endpoint: Endpoint,
+    /// For [`Endpoint::Submit`], only hit submissions exercising this choice
+    pub choice: Option<String>,
+    pub fault: Fault,
+    pub times: usize,
+}
+
+impl FaultRule {
+    /// Hit the next request to `endpoint` once
+    pub fn new(endpoint: Endpoint, fault: Fault) -> Self {
+        Self {
+            endpoint,
+            choice: None,
+            fault,
+            times: 1,
+        }
+    }
+
+    pub(super) fn matches(&self, endpoint: Endpoint, body: &Value) -> bool {
+        if self.times == 0 || self.endpoint != endpoint {
+            return false;
+        }
+        match &self.choice {
+            None => true,
+            Some(choice) => {
+                body.get("commands")
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|commands| {
+                        commands.iter().any(|command| {
+                            command
+                                .pointer("/ExerciseCommand/choice")
+                                .and_then(|v| v.as_str())
+                                == Some(choice.as_str())
+                        })
+                    })
+            }
+        }
+    }
+}
+
+/// Classify a request path, or `None` for unknown routes
+pub(super) fn endpoint(path: &str) -> Option<Endpoint> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["v2", "state", "ledger-end"] => Some(Endpoint::LedgerEnd),
+        ["v2", "state", "active-contracts"] => Some(Endpoint::ActiveContracts),
+        ["v2", "commands", ..] => Some(Endpoint::Submit),
+        ["v2", "updates", ..] => Some(Endpoint::Updates),
+        ["cbtc", "v1", ..] => Some(Endpoint::Bitsafe),
+        _ if segments.contains(&"registry") => Some(Endpoint::Registry),
+        _ => None,
+    }
+}
@@ -0,0 +1,369 @@
+//! Minimal HTTP/1.1 and websocket front end for the mock ledger.
+//!
+//! Each connection carries one request and is closed after the response,
+//! which every client the library uses handles transparently.
+
+use super::fault::{self, Fault};
+use super::ledger::{MockError, MockLedger};
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+/// Largest request head the server reads
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+/// Subprotocol the ledger's websocket endpoints authenticate with
+const WS_AUTH_PROTOCOL: &str = "daml.ws.auth";
+
+/// Accept connections until the task is aborted
+pub(super) async fn serve(listener: TcpListener, ledger: Arc<Mutex<MockLedger>>) {
+    loop {
+        let Ok((stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let ledger = ledger.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, ledger).await {
+                log::debug!("Mock server connection failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    ledger: Arc<Mutex<MockLedger>>,
+) -> Result<(), String> {
+    let head = peek_head(&stream).await?;
+    if head.to_ascii_lowercase().contains("upgrade: websocket") {
+        return handle_websocket(stream, ledger).await;
+    }
+
+    let (method, path, query, body) = read_request(&mut stream).await?;
+    let response = match apply_fault(&ledger, &path, &body).await {
+        Ok(()) => route(&ledger, &method, &path, &query, &body),
+        Err(e) => Err(e),
+    };
+    let (status, body) = match response {
+        Ok(body) => (200, body),
+        Err(e) => (e.status, error_body(&path, &e)),
+    };
+    write_response(&mut stream, status, &body).await
+}
+
+/// Take the first injected fault matching a request. Delays are served here,
+/// without holding the ledger lock; other faults become the request's error.
+async fn apply_fault(
+    ledger: &Mutex<MockLedger>,
+    path: &str,
+    body: &Value,
+) -> Result<(), MockError> {
+    let Some(endpoint) = fault::endpoint(path) else {
+        return Ok(());
+    };
+    let fault = ledger
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take_fault(endpoint, body);
+    match fault {
+        None => Ok(()),
+        Some(Fault::Delay(delay)) => {
+            tokio::time::sleep(delay).await;
+            Ok(())
+        }
+        Some(Fault::Contention) => Err(MockError::contention()),
+        Some(Fault::Unavailable) => Err(MockError::unavailable()),
+    }
+}
+
+/// Wait until the whole request head has arrived, without consuming it, so a
+/// websocket handshake can still be handed to tungstenite untouched
+async fn peek_head(stream: &TcpStream) -> Result<String, String> {
+    let mut buf = vec![0u8; MAX_HEAD_LEN];
+    loop {
+        let n = stream.peek(&mut buf).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed before request".to_string());
+        }
+        if let Some(end) = find_head_end(&buf[..n]) {
+            return Ok(String::from_utf8_lossy(&buf[..end]).into_owned());
+        }
+        if n == buf.len() {
+            return Err("Request head too large".to_string());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+    }
+}
+
+fn find_head_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n").map(|i| i + 4)
+}
+
+/// Read a request, returning its method, path, query string and JSON body.
+/// Form bodies, which only the Keycloak token endpoint is sent, read as null.
+async fn read_request(stream: &mut TcpStream) -> Result<(String, String, String, Value), String> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(end) = find_head_end(&buf) {
+            break end;
+        }
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed mid-request".to_string());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (path, query) = (path.to_string(), query.to_string());
+    let headers: Vec<(&str, &str)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect();
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    };
+    let content_length = header("content-length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let is_form = header("content-type")
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+    let mut body = buf[head_end..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed mid-body".to_string());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    let body = if body.is_empty() || is_form {
+        Value::Null
+    } else {
+        serde_json::from_slice(&body).map_err(|e| format!("Invalid JSON body: {}", e))?
+    };
+    Ok((method, path, query, body))
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &Value) -> Result<(), String> {
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    stream.shutdown().await.map_err(|e| e.to_string())
+}
+
+/// Ledger errors use the JSON API's `{code, cause}` shape; registry and
+/// Bitsafe errors use `{code, message}`
+fn error_body(path: &str, error: &MockError) -> Value {
+    if path.starts_with("/v2/") {
+        json!({ "code": error.code, "cause": error.message })
+    } else {
+        json!({ "code": error.code, "message": error.message })
+    }
+}
+
+fn route(
+    ledger: &Mutex<MockLedger>,
+    method: &str,
+    path: &str,
+    query: &str,
+    body: &Value,
+) -> Result<Value, MockError> {
+    let mut ledger = ledger.lock().unwrap_or_else(|e| e.into_inner());
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, segments.as_slice()) {
+        // Ledger JSON API
+        ("GET", ["v2", "state", "ledger-end"]) => Ok(json!({ "offset": ledger.ledger_end() })),
+        ("POST", ["v2", "state", "active-contracts"]) => {
+            Ok(Value::Array(ledger.active_contracts(body)))
+        }
+        ("POST", ["v2", "commands", "submit-and-wait-for-transaction"]) => ledger.submit(body),
+        ("POST", ["v2", "commands", "submit-and-wait"]) => {
+            let response = ledger.submit(body)?;
+            Ok(json!({
+                "updateId": response["transaction"]["updateId"],
+                "completionOffset": response["transaction"]["offset"],
+            }))
+        }
+        ("POST", ["v2", "updates"]) => {
+            let limit = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("limit="))
+                .and_then(|limit| limit.parse().ok());
+            Ok(Value::Array(ledger.updates(body, limit)))
+        }
+
+        // Token-standard registry, under any prefix
+        (
+            "POST",
+            [
+                ..,
+                "registry",
+                "transfer-instruction",
+                "v1",
+                "transfer-factory",
+            ],
+        ) => Ok(ledger.transfer_factory(body)),
+        (
+            "POST",
+            [
+                ..,
+                "registry",
+                "transfer-instruction",
+                "v1",
+                cid,
+                "choice-contexts",
+                choice,
+            ],
+        ) if matches!(*choice, "accept" | "reject" | "withdraw") => {
+            ledger.transfer_offer_context(cid)
+        }
+        (
+            "POST",
+            [
+                ..,
+                "registry",
+                "allocation-instruction",
+                "v1",
+                "allocation-factory",
+            ],
+        ) => Ok(ledger.allocation_factory()),
+        (
+            "POST",
+            [
+                ..,
+                "registry",
+                "allocations",
+                "v1",
+                cid,
+                "choice-contexts",
+                _,
+            ],
+        ) => ledger.allocation_context(cid),
+
+        // Keycloak, under any realm
+        ("POST", [.., "protocol", "openid-connect", "token"]) => Ok(token_response()),
+
+        // Bitsafe API
+        ("GET", ["cbtc", "v1", "account-contract-rules"]) => Ok(ledger.account_contract_rules()),
+        ("GET", ["cbtc", "v1", "token-standard-contracts"]) => {
+            Ok(ledger.token_standard_contracts())
+        }
+        ("GET", ["cbtc", "v1", "bitcoin-address", account_id]) => {
+            ledger.bitcoin_address(account_id)
+        }
+
+        _ => Err(MockError::not_found(format!(
+            "No mock route for {} {}",
+            method, path
+        ))),
+    }
+}
+
+/// Keycloak's response to a password or refresh grant. Every login succeeds;
+/// the token is never checked.
+fn token_response() -> Value {
+    json!({
+        "access_token": "mock-access-token",
+        "expires_in": 300,
+        "refresh_expires_in": 1800,
+        "refresh_token": "mock-refresh-token",
+        "token_type": "Bearer",
+        "not-before-policy": 0,
+        "session_state": "mock-session",
+        "scope": "openid profile email",
+    })
+}
+
+/// Serve an ACS websocket: read the request frame, send one frame per
+/// active contract, then close
+#[allow(clippy::result_large_err)] // the handshake callback's error type is tungstenite's
+async fn handle_websocket(stream: TcpStream, ledger: Arc<Mutex<MockLedger>>) -> Result<(), String> {
+    let mut path = String::new();
+    let callback = |request: &Request, mut response: Response| {
+        path = request.uri().path().to_string();
+        let offers_auth = request
+            .headers()
+            .get_all("sec-websocket-protocol")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.split(',').any(|p| p.trim() == WS_AUTH_PROTOCOL));
+        if offers_auth {
+            response.headers_mut().insert(
+                "sec-websocket-protocol",
+                HeaderValue::from_static(WS_AUTH_PROTOCOL),
+            );
+        }
+        Ok::<_, ErrorResponse>(response)
+    };
+    let mut ws = tokio_tungstenite::accept_hdr_async(stream, callback)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if path.trim_end_matches('/') != "/v2/state/active-contracts" {
+        ws.close(None).await.ok();
+        return Err(format!("No mock websocket route for {}", path));
+    }
+
+    let request = loop {
+        match ws.next().await {
+            Some(Ok(Message::Text(text))) => {
+                break serde_json::from_str::<Value>(&text).map_err(|e| e.to_string())?;
+            }
+            Some(Ok(Message::Binary(bytes))) => {
+                break serde_json::from_slice::<Value>(&bytes).map_err(|e| e.to_string())?;
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.to_string()),
+            None => return Ok(()),
+        }
+    };
+
+    if let Err(e) = apply_fault(&ledger, &path, &request).await {
+        ws.close(None).await.ok();
+        return Err(e.message);
+    }
+    let entries = ledger
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .active_contracts(&request);
+    for entry in entries {
+        ws.send(Message::Text(entry.to_string()))
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    ws.close(None).await.map_err(|e| e.to_string())
+}
@@ -0,0 +1,1680 @@
+//! In-memory ledger state served by [`super::MockServer`].
+//!
+//! Contracts are kept as plain JSON create arguments, in the shapes the
+//! library's parsers read. Amounts are handled as fixed-point integers with
+//! Daml's 10 decimal places, so balances never drift.
+
+use super::fault::{Endpoint, Fault, FaultRule};
+use crate::mint_redeem::constants::{
+    CLOSE_WITHDRAW_ACCOUNT_CHOICE, CREATE_DEPOSIT_ACCOUNT_CHOICE, CREATE_WITHDRAW_ACCOUNT_CHOICE,
+    DEPOSIT_ACCOUNT_RULES_TEMPLATE_ID, DEPOSIT_ACCOUNT_TEMPLATE_ID, HOLDING_TEMPLATE_ID,
+    UPDATE_DESTINATION_ADDRESS_CHOICE, WITHDRAW_ACCOUNT_RULES_TEMPLATE_ID,
+    WITHDRAW_ACCOUNT_TEMPLATE_ID, WITHDRAW_CHOICE, WITHDRAW_REQUEST_TEMPLATE_ID,
+};
+use chrono::{DateTime, Utc};
+use common::decimal::DamlDecimal;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+/// Instrument id of the holdings the mock issues
+pub const INSTRUMENT_ID: &str = "CBTC";
+
+/// Contract id returned by the registry's transfer-factory route
+pub const TRANSFER_FACTORY_CID: &str = "00mock-transfer-factory";
+
+/// Contract id returned by the registry's allocation-factory route
+pub const ALLOCATION_FACTORY_CID: &str = "00mock-allocation-factory";
+
+const SYNCHRONIZER_ID: &str = "mock-synchronizer";
+const PACKAGE_NAME: &str = "mock";
+
+const BURN_MINT_FACTORY_TEMPLATE_ID: &str =
+    "#utility-registry-app-v0:Utility.Registry.App.V0.Service.BurnMintFactory:BurnMintFactory";
+const INSTRUMENT_CONFIGURATION_TEMPLATE_ID: &str =
+    "#utility-registry-v0:Utility.Registry.V0.Configuration.Instrument:InstrumentConfiguration";
+const CREDENTIAL_TEMPLATE_ID: &str =
+    "#utility-credential-v0:Utility.Credential.V0.Credential:Credential";
+const CREDENTIAL_OFFER_TEMPLATE_ID: &str =
+    "#utility-credential-app-v0:Utility.Credential.App.V0.Model.Offer:CredentialOffer";
+const USER_SERVICE_TEMPLATE_ID: &str =
+    "#utility-credential-app-v0:Utility.Credential.App.V0.Service.User:UserService";
+
+/// Daml `Decimal` scale
+const SCALE: i128 = 10_000_000_000;
+
+/// A request the mock ledger refused, returned to the client as a non-2xx
+/// response
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct MockError {
+    pub status: u16,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl MockError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            code: "INVALID_ARGUMENT",
+            message: message.into(),
+        }
+    }
+
+    fn precondition(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            code: "FAILED_PRECONDITION",
+            message: message.into(),
+        }
+    }
+
+    /// A Daml assertion failed during interpretation
+    fn daml_failure(message: impl Into<String>) -> Self {
+        Self {
+            status: 400,
+            code: "DAML_FAILURE",
+            message: format!("Interpretation error: Error: {}", message.into()),
+        }
+    }
+
+    fn unauthorized(party: &str) -> Self {
+        Self {
+            status: 403,
+            code: "DAML_AUTHORIZATION_ERROR",
+            message: format!("Submission is not authorized by {}", party),
+        }
+    }
+
+    pub(super) fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: 404,
+            code: "NOT_FOUND",
+            message: message.into(),
+        }
+    }
+
+    pub(super) fn contention() -> Self {
+        Self {
+            status: 409,
+            code: "LOCAL_VERDICT_LOCKED_CONTRACTS",
+            message: "Contracts are locked by a concurrent transaction".to_string(),
+        }
+    }
+
+    pub(super) fn unavailable() -> Self {
+        Self {
+            status: 503,
+            code: "UNAVAILABLE",
+            message: "Service is temporarily unavailable".to_string(),
+        }
+    }
+
+    fn contract_not_found(contract_id: &str) -> Self {
+        Self {
+            status: 404,
+            code: "CONTRACT_NOT_FOUND",
+            message: format!("Contract could not be found with id {}", contract_id),
+        }
+    }
+}
+
+/// An active contract on the mock ledger
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockContract {
+    pub contract_id: String,
+    pub template_id: String,
+    pub create_argument: Value,
+    pub signatories: Vec<String>,
+    pub observers: Vec<String>,
+    /// `(interface id, view value)` for each interface the contract implements
+    pub interface_views: Vec<(String, Value)>,
+    /// Offset of the transaction that created the contract
+    pub offset: i64,
+    pub created_at: String,
+}
+
+impl MockContract {
+    /// Whether `party` is a signatory or observer
+    pub fn is_visible_to(&self, party: &str) -> bool {
+        self.signatories.iter().any(|p| p == party) || self.observers.iter().any(|p| p == party)
+    }
+
+    /// `amount` of a holding, or zero for other contracts
+    fn amount(&self) -> i128 {
+        self.create_argument
+            .get("amount")
+            .and_then(|v| parse_amount(v).ok())
+            .unwrap_or(0)
+    }
+
+    fn str_field(&self, key: &str) -> &str {
+        self.create_argument
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+    }
+
+    fn created_event(&self, node_id: usize, views: &[&(String, Value)]) -> Value {
+        let mut witnesses = self.signatories.clone();
+        witnesses.extend(self.observers.iter().cloned());
+        let mut event = json!({
+            "offset": self.offset,
+            "nodeId": node_id,
+            "contractId": self.contract_id,
+            "templateId": self.template_id,
+            "createArgument": self.create_argument,
+            "createdEventBlob": format!("mock-blob-{}", self.contract_id),
+            "witnessParties": witnesses,
+            "signatories": self.signatories,
+            "observers": self.observers,
+            "createdAt": self.created_at,
+            "packageName": PACKAGE_NAME,
+            "representativePackageId": PACKAGE_NAME,
+            "acsDelta": true,
+        });
+        if !views.is_empty() {
+            event["interfaceViews"] = views
+                .iter()
+                .map(|(interface_id, view)| {
+                    json!({
+                        "interfaceId": interface_id,
+                        "viewStatus": { "code": 0, "message": "", "details": [] },
+                        "viewValue": view,
+                    })
+                })
+                .collect();
+        }
+        event
+    }
+}
+
+/// In-memory state behind the mock ledger, registry and Bitsafe routes
+///
+/// Holds CBTC holdings, transfer offers, allocations and CBTC deposit and
+/// withdraw accounts. Holdings are UTXOs: every choice that spends holdings
+/// archives its inputs and creates new outputs, with any change returned to
+/// the sender. Submissions are atomic; a failing command leaves the ledger
+/// untouched.
+///
+/// Deadlines (`executeBefore`, `settleBefore`) are checked against the
+/// ledger's clock, which follows wall-clock time until [`Self::set_time`] or
+/// [`Self::advance_time`] fixes it. Nothing expires on its own; call
+/// [`Self::expire`] to play the registry's expiry automation.
+#[derive(Debug, Clone)]
+pub struct MockLedger {
+    admin: String,
+    offset: i64,
+    next_id: u64,
+    contracts: Vec<MockContract>,
+    submissions: Vec<Value>,
+    transactions: Vec<Value>,
+    faults: Vec<FaultRule>,
+    clock: Option<DateTime<Utc>>,
+    attestors_requested: u32,
+    attestors_responded: u32,
+    deposit_rules_cid: String,
+    withdraw_rules_cid: String,
+    burn_mint_factory_cid: String,
+    instrument_configuration_cid: String,
+    issuer_credential_cid: String,
+}
+
+impl MockLedger {
+    /// Create a ledger whose instrument admin (the decentralized party) is
+    /// `admin`, with the CBTC account rules and token-standard contracts the
+    /// Bitsafe routes hand out already in place.
+    pub fn new(admin: &str) -> Self {
+        let mut ledger = Self {
+            admin: admin.to_string(),
+            offset: 0,
+            next_id: 0,
+            contracts: Vec::new(),
+            submissions: Vec::new(),
+            transactions: Vec::new(),
+            faults: Vec::new(),
+            clock: None,
+            attestors_requested: 3,
+            attestors_responded: 3,
+            deposit_rules_cid: String::new(),
+            withdraw_rules_cid: String::new(),
+            burn_mint_factory_cid: String::new(),
+            instrument_configuration_cid: String::new(),
+            issuer_credential_cid: String::new(),
+        };
+        let admin_only = json!({ "operator": admin, "registrar": admin });
+        ledger.offset += 1;
+        ledger.deposit_rules_cid = ledger.insert(
+            DEPOSIT_ACCOUNT_RULES_TEMPLATE_ID,
+            admin_only.clone(),
+            admin,
+            &[],
+        );
+        ledger.withdraw_rules_cid = ledger.insert(
+            WITHDRAW_ACCOUNT_RULES_TEMPLATE_ID,
+            admin_only.clone(),
+            admin,
+            &[],
+        );
+        ledger.burn_mint_factory_cid = ledger.insert(
+            BURN_MINT_FACTORY_TEMPLATE_ID,
+            admin_only.clone(),
+            admin,
+            &[],
+        );
+        ledger.instrument_configuration_cid = ledger.insert(
+            INSTRUMENT_CONFIGURATION_TEMPLATE_ID,
+            admin_only.clone(),
+            admin,
+            &[],
+        );
+        ledger.issuer_credential_cid =
+            ledger.insert(CREDENTIAL_TEMPLATE_ID, admin_only, admin, &[]);
+        ledger
+    }
+
+    /// The instrument admin (decentralized party)
+    pub fn admin(&self) -> &str {
+        &self.admin
+    }
+
+    /// Offset of the latest transaction
+    pub fn ledger_end(&self) -> i64 {
+        self.offset
+    }
+
+    /// All active contracts, oldest first
+    pub fn contracts(&self) -> &[MockContract] {
+        &self.contracts
+    }
+
+    /// The active contract with `contract_id`
+    pub fn contract(&self, contract_id: &str) -> Option<&MockContract> {
+        self.contracts.iter().find(|c| c.contract_id == contract_id)
+    }
+
+    /// Request bodies of all successful submissions, in order
+    pub fn submissions(&self) -> &[Value] {
+        &self.submissions
+    }
+
+    /// All committed transactions, oldest first, as served by `/v2/updates`
+    pub fn transactions(&self) -> &[Value] {
+        &self.transactions
+    }
+
+    /// The ledger's current time
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.unwrap_or_else(Utc::now)
+    }
+
+    /// Fix the ledger's clock at `time`
+    pub fn set_time(&mut self, time: DateTime<Utc>) {
+        self.clock = Some(time);
+    }
+
+    /// Move the ledger's clock forward by `by`, fixing it if it was following
+    /// wall-clock time
+    pub fn advance_time(&mut self, by: chrono::Duration) {
+        self.clock = Some(self.now() + by);
+    }
+
+    /// Inject a fault into upcoming requests. Rules are consumed in the order
+    /// they were added.
+    pub fn inject_fault(&mut self, rule: FaultRule) {
+        self.faults.push(rule);
+    }
+
+    /// Use up and return the first fault matching a request
+    pub(super) fn take_fault(&mut self, endpoint: Endpoint, body: &Value) -> Option<Fault> {
+        let rule = self
+            .faults
+            .iter_mut()
+            .find(|rule| rule.matches(endpoint, body))?;
+        rule.times -= 1;
+        let fault = rule.fault;
+        self.faults.retain(|rule| rule.times > 0);
+        Some(fault)
+    }
+
+    /// Mint an unlocked CBTC holding for `owner`, returning its contract id
+    pub fn create_holding(&mut self, owner: &str, amount: DamlDecimal) -> Result<String, String> {
+        let amount = decimal_amount(&amount)?;
+        self.operate(|ledger| Ok(ledger.insert_holding(owner, amount, Value::Null)))
+    }
+
+    /// Merge `owner`'s unlocked `inputs` and split them into holdings of
+    /// `outputs`, plus one holding for any remainder. Returns the new
+    /// holdings' contract ids, outputs first.
+    pub fn merge_split(
+        &mut self,
+        owner: &str,
+        inputs: &[String],
+        outputs: &[DamlDecimal],
+    ) -> Result<Vec<String>, String> {
+        let outputs = outputs
+            .iter()
+            .map(decimal_amount)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.operate(|ledger| {
+            let remainder = ledger.spend(owner, inputs, outputs.iter().sum())?;
+            let mut cids: Vec<String> = outputs
+                .iter()
+                .filter(|amount| **amount > 0)
+                .map(|amount| ledger.insert_holding(owner, *amount, Value::Null))
+                .collect();
+            cids.extend(remainder);
+            Ok(cids)
+        })
+    }
+
+    /// Act as the attestors completing a deposit: mint `amount` to the
+    /// deposit account's owner and advance the account to `bitcoin_block`,
+    /// which must be past its last processed block. `deposit_account_id` is
+    /// the account's `id`, or its contract id while it has none. Returns the
+    /// minted holding's contract id.
+    pub fn credit_deposit(
+        &mut self,
+        deposit_account_id: &str,
+        amount: DamlDecimal,
+        bitcoin_block: i64,
+    ) -> Result<String, String> {
+        let amount = decimal_amount(&amount)?;
+        self.operate(|ledger| {
+            let account = ledger
+                .contracts
+                .iter()
+                .filter(|c| same_identifier(&c.template_id, DEPOSIT_ACCOUNT_TEMPLATE_ID))
+                .find(|c| account_id(c) == deposit_account_id)
+                .cloned()
+                .ok_or_else(|| MockError::not_found("account not found"))?;
+            let last_block = account
+                .str_field("lastProcessedBitcoinBlock")
+                .parse::<i64>()
+                .unwrap_or(0);
+            if bitcoin_block <= last_block {
+                return Err(MockError::precondition(format!(
+                    "Bitcoin block {} is not after the last processed block {}",
+                    bitcoin_block, last_block
+                )));
+            }
+
+            ledger.archive(&account.contract_id)?;
+            let owner = account.str_field("owner").to_string();
+            let mut argument = account.create_argument.clone();
+            argument["id"] = json!(account_id(&account));
+            argument["lastProcessedBitcoinBlock"] = json!(bitcoin_block.to_string());
+            let admin = ledger.admin.clone();
+            ledger.insert(DEPOSIT_ACCOUNT_TEMPLATE_ID, argument, &admin, &[&owner]);
+            Ok(ledger.insert_holding(&owner, amount, Value::Null))
+        })
+    }
+
+    /// Play the registry's expiry automation: archive every transfer offer
+    /// past its `executeBefore` and every allocation past its `settleBefore`,
+    /// returning the locked amounts to their senders. Returns the expired
+    /// contract ids.
+    pub fn expire(&mut self) -> Vec<String> {
+        let now = self.now();
+        let expired: Vec<MockContract> = self
+            .contracts
+            .iter()
+            .filter(|c| {
+                let deadline = if is_transfer_offer(c) {
+                    &c.create_argument["transfer"]["executeBefore"]
+                } else if is_allocation(c) {
+                    &c.create_argument["allocation"]["settlement"]["settleBefore"]
+                } else {
+                    return false;
+                };
+                deadline.as_str().is_some_and(|d| is_before(d, now))
+            })
+            .cloned()
+            .collect();
+        if expired.is_empty() {
+            return Vec::new();
+        }
+
+        self.operate(|ledger| {
+            for contract in &expired {
+                let (amount, sender) = if is_transfer_offer(contract) {
+                    let amount = ledger.release_offer(contract)?;
+                    (amount, &contract.create_argument["transfer"]["sender"])
+                } else {
+                    let amount = ledger.release_allocation_holdings(contract)?;
+                    let leg = &contract.create_argument["allocation"]["transferLeg"];
+                    (amount, &leg["sender"])
+                };
+                let sender = sender.as_str().unwrap_or_default().to_string();
+                ledger.insert_holding(&sender, amount, Value::Null);
+            }
+            Ok(())
+        })
+        .expect("expired contracts are active");
+        expired.into_iter().map(|c| c.contract_id).collect()
+    }
+
+    /// `owner`'s unlocked CBTC holdings
+    pub fn holdings(&self, owner: &str) -> Vec<&MockContract> {
+        self.contracts
+            .iter()
+            .filter(|c| is_holding(c) && c.str_field("owner") == owner)
+            .filter(|c| c.create_argument["lock"].is_null())
+            .collect()
+    }
+
+    /// Total of `owner`'s unlocked CBTC holdings
+    pub fn balance(&self, owner: &str) -> DamlDecimal {
+        let total = self.holdings(owner).iter().map(|c| c.amount()).sum();
+        DamlDecimal::parse(&format_amount(total)).expect("formatted amount is a valid decimal")
+    }
+
+    /// Issue `holder` a credential from the admin making `claims` (property,
+    /// value) about the holder, returning its contract id
+    pub fn issue_credential(&mut self, holder: &str, claims: &[(&str, &str)]) -> String {
+        let claims: Vec<Value> = claims
+            .iter()
+            .map(|(property, value)| {
+                json!({ "subject": holder, "property": property, "value": value })
+            })
+            .collect();
+        self.operate(|ledger| {
+            let id = format!("credential-{}", ledger.next_id + 1);
+            let argument = json!({
+                "issuer": ledger.admin,
+                "holder": holder,
+                "id": id,
+                "description": "Issued by the mock ledger",
+                "claims": claims,
+            });
+            let admin = ledger.admin.clone();
+            Ok(ledger.insert(CREDENTIAL_TEMPLATE_ID, argument, &admin, &[holder]))
+        })
+        .expect("issuing a credential cannot fail")
+    }
+
+    /// Onboard `party` to the credential app: create its UserService, through
+    /// which it accepts credential offers and, as an issuer, offers and
+    /// revokes credentials. Returns the UserService contract id.
+    pub fn add_user_service(&mut self, party: &str) -> String {
+        self.operate(|ledger| {
+            let argument = json!({
+                "operator": ledger.admin,
+                "user": party,
+                "dso": ledger.admin,
+            });
+            let admin = ledger.admin.clone();
+            Ok(ledger.insert(USER_SERVICE_TEMPLATE_ID, argument, &admin, &[party]))
+        })
+        .expect("creating a UserService cannot fail")
+    }
+
+    /// Set the attestor counts the Bitsafe `bitcoin-address` route reports
+    pub fn set_attestors(&mut self, requested: u32, responded: u32) {
+        self.attestors_requested = requested;
+        self.attestors_responded = responded;
+    }
+
+    /// Act as the registrar paying out a withdraw account's pending balance:
+    /// creates a WithdrawRequest carrying `btc_tx_id` and resets the account's
+    /// pending balance. Returns the WithdrawRequest contract id.
+    pub fn complete_withdrawal(
+        &mut self,
+        withdraw_account_contract_id: &str,
+        btc_tx_id: &str,
+    ) -> Result<String, String> {
+        self.operate(|ledger| {
+            let account = ledger.archive(withdraw_account_contract_id)?;
+            let owner = account.str_field("owner").to_string();
+            let request = json!({
+                "owner": owner,
+                "registrar": ledger.admin,
+                "amount": account.create_argument["pendingBalance"],
+                "destinationBtcAddress": account.create_argument["destinationBtcAddress"],
+                "btcTxId": btc_tx_id,
+                "sourceAccountId": account_id(&account),
+            });
+            let admin = ledger.admin.clone();
+            let request_cid =
+                ledger.insert(WITHDRAW_REQUEST_TEMPLATE_ID, request, &admin, &[&owner]);
+
+            let mut argument = account.create_argument.clone();
+            argument["pendingBalance"] = json!(format_amount(0));
+            ledger.recreate_withdraw_account(&account, argument);
+            Ok(request_cid)
+        })
+    }
+
+    /// Run a ledger-side operation (by the registrar, the attestors or a test)
+    /// as one transaction, rolling it back if it fails
+    fn operate<T>(
+        &mut self,
+        operation: impl FnOnce(&mut Self) -> Result<T, MockError>,
+    ) -> Result<T, String> {
+        let snapshot = (self.contracts.clone(), self.next_id, self.offset);
+        let existing = self.contract_ids();
+        self.offset += 1;
+        match operation(self) {
+            Ok(value) => {
+                let events = self.created_events_since(&existing, 0);
+                self.commit(events, "");
+                Ok(value)
+            }
+            Err(e) => {
+                (self.contracts, self.next_id, self.offset) = snapshot;
+                Err(e.message)
+            }
+        }
+    }
+
+    /// Record a transaction at the current offset, returning it
+    fn commit(&mut self, events: Vec<Value>, command_id: &str) -> Value {
+        let now = self.now().to_rfc3339();
+        let transaction = json!({
+            "updateId": format!("mock-update-{}", self.offset),
+            "commandId": command_id,
+            "workflowId": "",
+            "effectiveAt": now,
+            "events": events,
+            "offset": self.offset,
+            "synchronizerId": SYNCHRONIZER_ID,
+            "recordTime": now,
+        });
+        self.transactions.push(transaction.clone());
+        transaction
+    }
+
+    fn contract_ids(&self) -> HashSet<String> {
+        self.contracts
+            .iter()
+            .map(|c| c.contract_id.clone())
+            .collect()
+    }
+
+    /// `CreatedEvent`s for the contracts not in `existing`, numbered from
+    /// `node_id`
+    fn created_events_since(&self, existing: &HashSet<String>, node_id: usize) -> Vec<Value> {
+        self.contracts
+            .iter()
+            .filter(|c| !existing.contains(&c.contract_id))
+            .enumerate()
+            .map(|(i, c)| json!({ "CreatedEvent": c.created_event(node_id + i, &[]) }))
+            .collect()
+    }
+
+    // ---------- ledger routes ----------
+
+    /// Active contracts matching an ACS request's party and template or
+    /// interface filters, as `JsGetActiveContractsResponse` entries
+    pub(super) fn active_contracts(&self, request: &Value) -> Vec<Value> {
+        let mut parties = Vec::new();
+        collect_keys(request, "filtersByParty", &mut parties);
+        let mut template_ids = Vec::new();
+        collect_strings(request, "templateId", &mut template_ids);
+        let mut interface_ids = Vec::new();
+        collect_strings(request, "interfaceId", &mut interface_ids);
+
+        self.contracts
+            .iter()
+            .filter(|c| parties.is_empty() || parties.iter().any(|p| c.is_visible_to(p)))
+            .filter_map(|c| {
+                let views: Vec<&(String, Value)> = c
+                    .interface_views
+                    .iter()
+                    .filter(|(id, _)| interface_ids.iter().any(|i| same_identifier(i, id)))
+                    .collect();
+                let wildcard = template_ids.is_empty() && interface_ids.is_empty();
+                let matches_template = template_ids
+                    .iter()
+                    .any(|t| same_identifier(t, &c.template_id));
+                if !(wildcard || matches_template || !views.is_empty()) {
+                    return None;
+                }
+                Some(json!({
+                    "workflowId": "",
+                    "contractEntry": {
+                        "JsActiveContract": {
+                            "createdEvent": c.created_event(0, &views),
+                            "synchronizerId": SYNCHRONIZER_ID,
+                            "reassignmentCounter": 0,
+                        }
+                    }
+                }))
+            })
+            .collect()
+    }
+
+    /// Apply a `submit-and-wait-for-transaction` request, returning the
+    /// `{ "transaction": ... }` response
+    pub(super) fn submit(&mut self, request: &Value) -> Result<Value, MockError> {
+        let act_as: Vec<String> = request
+            .get("actAs")
+            .and_then(|v| v.as_array())
+            .map(|parties| {
+                parties
+                    .iter()
+                    .filter_map(|p| p.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let commands = request
+            .get("commands")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| MockError::invalid("Missing 'commands'"))?;
+
+        let snapshot = (self.contracts.clone(), self.next_id, self.offset);
+        self.offset += 1;
+        let mut events = Vec::new();
+        for command in commands {
+            if let Err(e) = self.apply_command(command, &act_as, &mut events) {
+                (self.contracts, self.next_id, self.offset) = snapshot;
+                return Err(e);
+            }
+        }
+        self.submissions.push(request.clone());
+
+        let command_id = request["commandId"].as_str().unwrap_or_default();
+        let transaction = self.commit(events, command_id);
+        Ok(json!({ "transaction": transaction }))
+    }
+
+    /// Committed transactions in `(beginExclusive, endInclusive]` with an
+    /// event witnessed by one of the request's parties, as `/v2/updates`
+    /// elements
+    pub(super) fn updates(&self, request: &Value, limit: Option<usize>) -> Vec<Value> {
+        let begin = request["beginExclusive"].as_i64().unwrap_or(0);
+        let end = request["endInclusive"].as_i64().unwrap_or(self.offset);
+        let mut parties = Vec::new();
+        collect_keys(request, "filtersByParty", &mut parties);
+
+        self.transactions
+            .iter()
+            .filter(|tx| {
+                let offset = tx["offset"].as_i64().unwrap_or_default();
+                offset > begin && offset <= end
+            })
+            .filter(|tx| parties.is_empty() || is_witnessed(tx, &parties))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|tx| json!({ "update": { "Transaction": { "value": tx } } }))
+            .collect()
+    }
+
+    // ---------- registry routes ----------
+
+    /// Transfer-factory response for a `TransferFactory_Transfer` request
+    pub(super) fn transfer_factory(&self, request: &Value) -> Value {
+        let transfer = &request["choiceArguments"]["transfer"];
+        let kind = if transfer["sender"] == transfer["receiver"] {
+            "self"
+        } else {
+            "offer"
+        };
+        json!({
+            "factoryId": TRANSFER_FACTORY_CID,
+            "transferKind": kind,
+            "choiceContext": empty_choice_context(),
+        })
+    }
+
+    /// Choice context for accepting, rejecting or withdrawing an active
+    /// transfer offer
+    pub(super) fn transfer_offer_context(&self, contract_id: &str) -> Result<Value, MockError> {
+        match self.contract(contract_id) {
+            Some(c) if is_transfer_offer(c) => Ok(empty_choice_context()),
+            _ => Err(MockError::contract_not_found(contract_id)),
+        }
+    }
+
+    /// Allocation-factory response for an `AllocationFactory_Allocate` request
+    pub(super) fn allocation_factory(&self) -> Value {
+        json!({
+            "factoryId": ALLOCATION_FACTORY_CID,
+            "choiceContext": empty_choice_context(),
+        })
+    }
+
+    /// Choice context for executing, withdrawing or cancelling an allocation
+    pub(super) fn allocation_context(&self, contract_id: &str) -> Result<Value, MockError> {
+        match self.contract(contract_id) {
+            Some(c) if is_allocation(c) => Ok(empty_choice_context()),
+            _ => Err(MockError::contract_not_found(contract_id)),
+        }
+    }
+
+    // ---------- Bitsafe routes ----------
+
+    pub(super) fn account_contract_rules(&self) -> Value {
+        json!({
+            "da_rules": self.contract_info(&self.deposit_rules_cid),
+            "wa_rules": self.contract_info(&self.withdraw_rules_cid),
+        })
+    }
+
+    pub(super) fn token_standard_contracts(&self) -> Value {
+        json!({
+            "burn_mint_factory": self.contract_info(&self.burn_mint_factory_cid),
+            "instrument_configuration": self.contract_info(&self.instrument_configuration_cid),
+            "issuer_credential": self.contract_info(&self.issuer_credential_cid),
+        })
+    }
+
+    /// Deposit address of the deposit account identified by `account_id`
+    /// (its `id`, or its contract id while it has none)
+    pub(super) fn bitcoin_address(&self, account_id_or_cid: &str) -> Result<Value, MockError> {
+        let account = self
+            .contracts
+            .iter()
+            .filter(|c| same_identifier(&c.template_id, DEPOSIT_ACCOUNT_TEMPLATE_ID))
+            .find(|c| c.contract_id == account_id_or_cid || account_id(c) == account_id_or_cid)
+            .ok_or_else(|| MockError::not_found("account not found"))?;
+        let suffix: String = account_id(account)
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .take(32)
+            .collect();
+        Ok(json!({
+            "bitcoin_address": format!("bcrt1qmock{}", suffix.to_ascii_lowercase()),
+            "attestors_requested": self.attestors_requested,
+            "attestors_responded": self.attestors_responded,
+        }))
+    }
+
+    fn contract_info(&self, contract_id: &str) -> Value {
+        let template_id = self
+            .contract(contract_id)
+            .map(|c| c.template_id.as_str())
+            .unwrap_or_default();
+        json!({
+            "contract_id": contract_id,
+            "template_id": template_id,
+            "created_event_blob": format!("mock-blob-{}", contract_id),
+        })
+    }
+
+    // ---------- commands ----------
+
+    fn apply_command(
+        &mut self,
+        command: &Value,
+        act_as: &[String],
+        events: &mut Vec<Value>,
+    ) -> Result<(), MockError> {
+        if let Some(create) = command.get("CreateCommand") {
+            let template_id = create["templateId"]
+                .as_str()
+                .ok_or_else(|| MockError::invalid("CreateCommand is missing 'templateId'"))?;
+            let submitter = act_as
+                .first()
+                .ok_or_else(|| MockError::invalid("Submission has no actAs party"))?
+                .clone();
+            let cid = self.insert(
+                template_id,
+                create["createArguments"].clone(),
+                &submitter,
+                &[],
+            );
+            let contract = self.contract(&cid).expect("contract was just created");
+            events.push(json!({ "CreatedEvent": contract.created_event(events.len(), &[]) }));
+            return Ok(());
+        }
+
+        let exercise = command
+            .get("ExerciseCommand")
+            .ok_or_else(|| MockError::invalid("Unsupported command; expected ExerciseCommand"))?;
+        let contract_id = exercise["contractId"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let choice = exercise["choice"].as_str().unwrap_or_default().to_string();
+        let argument = &exercise["choiceArgument"];
+
+        let existing = self.contract_ids();
+        let result = self.exercise(&contract_id, &choice, argument, act_as)?;
+        let consuming = existing.contains(&contract_id) && self.contract(&contract_id).is_none();
+        let node_id = events.len();
+        let created = self.created_events_since(&existing, node_id + 1);
+
+        events.push(json!({
+            "ExercisedEvent": {
+                "offset": self.offset,
+                "nodeId": node_id,
+                "contractId": contract_id,
+                "templateId": exercise["templateId"],
+                "choice": choice,
+                "choiceArgument": argument,
+                "actingParties": act_as,
+                "consuming": consuming,
+                "witnessParties": act_as,
+                "lastDescendantNodeId": node_id + created.len(),
+                "exerciseResult": result,
+                "packageName": PACKAGE_NAME,
+                "acsDelta": true,
+            }
+        }));
+        events.extend(created);
+        Ok(())
+    }
+
+    fn exercise(
+        &mut self,
+        contract_id: &str,
+        choice: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        match choice {
+            "TransferFactory_Transfer" => self.transfer(argument, act_as),
+            "TransferInstruction_Accept" => self.accept_offer(contract_id, act_as),
+            "TransferInstruction_Reject" => self.return_offer(contract_id, act_as, "receiver"),
+            "TransferInstruction_Withdraw" => self.return_offer(contract_id, act_as, "sender"),
+            "AllocationFactory_Allocate" => self.allocate(argument, act_as),
+            "Allocation_ExecuteTransfer" => self.execute_allocation(contract_id, act_as),
+            "Allocation_Withdraw" => self.release_allocation(contract_id, act_as, false),
+            "Allocation_Cancel" => self.release_allocation(contract_id, act_as, true),
+            CREATE_DEPOSIT_ACCOUNT_CHOICE => {
+                self.create_deposit_account(contract_id, argument, act_as)
+            }
+            CREATE_WITHDRAW_ACCOUNT_CHOICE => {
+                self.create_withdraw_account(contract_id, argument, act_as)
+            }
+            WITHDRAW_CHOICE => self.withdraw(contract_id, argument, act_as),
+            UPDATE_DESTINATION_ADDRESS_CHOICE => {
+                self.update_destination(contract_id, argument, act_as)
+            }
+            CLOSE_WITHDRAW_ACCOUNT_CHOICE => self.close_withdraw_account(contract_id, act_as),
+            "UserService_OfferFreeCredential" => {
+                self.offer_credential(contract_id, argument, act_as)
+            }
+            "UserService_AcceptFreeCredentialOffer" => {
+                self.accept_credential_offer(contract_id, argument, act_as)
+            }
+            "UserService_CancelCredentialOffer" => {
+                self.cancel_credential_offer(contract_id, argument, act_as)
+            }
+            "UserService_RevokeCredential" => self.revoke_credential(contract_id, argument, act_as),
+            other => Err(MockError::invalid(format!(
+                "Choice {} is not supported by the mock ledger",
+                other
+            ))),
+        }
+    }
+
+    /// `TransferFactory_Transfer`: self-transfers complete immediately (this
+    /// is how split and merge are done); transfers to another party lock the
+    /// amount in a pending offer.
+    fn transfer(&mut self, argument: &Value, act_as: &[String]) -> Result<Value, MockError> {
+        let transfer = &argument["transfer"];
+        let sender = str_arg(transfer, "sender")?;
+        let receiver = str_arg(transfer, "receiver")?;
+        authorize(act_as, &sender)?;
+        self.check_instrument(&transfer["instrumentId"])?;
+        let amount = parse_amount(&transfer["amount"])?;
+        if amount <= 0 {
+            return Err(MockError::invalid("Transfer amount must be positive"));
+        }
+        if let Some(deadline) = transfer["executeBefore"]
+            .as_str()
+            .filter(|d| is_before(d, self.now()))
+        {
+            return Err(MockError::daml_failure(format!(
+                "Transfer `executeBefore` must be in the future: {}",
+                deadline
+            )));
+        }
+
+        let sender_change_cids =
+            self.spend(&sender, &cid_list(&transfer["inputHoldingCids"]), amount)?;
+        if sender == receiver {
+            let cid = self.insert_holding(&receiver, amount, Value::Null);
+            return Ok(json!({
+                "output": {
+                    "tag": "TransferInstructionResult_Completed",
+                    "value": { "receiverHoldingCids": [cid] }
+                },
+                "senderChangeCids": sender_change_cids,
+                "meta": { "values": {} }
+            }));
+        }
+
+        let lock = json!({
+            "holders": [self.admin],
+            "expiresAt": transfer["executeBefore"],
+            "expiresAfter": null,
+            "context": format!("transfer to {}", receiver),
+        });
+        let locked_cid = self.insert_holding(&sender, amount, lock);
+        let mut offer = transfer.clone();
+        offer["inputHoldingCids"] = json!([locked_cid]);
+        let view = json!({
+            "originalInstructionCid": null,
+            "transfer": offer,
+            "status": { "tag": "TransferPendingReceiverAcceptance", "value": {} },
+            "meta": { "values": {} }
+        });
+        let admin = self.admin.clone();
+        let offer_cid = self.insert_with_views(
+            common::consts::TEMPLATE_TRANSFER_OFFER,
+            json!({ "transfer": offer }),
+            &admin,
+            &[&sender, &receiver],
+            vec![(
+                common::consts::TEMPLATE_TRANSFER_INSTRUCTION.to_string(),
+                view,
+            )],
+        );
+        Ok(json!({
+            "output": {
+                "tag": "TransferInstructionResult_Pending",
+                "value": { "transferInstructionCid": offer_cid }
+            },
+            "senderChangeCids": sender_change_cids,
+            "meta": { "values": {} }
+        }))
+    }
+
+    fn accept_offer(&mut self, contract_id: &str, act_as: &[String]) -> Result<Value, MockError> {
+        let offer = self.transfer_offer(contract_id)?;
+        let transfer = &offer.create_argument["transfer"];
+        let receiver = str_arg(transfer, "receiver")?;
+        authorize(act_as, &receiver)?;
+        if let Some(deadline) = transfer["executeBefore"]
+            .as_str()
+            .filter(|d| is_before(d, self.now()))
+        {
+            return Err(MockError::daml_failure(format!(
+                "Transfer `executeBefore` must be in the future: {}",
+                deadline
+            )));
+        }
+
+        let amount = self.release_offer(&offer)?;
+        let cid = self.insert_holding(&receiver, amount, Value::Null);
+        Ok(json!({
+            "output": {
+                "tag": "TransferInstructionResult_Completed",
+                "value": { "receiverHoldingCids": [cid] }
+            },
+            "senderChangeCids": [],
+            "meta": { "values": {} }
+        }))
+    }
+
+    /// Reject (by the receiver) or withdraw (by the sender) an offer, returning
+    /// the locked amount to the sender
+    fn return_offer(
+        &mut self,
+        contract_id: &str,
+        act_as: &[String],
+        controller: &str,
+    ) -> Result<Value, MockError> {
+        let offer = self.transfer_offer(contract_id)?;
+        let transfer = &offer.create_argument["transfer"];
+        authorize(act_as, &str_arg(transfer, controller)?)?;
+
+        let amount = self.release_offer(&offer)?;
+        let sender = str_arg(transfer, "sender")?;
+        let cid = self.insert_holding(&sender, amount, Value::Null);
+        Ok(json!({
+            "output": { "tag": "TransferInstructionResult_Failed", "value": {} },
+            "senderChangeCids": [cid],
+            "meta": { "values": {} }
+        }))
+    }
+
+    fn transfer_offer(&self, contract_id: &str) -> Result<MockContract, MockError> {
+        self.contract(contract_id)
+            .filter(|c| is_transfer_offer(c))
+            .cloned()
+            .ok_or_else(|| MockError::contract_not_found(contract_id))
+    }
+
+    /// Archive an offer and its locked holding, returning the locked amount
+    fn release_offer(&mut self, offer: &MockContract) -> Result<i128, MockError> {
+        self.archive(&offer.contract_id)?;
+        let mut amount = 0;
+        for cid in cid_list(&offer.create_argument["transfer"]["inputHoldingCids"]) {
+            amount += self.archive(&cid)?.amount();
+        }
+        Ok(amount)
+    }
+
+    /// `AllocationFactory_Allocate`: lock the leg's amount for the executor
+    fn allocate(&mut self, argument: &Value, act_as: &[String]) -> Result<Value, MockError> {
+        let allocation = &argument["allocation"];
+        let leg = &allocation["transferLeg"];
+        let sender = str_arg(leg, "sender")?;
+        let receiver = str_arg(leg, "receiver")?;
+        let executor = str_arg(&allocation["settlement"], "executor")?;
+        authorize(act_as, &sender)?;
+        self.check_instrument(&leg["instrumentId"])?;
+        let amount = parse_amount(&leg["amount"])?;
+
+        let sender_change_cids =
+            self.spend(&sender, &cid_list(&argument["inputHoldingCids"]), amount)?;
+        let lock = json!({
+            "holders": [self.admin, executor],
+            "expiresAt": allocation["settlement"]["settleBefore"],
+            "expiresAfter": null,
+            "context": "allocation",
+        });
+        let locked_cid = self.insert_holding(&sender, amount, lock);
+        let view = json!({
+            "allocation": allocation,
+            "holdingCids": [locked_cid],
+            "meta": { "values": {} }
+        });
+        let admin = self.admin.clone();
+        let allocation_cid = self.insert_with_views(
+            common::consts::TEMPLATE_ALLOCATION,
+            view.clone(),
+            &admin,
+            &[&sender, &receiver, &executor],
+            vec![(crate::allocation::INTERFACE_ALLOCATION.to_string(), view)],
+        );
+        Ok(json!({
+            "output": {
+                "tag": "AllocationInstructionResult_Completed",
+                "value": { "allocationCid": allocation_cid }
+            },
+            "senderChangeCids": sender_change_cids,
+            "meta": { "values": {} }
+        }))
+    }
+
+    fn execute_allocation(
+        &mut self,
+        contract_id: &str,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let allocation = self.allocation(contract_id)?;
+        let settlement = &allocation.create_argument["allocation"]["settlement"];
+        authorize(act_as, &str_arg(settlement, "executor")?)?;
+
+        let amount = self.release_allocation_holdings(&allocation)?;
+        let receiver = str_arg(
+            &allocation.create_argument["allocation"]["transferLeg"],
+            "receiver",
+        )?;
+        let cid = self.insert_holding(&receiver, amount, Value::Null);
+        Ok(json!({
+            "senderHoldingCids": [],
+            "receiverHoldingCids": [cid],
+            "meta": { "values": {} }
+        }))
+    }
+
+    /// Withdraw (by the sender) or cancel (by the executor) an allocation,
+    /// returning the locked amount to the sender
+    fn release_allocation(
+        &mut self,
+        contract_id: &str,
+        act_as: &[String],
+        cancel: bool,
+    ) -> Result<Value, MockError> {
+        let allocation = self.allocation(contract_id)?;
+        let allocation_value = &allocation.create_argument["allocation"];
+        let sender = str_arg(&allocation_value["transferLeg"], "sender")?;
+        let controller = if cancel {
+            str_arg(&allocation_value["settlement"], "executor")?
+        } else {
+            sender.clone()
+        };
+        authorize(act_as, &controller)?;
+
+        let amount = self.release_allocation_holdings(&allocation)?;
+        let cid = self.insert_holding(&sender, amount, Value::Null);
+        Ok(json!({
+            "senderHoldingCids": [cid],
+            "meta": { "values": {} }
+        }))
+    }
+
+    fn allocation(&self, contract_id: &str) -> Result<MockContract, MockError> {
+        self.contract(contract_id)
+            .filter(|c| is_allocation(c))
+            .cloned()
+            .ok_or_else(|| MockError::contract_not_found(contract_id))
+    }
+
+    fn release_allocation_holdings(
+        &mut self,
+        allocation: &MockContract,
+    ) -> Result<i128, MockError> {
+        self.archive(&allocation.contract_id)?;
+        let mut amount = 0;
+        for cid in cid_list(&allocation.create_argument["holdingCids"]) {
+            amount += self.archive(&cid)?.amount();
+        }
+        Ok(amount)
+    }
+
+    fn create_deposit_account(
+        &mut self,
+        rules_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        self.require_contract(rules_cid, DEPOSIT_ACCOUNT_RULES_TEMPLATE_ID)?;
+        let owner = str_arg(argument, "owner")?;
+        authorize(act_as, &owner)?;
+        self.check_credentials(&owner, &argument["credentialCids"])?;
+        let account = json!({
+            "id": null,
+            "owner": owner,
+            "operator": self.admin,
+            "registrar": self.admin,
+            "lastProcessedBitcoinBlock": "0",
+            "limits": null,
+        });
+        let admin = self.admin.clone();
+        let cid = self.insert(DEPOSIT_ACCOUNT_TEMPLATE_ID, account, &admin, &[&owner]);
+        Ok(json!({ "depositAccountCid": cid }))
+    }
+
+    fn create_withdraw_account(
+        &mut self,
+        rules_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        self.require_contract(rules_cid, WITHDRAW_ACCOUNT_RULES_TEMPLATE_ID)?;
+        let owner = str_arg(argument, "owner")?;
+        authorize(act_as, &owner)?;
+        self.check_credentials(&owner, &argument["credentialCids"])?;
+        let account = json!({
+            "id": null,
+            "owner": owner,
+            "operator": self.admin,
+            "registrar": self.admin,
+            "destinationBtcAddress": str_arg(argument, "destinationBtcAddress")?,
+            "pendingBalance": format_amount(0),
+            "limits": null,
+        });
+        let admin = self.admin.clone();
+        let cid = self.insert(WITHDRAW_ACCOUNT_TEMPLATE_ID, account, &admin, &[&owner]);
+        Ok(json!({ "withdrawAccountCid": cid }))
+    }
+
+    /// `CBTCWithdrawAccount_Withdraw`: burn `amount` from `tokens` and add it
+    /// to the account's pending balance
+    fn withdraw(
+        &mut self,
+        contract_id: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let account = self.withdraw_account(contract_id, act_as)?;
+        let amount = parse_amount(&argument["amount"])?;
+        if amount <= 0 {
+            return Err(MockError::invalid("Withdraw amount must be positive"));
+        }
+        let owner = account.str_field("owner").to_string();
+        self.check_credentials(&owner, &argument["credentialCids"])?;
+        let change_cids = self.spend(&owner, &cid_list(&argument["tokens"]), amount)?;
+
+        let pending = parse_amount(&account.create_argument["pendingBalance"])?;
+        self.archive(contract_id)?;
+        let mut updated = account.create_argument.clone();
+        updated["pendingBalance"] = json!(format_amount(pending + amount));
+        let cid = self.recreate_withdraw_account(&account, updated);
+        Ok(json!({ "withdrawAccountCid": cid, "changeCids": change_cids }))
+    }
+
+    fn update_destination(
+        &mut self,
+        contract_id: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let account = self.withdraw_account(contract_id, act_as)?;
+        let destination = str_arg(argument, "newDestinationBtcAddress")?;
+        self.archive(contract_id)?;
+        let mut updated = account.create_argument.clone();
+        updated["destinationBtcAddress"] = json!(destination);
+        let cid = self.recreate_withdraw_account(&account, updated);
+        Ok(json!({ "withdrawAccountCid": cid }))
+    }
+
+    fn close_withdraw_account(
+        &mut self,
+        contract_id: &str,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let account = self.withdraw_account(contract_id, act_as)?;
+        if parse_amount(&account.create_argument["pendingBalance"])? != 0 {
+            return Err(MockError::precondition(format!(
+                "Withdraw account {} has a pending balance",
+                contract_id
+            )));
+        }
+        self.archive(contract_id)?;
+        Ok(json!({}))
+    }
+
+    fn withdraw_account(
+        &self,
+        contract_id: &str,
+        act_as: &[String],
+    ) -> Result<MockContract, MockError> {
+        let account = self.require_contract(contract_id, WITHDRAW_ACCOUNT_TEMPLATE_ID)?;
+        authorize(act_as, account.str_field("owner"))?;
+        Ok(account)
+    }
+
+    /// Recreate a withdraw account with `argument`, keeping its account id
+    fn recreate_withdraw_account(&mut self, account: &MockContract, mut argument: Value) -> String {
+        argument["id"] = json!(account_id(account));
+        let admin = self.admin.clone();
+        let owner = account.str_field("owner").to_string();
+        self.insert(WITHDRAW_ACCOUNT_TEMPLATE_ID, argument, &admin, &[&owner])
+    }
+
+    /// The UserService `contract_id`, exercised by its user
+    fn user_service(&self, contract_id: &str, act_as: &[String]) -> Result<String, MockError> {
+        let service = self.require_contract(contract_id, USER_SERVICE_TEMPLATE_ID)?;
+        let user = service.str_field("user").to_string();
+        authorize(act_as, &user)?;
+        Ok(user)
+    }
+
+    /// `UserService_OfferFreeCredential`: the user offers `holder` a credential
+    fn offer_credential(
+        &mut self,
+        service_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let issuer = self.user_service(service_cid, act_as)?;
+        let holder = str_arg(argument, "holder")?;
+        let offer = json!({
+            "issuer": issuer,
+            "holder": holder,
+            "id": str_arg(argument, "id")?,
+            "description": str_arg(argument, "description")?,
+            "claims": argument["claims"],
+        });
+        let cid = self.insert(CREDENTIAL_OFFER_TEMPLATE_ID, offer, &issuer, &[&holder]);
+        Ok(json!({ "credentialOfferCid": cid }))
+    }
+
+    /// `UserService_AcceptFreeCredentialOffer`: the holder turns an offer into
+    /// a credential
+    fn accept_credential_offer(
+        &mut self,
+        service_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let holder = self.user_service(service_cid, act_as)?;
+        let offer_cid = str_arg(argument, "credentialOfferCid")?;
+        let offer = self.require_contract(&offer_cid, CREDENTIAL_OFFER_TEMPLATE_ID)?;
+        if offer.str_field("holder") != holder {
+            return Err(MockError::invalid(format!(
+                "Credential offer {} is not for {}",
+                offer_cid, holder
+            )));
+        }
+        self.archive(&offer_cid)?;
+        let issuer = offer.str_field("issuer").to_string();
+        let cid = self.insert(
+            CREDENTIAL_TEMPLATE_ID,
+            offer.create_argument.clone(),
+            &issuer,
+            &[&holder],
+        );
+        Ok(json!({ "credentialCid": cid }))
+    }
+
+    /// `UserService_CancelCredentialOffer`: the issuer archives a pending
+    /// offer
+    fn cancel_credential_offer(
+        &mut self,
+        service_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let issuer = self.user_service(service_cid, act_as)?;
+        let offer_cid = str_arg(argument, "credentialOfferCid")?;
+        self.archive_issued(&offer_cid, CREDENTIAL_OFFER_TEMPLATE_ID, &issuer)?;
+        Ok(json!({}))
+    }
+
+    /// `UserService_RevokeCredential`: the issuer archives a credential
+    fn revoke_credential(
+        &mut self,
+        service_cid: &str,
+        argument: &Value,
+        act_as: &[String],
+    ) -> Result<Value, MockError> {
+        let issuer = self.user_service(service_cid, act_as)?;
+        let credential_cid = str_arg(argument, "credentialCid")?;
+        self.archive_issued(&credential_cid, CREDENTIAL_TEMPLATE_ID, &issuer)?;
+        Ok(json!({}))
+    }
+
+    /// Archive a credential or offer, which only its issuer may do
+    fn archive_issued(
+        &mut self,
+        contract_id: &str,
+        template_id: &str,
+        issuer: &str,
+    ) -> Result<(), MockError> {
+        let contract = self.require_contract(contract_id, template_id)?;
+        if contract.str_field("issuer") != issuer {
+            return Err(MockError::unauthorized(contract.str_field("issuer")));
+        }
+        self.archive(contract_id)?;
+        Ok(())
+    }
+
+    fn require_contract(
+        &self,
+        contract_id: &str,
+        template_id: &str,
+    ) -> Result<MockContract, MockError> {
+        self.contract(contract_id)
+            .filter(|c| same_identifier(&c.template_id, template_id))
+            .cloned()
+            .ok_or_else(|| MockError::contract_not_found(contract_id))
+    }
+
+    /// Presented credentials must be active and held by `owner`
+    fn check_credentials(&self, owner: &str, credential_cids: &Value) -> Result<(), MockError> {
+        for cid in cid_list(credential_cids) {
+            let credential = self.require_contract(&cid, CREDENTIAL_TEMPLATE_ID)?;
+            if credential.str_field("holder") != owner {
+                return Err(MockError::invalid(format!(
+                    "Credential {} is not held by {}",
+                    cid, owner
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_instrument(&self, instrument_id: &Value) -> Result<(), MockError> {
+        let id = instrument_id["id"].as_str().unwrap_or_default();
+        let admin = instrument_id["admin"].as_str().unwrap_or_default();
+        if !id.eq_ignore_ascii_case(INSTRUMENT_ID) || admin != self.admin {
+            return Err(MockError::invalid(format!(
+                "Unknown instrument {}/{}; the mock only issues {}/{}",
+                admin, id, self.admin, INSTRUMENT_ID
+            )));
+        }
+        Ok(())
+    }
+
+    /// Archive `owner`'s unlocked `inputs`, returning change above `amount`
+    /// to `owner`. Returns the change holding ids.
+    fn spend(
+        &mut self,
+        owner: &str,
+        inputs: &[String],
+        amount: i128,
+    ) -> Result<Vec<String>, MockError> {
+        if inputs.is_empty() {
+            return Err(MockError::invalid("No input holdings provided"));
+        }
+        let mut total = 0;
+        for cid in inputs {
+            let holding = self
+                .contract(cid)
+                .filter(|c| is_holding(c))
+                .cloned()
+                .ok_or_else(|| MockError::contract_not_found(cid))?;
+            if holding.str_field("owner") != owner {
+                return Err(MockError::invalid(format!(
+                    "Holding {} is not owned by {}",
+                    cid, owner
+                )));
+            }
+            if !holding.create_argument["lock"].is_null() {
+                return Err(MockError::precondition(format!(
+                    "Holding {} is locked",
+                    cid
+                )));
+            }
+            total += holding.amount();
+            self.archive(cid)?;
+        }
+        if total < amount {
+            return Err(MockError::precondition(format!(
+                "Insufficient funds: inputs hold {}, {} needed",
+                format_amount(total),
+                format_amount(amount)
+            )));
+        }
+        let change = total - amount;
+        Ok(if change > 0 {
+            vec![self.insert_holding(owner, change, Value::Null)]
+        } else {
+            vec![]
+        })
+    }
+
+    // ---------- storage ----------
+
+    fn insert_holding(&mut self, owner: &str, amount: i128, lock: Value) -> String {
+        let admin = self.admin.clone();
+        let amount = format_amount(amount);
+        let argument = json!({
+            "operator": admin,
+            "provider": admin,
+            "registrar": admin,
+            "owner": owner,
+            "instrument": { "source": admin, "id": INSTRUMENT_ID, "scheme": "RegistrarInternalScheme" },
+            "label": "",
+            "amount": amount,
+            "lock": lock,
+        });
+        let view = json!({
+            "owner": owner,
+            "instrumentId": { "admin": admin, "id": INSTRUMENT_ID },
+            "amount": amount,
+            "lock": lock,
+            "meta": { "values": {} }
+        });
+        self.insert_with_views(
+            HOLDING_TEMPLATE_ID,
+            argument,
+            &admin,
+            &[owner],
+            vec![(common::consts::INTERFACE_HOLDING.to_string(), view)],
+        )
+    }
+
+    fn insert(
+        &mut self,
+        template_id: &str,
+        argument: Value,
+        signatory: &str,
+        observers: &[&str],
+    ) -> String {
+        self.insert_with_views(template_id, argument, signatory, observers, Vec::new())
+    }
+
+    fn insert_with_views(
+        &mut self,
+        template_id: &str,
+        argument: Value,
+        signatory: &str,
+        observers: &[&str],
+        interface_views: Vec<(String, Value)>,
+    ) -> String {
+        self.next_id += 1;
+        let contract_id = format!("00mock{:010x}", self.next_id);
+        self.contracts.push(MockContract {
+            contract_id: contract_id.clone(),
+            template_id: template_id.to_string(),
+            create_argument: argument,
+            signatories: vec![signatory.to_string()],
+            observers: observers
+                .iter()
+                .filter(|p| **p != signatory)
+                .map(|p| p.to_string())
+                .collect(),
+            interface_views,
+            offset: self.offset,
+            created_at: self.now().to_rfc3339(),
+        });
+        contract_id
+    }
+
+    fn archive(&mut self, contract_id: &str) -> Result<MockContract, MockError> {
+        let index = self
+            .contracts
+            .iter()
+            .position(|c| c.contract_id == contract_id)
+            .ok_or_else(|| MockError::contract_not_found(contract_id))?;
+        Ok(self.contracts.remove(index))
+    }
+}
+
+fn empty_choice_context() -> Value {
+    json!({
+        "choiceContextData": { "values": {} },
+        "disclosedContracts": []
+    })
+}
+
+fn is_holding(contract: &MockContract) -> bool {
+    same_identifier(&contract.template_id, HOLDING_TEMPLATE_ID)
+}
+
+fn is_transfer_offer(contract: &MockContract) -> bool {
+    same_identifier(
+        &contract.template_id,
+        common::consts::TEMPLATE_TRANSFER_OFFER,
+    )
+}
+
+fn is_allocation(contract: &MockContract) -> bool {
+    same_identifier(&contract.template_id, common::consts::TEMPLATE_ALLOCATION)
+}
+
+/// The account's stable id: its `id` field, or its contract id while unset
+fn account_id(contract: &MockContract) -> String {
+    match contract.create_argument["id"].as_str() {
+        Some(id) => id.to_string(),
+        None => contract.contract_id.clone(),
+    }
+}
+
+/// Compare template or interface ids by `Module:Entity`, ignoring whether
+/// the package is given by name (`#pkg`) or by id
+fn same_identifier(a: &str, b: &str) -> bool {
+    fn qualified_name(id: &str) -> &str {
+        id.split_once(':').map(|(_, rest)| rest).unwrap_or(id)
+    }
+    qualified_name(a) == qualified_name(b)
+}
+
+fn authorize(act_as: &[String], party: &str) -> Result<(), MockError> {
+    if act_as.iter().any(|p| p == party) {
+        Ok(())
+    } else {
+        Err(MockError::unauthorized(party))
+    }
+}
+
+fn is_before(timestamp: &str, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t < now)
+        .unwrap_or(false)
+}
+
+/// Whether any event in `transaction` is witnessed by one of `parties`
+fn is_witnessed(transaction: &Value, parties: &[String]) -> bool {
+    transaction["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|event| event.as_object()?.values().next())
+        .filter_map(|event| event["witnessParties"].as_array())
+        .flatten()
+        .any(|witness| parties.iter().any(|party| witness == party))
+}
+
+fn str_arg(value: &Value, key: &str) -> Result<String, MockError> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| MockError::invalid(format!("Missing '{}' field", key)))
+}
+
+fn cid_list(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|cids| {
+            cids.iter()
+                .filter_map(|cid| cid.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keys of every object named `name`, anywhere in `value`
+fn collect_keys(value: &Value, name: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if let Some(inner) = child.as_object().filter(|_| key == name) {
+                    out.extend(inner.keys().cloned());
+                }
+                collect_keys(child, name, out);
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_keys(item, name, out)),
+        _ => {}
+    }
+}
+
+/// String values of every field named `name`, anywhere in `value`
+fn collect_strings(value: &Value, name: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if let Some(s) = child.as_str().filter(|_| key == name) {
+                    out.push(s.to_string());
+                }
+                collect_strings(child, name, out);
+            }
+        }
+        Value::Array(items) => items
+            .iter()
+            .for_each(|item| collect_strings(item, name, out)),
+        _ => {}
+    }
+}
+
+fn decimal_amount(amount: &DamlDecimal) -> Result<i128, String> {
+    parse_amount(&json!(amount.to_string())).map_err(|e| e.message)
+}
+
+/// Parse a Daml decimal (JSON string or number) into units of 10^-10
+fn parse_amount(value: &Value) -> Result<i128, MockError> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return Err(MockError::invalid("Missing or invalid amount")),
+    };
+    let invalid = || MockError::invalid(format!("Invalid amount '{}'", text));
+    let (int, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if int.is_empty() || frac.len() > 10 || !digits(int) || !digits(frac) {
+        return Err(invalid());
+    }
+    let int: i128 = int.parse().map_err(|_| invalid())?;
+    let frac: i128 = format!("{:0<10}", frac).parse().map_err(|_| invalid())?;
+    Ok(int * SCALE + frac)
+}
+
+fn format_amount(amount: i128) -> String {
+    let frac = format!("{:010}", amount % SCALE);
+    let frac = frac.trim_end_matches('0');
+    format!(
+        "{}.{}",
+        amount / SCALE,
+        if frac.is_empty() { "0" } else { frac }
+    )
+}
@@ -0,0 +1,936 @@
+//! Local mock of the Canton JSON Ledger API, the token-standard registry and
+//! the Bitsafe API, for running the library offline.
+//!
+//! [`MockServer`] listens on a random localhost port and serves every endpoint
+//! the library calls: ledger end, active contracts (HTTP and websocket),
+//! submit-and-wait, the transfer-factory and allocation-factory routes, the
+//! accept/reject/withdraw and allocation choice contexts, the Bitsafe
+//! `/cbtc/v1/` routes and a Keycloak token endpoint. All of them are backed by one in-memory
+//! [`MockLedger`], so a transfer submitted through [`crate::transfer`] shows up
+//! in the receiver's ACS and can be accepted with [`crate::accept`].
+//!
+//! Access tokens are accepted but not checked; a submission is authorized by
+//! its `actAs` parties alone. Logins through `keycloak::login` against
+//! `password_url(&server.url(), realm)` always succeed.
+//!
+//! # Simulating the network
+//!
+//! Applications swap the real network for the mock by pointing their
+//! `ledger_host`, `registry_url` and Bitsafe API URL at the server; no other
+//! code changes. The ledger then behaves as a token-standard ledger would:
+//!
+//! - Holdings are UTXOs. Transfers, [`MockLedger::merge_split`], allocations
+//!   and withdrawals archive their inputs and return change.
+//! - Transfer offers and allocations carry deadlines checked against the
+//!   ledger's clock, which tests move with [`MockLedger::advance_time`].
+//!   [`MockLedger::expire`] returns expired offers and allocations to their
+//!   senders.
+//! - [`MockLedger::credit_deposit`] and [`MockLedger::complete_withdrawal`]
+//!   play the attestors' side of minting and redeeming.
+//! - Every transaction is recorded and served from `/v2/updates`, so
+//!   [`crate::mint_redeem::mint::poll_deposits`] sees deposits.
+//! - [`MockLedger::add_user_service`] onboards a party to the credential app,
+//!   so it can offer, accept and revoke credentials through
+//!   [`crate::credentials`]; [`MockLedger::issue_credential`] skips the offer.
+//! - [`MockLedger::inject_fault`] makes upcoming requests fail with
+//!   contention or unavailability, or stall past a client's timeout.
+//!
+//! Available in the crate's own tests and, for downstream crates, behind the
+//! `mock-server` feature.
+//!
+//! # Example
+//! ```ignore
+//! use cbtc::mock_server::MockServer;
+//!
+//! let server = MockServer::start().await?;
+//! server
+//!     .ledger()
+//!     .create_holding("alice::1220...", DamlDecimal::parse("1.0")?)?;
+//!
+//! cbtc::transfer::submit(cbtc::transfer::Params {
+//!     ledger_host: server.ledger_host(),
+//!     registry_url: server.registry_url(),
+//!     decentralized_party_id: server.decentralized_party_id(),
+//!     access_token: String::new(),
+//!     transfer,
+//! })
+//! .await?;
+//! ```
+
+mod fault;
+mod http;
+mod ledger;
+
+pub use fault::{Endpoint, Fault, FaultRule};
+pub use ledger::{
+    ALLOCATION_FACTORY_CID, INSTRUMENT_ID, MockContract, MockLedger, TRANSFER_FACTORY_CID,
+};
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Decentralized party the mock issues CBTC as, unless one is given
+pub const DEFAULT_DECENTRALIZED_PARTY_ID: &str = "cbtc-network::1220mock";
+
+/// A running mock server. Stops when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    ledger: Arc<Mutex<MockLedger>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Start a server over an empty ledger administered by
+    /// [`DEFAULT_DECENTRALIZED_PARTY_ID`]
+    pub async fn start() -> Result<Self, String> {
+        Self::start_with(MockLedger::new(DEFAULT_DECENTRALIZED_PARTY_ID)).await
+    }
+
+    /// Start a server over a prepared ledger
+    pub async fn start_with(ledger: MockLedger) -> Result<Self, String> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| format!("Failed to bind mock server: {}", e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read mock server address: {}", e))?;
+        let ledger = Arc::new(Mutex::new(ledger));
+        let task = tokio::spawn(http::serve(listener, ledger.clone()));
+        Ok(Self { addr, ledger, task })
+    }
+
+    /// Base URL, e.g. `http://127.0.0.1:41234`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Value for `ledger_host` params
+    pub fn ledger_host(&self) -> String {
+        self.url()
+    }
+
+    /// Value for `registry_url` params
+    pub fn registry_url(&self) -> String {
+        self.url()
+    }
+
+    /// Bitsafe API URL. Being a localhost URL, it maps to
+    /// [`crate::mint_redeem::bitcoin_address::BitcoinNetwork::Regtest`].
+    pub fn bitsafe_api_url(&self) -> String {
+        self.url()
+    }
+
+    /// Value for `decentralized_party_id` params
+    pub fn decentralized_party_id(&self) -> String {
+        self.ledger().admin().to_string()
+    }
+
+    /// Lock the ledger to seed or inspect it. Don't hold the guard across an
+    /// `.await` on a request to this server.
+    pub fn ledger(&self) -> MutexGuard<'_, MockLedger> {
+        self.ledger.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint_redeem::attestor::{self, BitsafeClient, BitsafeError, QuorumPolicy};
+    use crate::mint_redeem::bitcoin_address::BitcoinNetwork;
+    use crate::mint_redeem::{mint, redeem};
+    use common::decimal::DamlDecimal;
+    use std::time::Duration;
+
+    const ALICE: &str = "alice::1220aaaa";
+    const BOB: &str = "bob::1220bbbb";
+
+    fn d(s: &str) -> DamlDecimal {
+        DamlDecimal::parse(s).unwrap()
+    }
+
+    fn transfer(server: &MockServer, amount: &str) -> common::transfer::Transfer {
+        common::transfer::Transfer {
+            sender: ALICE.to_string(),
+            receiver: BOB.to_string(),
+            amount: d(amount),
+            instrument_id: common::transfer::InstrumentId {
+                admin: server.decentralized_party_id(),
+                id: INSTRUMENT_ID.to_string(),
+            },
+            requested_at: chrono::Utc::now().to_rfc3339(),
+            execute_before: (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339(),
+            input_holding_cids: None,
+            meta: None,
+        }
+    }
+
+    async fn send_offer(server: &MockServer, amount: &str) -> String {
+        crate::transfer::submit(crate::transfer::Params {
+            transfer: transfer(server, amount),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap();
+
+        let offers = crate::utils::fetch_incoming_transfers(
+            server.ledger_host(),
+            BOB.to_string(),
+            String::new(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(offers.len(), 1);
+        offers[0].created_event.contract_id.clone()
+    }
+
+    #[tokio::test]
+    async fn holdings_are_served_over_the_acs_websocket() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("0.5")).unwrap();
+        server.ledger().create_holding(ALICE, d("0.25")).unwrap();
+        server.ledger().create_holding(BOB, d("1")).unwrap();
+
+        let holdings = crate::active_contracts::get(crate::active_contracts::Params {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            access_token: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let total = holdings
+            .iter()
+            .filter_map(crate::utils::extract_amount)
+            .fold(DamlDecimal::ZERO, |acc, amount| acc + amount);
+        assert_eq!(holdings.len(), 2);
+        assert_eq!(total, d("0.75"));
+    }
+
+    #[tokio::test]
+    async fn transfer_offer_can_be_accepted() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+
+        let offer_cid = send_offer(&server, "0.3").await;
+        // The offered amount is locked until the receiver acts
+        assert_eq!(server.ledger().balance(ALICE), d("0.7"));
+
+        crate::accept::submit(crate::accept::Params {
+            transfer_offer_contract_id: offer_cid.clone(),
+            receiver_party: BOB.to_string(),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(server.ledger().balance(ALICE), d("0.7"));
+        assert_eq!(server.ledger().balance(BOB), d("0.3"));
+        assert!(server.ledger().contract(&offer_cid).is_none());
+    }
+
+    #[tokio::test]
+    async fn rejected_and_withdrawn_offers_return_funds() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+
+        let offer_cid = send_offer(&server, "0.4").await;
+        crate::reject::submit(crate::reject::Params {
+            transfer_offer_contract_id: offer_cid,
+            receiver_party: BOB.to_string(),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+            reason: Some("wrong amount".to_string()),
+        })
+        .await
+        .unwrap();
+        assert_eq!(server.ledger().balance(ALICE), d("1.0"));
+
+        let offer_cid = send_offer(&server, "0.4").await;
+        crate::cancel_offers::submit(crate::cancel_offers::Params {
+            transfer_offer_contract_id: offer_cid.clone(),
+            sender_party: ALICE.to_string(),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(server.ledger().balance(ALICE), d("1.0"));
+        assert_eq!(server.ledger().balance(BOB), DamlDecimal::ZERO);
+
+        // The offer is gone, so accepting it now fails
+        let err = crate::accept::submit(crate::accept::Params {
+            transfer_offer_contract_id: offer_cid,
+            receiver_party: BOB.to_string(),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap_err();
+        assert!(
+            err.contains("CONTRACT_NOT_FOUND"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_submission_leaves_ledger_untouched() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("0.1")).unwrap();
+        let end = server.ledger().ledger_end();
+
+        let err = crate::transfer::submit(crate::transfer::Params {
+            transfer: transfer(&server, "0.5"),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.contains("Insufficient funds"),
+            "unexpected error: {err}"
+        );
+        assert_eq!(server.ledger().balance(ALICE), d("0.1"));
+        assert_eq!(server.ledger().ledger_end(), end);
+    }
+
+    #[tokio::test]
+    async fn withdraw_account_is_created_from_bitsafe_rules() {
+        let server = MockServer::start().await.unwrap();
+        let network = BitcoinNetwork::from_api_url(&server.bitsafe_api_url()).unwrap();
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+
+        let account = redeem::create_withdraw_account(redeem::CreateWithdrawAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules_contract_id: rules.wa_rules.contract_id,
+            account_rules_template_id: rules.wa_rules.template_id,
+            account_rules_created_event_blob: rules.wa_rules.created_event_blob,
+            destination_btc_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            bitcoin_network: network,
+            credential_cids: vec![],
+            decentralized_party_id: None,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(account.owner, ALICE);
+        assert_eq!(account.pending_balance, DamlDecimal::ZERO);
+        let accounts = redeem::list_withdraw_accounts(redeem::ListWithdrawAccountsParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            access_token: String::new(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].contract_id, account.contract_id);
+    }
+
+    fn last_credential_cids(server: &MockServer) -> serde_json::Value {
+        let ledger = server.ledger();
+        let submission = ledger.submissions().last().unwrap();
+        submission["commands"][0]["ExerciseCommand"]["choiceArgument"]["credentialCids"].clone()
+    }
+
+    #[tokio::test]
+    async fn account_creation_discovers_the_minter_credential() {
+        let server = MockServer::start().await.unwrap();
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+        let params = || mint::CreateDepositAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules: rules.clone(),
+            credential_cids: vec![],
+            decentralized_party_id: Some(server.decentralized_party_id()),
+        };
+
+        server
+            .ledger()
+            .issue_credential(ALICE, &[("hasCBTCRole", "Viewer")]);
+        let err = mint::create_deposit_account(params()).await.unwrap_err();
+        assert!(
+            err.contains(&format!(
+                "missing a required credential from {} claiming hasCBTCRole=Minter",
+                server.decentralized_party_id()
+            )),
+            "unexpected error: {err}"
+        );
+
+        let minter = server
+            .ledger()
+            .issue_credential(ALICE, &[("hasCBTCRole", "Minter")]);
+        mint::create_deposit_account(params()).await.unwrap();
+        assert_eq!(last_credential_cids(&server), serde_json::json!([minter]));
+    }
+
+    #[tokio::test]
+    async fn issuer_offers_lists_and_revokes_credentials() {
+        use crate::credentials::{self, Claim, ClaimFilter};
+        const ISSUER: &str = "compliance::1220cccc";
+
+        let server = MockServer::start().await.unwrap();
+        for party in [ISSUER, ALICE] {
+            server.ledger().add_user_service(party);
+        }
+        let service = |party: &str| {
+            let party = party.to_string();
+            let ledger_host = server.ledger_host();
+            async move {
+                credentials::find_user_service(credentials::FindUserServiceParams {
+                    ledger_host,
+                    party,
+                    access_token: String::new(),
+                })
+                .await
+                .unwrap()
+            }
+        };
+        let issuer_service = service(ISSUER).await;
+        let kyc = |holder: &str, value: &str| credentials::OfferCredentialParams {
+            ledger_host: server.ledger_host(),
+            party: ISSUER.to_string(),
+            access_token: String::new(),
+            user_service_contract_id: issuer_service.contract_id.clone(),
+            user_service_template_id: issuer_service.template_id.clone(),
+            holder: holder.to_string(),
+            id: format!("kyc-{}", holder),
+            description: "KYC attestation".to_string(),
+            claims: vec![Claim {
+                subject: holder.to_string(),
+                property: "hasKYC".to_string(),
+                value: value.to_string(),
+            }],
+        };
+        let issued = |value: Option<&str>| credentials::ListIssuedCredentialsParams {
+            ledger_host: server.ledger_host(),
+            party: ISSUER.to_string(),
+            access_token: String::new(),
+            claim: Some(ClaimFilter {
+                property: "hasKYC".to_string(),
+                value: value.map(str::to_string),
+            }),
+        };
+
+        let offer = credentials::offer_credential(kyc(ALICE, "true"))
+            .await
+            .unwrap();
+        assert_eq!(offer.holder, ALICE);
+        let bobs = credentials::offer_credential(kyc(BOB, "true"))
+            .await
+            .unwrap();
+        assert_eq!(
+            credentials::list_issued_credential_offers(issued(None))
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        credentials::cancel_credential_offer(credentials::CancelCredentialOfferParams {
+            ledger_host: server.ledger_host(),
+            party: ISSUER.to_string(),
+            access_token: String::new(),
+            user_service_contract_id: issuer_service.contract_id.clone(),
+            user_service_template_id: issuer_service.template_id.clone(),
+            credential_offer_cid: bobs.contract_id,
+        })
+        .await
+        .unwrap();
+        assert!(server.ledger().contract(&offer.contract_id).is_some());
+
+        let alice_service = service(ALICE).await;
+        let credential =
+            credentials::accept_credential_offer(credentials::AcceptCredentialOfferParams {
+                ledger_host: server.ledger_host(),
+                party: ALICE.to_string(),
+                access_token: String::new(),
+                user_service_contract_id: alice_service.contract_id.clone(),
+                user_service_template_id: alice_service.template_id.clone(),
+                credential_offer_cid: offer.contract_id,
+            })
+            .await
+            .unwrap();
+        assert!(
+            credentials::list_issued_credential_offers(issued(None))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let holders: Vec<String> = credentials::list_issued_credentials(issued(Some("true")))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.holder)
+            .collect();
+        assert_eq!(holders, vec![ALICE]);
+        assert!(
+            credentials::list_issued_credentials(issued(Some("false")))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let revoke = |as_party: &str, service: &credentials::UserServiceInfo| {
+            credentials::revoke_credential(credentials::RevokeCredentialParams {
+                ledger_host: server.ledger_host(),
+                party: as_party.to_string(),
+                access_token: String::new(),
+                user_service_contract_id: service.contract_id.clone(),
+                user_service_template_id: service.template_id.clone(),
+                credential_cid: credential.contract_id.clone(),
+            })
+        };
+        // Only the issuer can revoke
+        revoke(ALICE, &alice_service).await.unwrap_err();
+        revoke(ISSUER, &issuer_service).await.unwrap();
+        assert!(
+            credentials::list_issued_credentials(issued(None))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn withdrawal_discovers_credentials_from_the_account_registrar() {
+        let server = MockServer::start().await.unwrap();
+        let minter = server
+            .ledger()
+            .issue_credential(ALICE, &[("hasCBTCRole", "Minter")]);
+        let holding = server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+        let account = redeem::create_withdraw_account(redeem::CreateWithdrawAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules_contract_id: rules.wa_rules.contract_id,
+            account_rules_template_id: rules.wa_rules.template_id,
+            account_rules_created_event_blob: rules.wa_rules.created_event_blob,
+            destination_btc_address: "bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080".to_string(),
+            bitcoin_network: BitcoinNetwork::Regtest,
+            credential_cids: vec![minter.clone()],
+            decentralized_party_id: None,
+        })
+        .await
+        .unwrap();
+
+        let account = redeem::submit_withdraw(redeem::SubmitWithdrawParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            api_url: server.bitsafe_api_url(),
+            account,
+            amount: d("0.4"),
+            holding_contract_ids: vec![holding],
+            credential_cids: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(account.pending_balance, d("0.4"));
+        assert_eq!(last_credential_cids(&server), serde_json::json!([minter]));
+    }
+
+    #[tokio::test]
+    async fn bitsafe_routes_report_attestor_quorum() {
+        let server = MockServer::start().await.unwrap();
+        let client = BitsafeClient::new(&server.bitsafe_api_url());
+
+        let err = client.get_bitcoin_address("00unknown").await.unwrap_err();
+        assert!(err.is_not_found(), "unexpected error: {err}");
+
+        let contracts = client.get_token_standard_contracts().await.unwrap();
+        assert!(!contracts.burn_mint_factory.contract_id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn quorum_wait_gives_up_when_attestors_are_missing() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().set_attestors(3, 1);
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+        let account = mint::create_deposit_account(mint::CreateDepositAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules: rules,
+            credential_cids: vec![],
+            decentralized_party_id: None,
+        })
+        .await
+        .unwrap();
+
+        let client = BitsafeClient::new(&server.bitsafe_api_url());
+        let policy = QuorumPolicy {
+            max_wait: Duration::from_millis(50),
+            poll_interval: Duration::from_millis(10),
+            ..QuorumPolicy::default()
+        };
+        let err = client
+            .get_bitcoin_address_with_quorum(&account.contract_id, &policy)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BitsafeError::QuorumNotReached { .. }));
+
+        server.ledger().set_attestors(3, 3);
+        let address = client
+            .get_bitcoin_address_with_quorum(&account.contract_id, &policy)
+            .await
+            .unwrap();
+        assert_eq!(address.attestors_responded, 3);
+    }
+
+    fn accept_params(server: &MockServer, offer_cid: &str) -> crate::accept::Params {
+        crate::accept::Params {
+            transfer_offer_contract_id: offer_cid.to_string(),
+            receiver_party: BOB.to_string(),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_offer_cannot_be_accepted_and_returns_funds() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+        let offer_cid = send_offer(&server, "0.3").await;
+        assert!(server.ledger().expire().is_empty());
+
+        server.ledger().advance_time(chrono::Duration::hours(2));
+        let err = crate::accept::submit(accept_params(&server, &offer_cid))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            crate::utils::OfferErrorKind::classify(&err),
+            crate::utils::OfferErrorKind::Expired,
+            "unexpected error: {err}"
+        );
+
+        assert_eq!(server.ledger().expire(), vec![offer_cid]);
+        assert_eq!(server.ledger().balance(ALICE), d("1.0"));
+        assert_eq!(server.ledger().balance(BOB), DamlDecimal::ZERO);
+    }
+
+    #[tokio::test]
+    async fn expired_allocation_returns_funds() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+        let now = server.ledger().now();
+        let settlement = crate::allocation::Settlement {
+            executor: BOB.to_string(),
+            settlement_ref: "trade-1".to_string(),
+            settlement_ref_cid: None,
+            requested_at: now.to_rfc3339(),
+            allocate_before: (now + chrono::Duration::minutes(10)).to_rfc3339(),
+            settle_before: (now + chrono::Duration::minutes(20)).to_rfc3339(),
+            meta: common::allocation::Metadata::default(),
+        };
+        let leg = crate::allocation::Leg {
+            leg_id: "leg-0".to_string(),
+            sender: ALICE.to_string(),
+            receiver: BOB.to_string(),
+            amount: d("0.4"),
+            instrument_admin: server.decentralized_party_id(),
+            instrument_id: INSTRUMENT_ID.to_string(),
+            meta: common::allocation::Metadata::default(),
+        };
+        crate::allocation::allocate(crate::allocation::Params {
+            allocation: crate::allocation::specification(&settlement, &leg),
+            requested_at: now.to_rfc3339(),
+            input_holding_cids: vec![],
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(server.ledger().balance(ALICE), d("0.6"));
+
+        server.ledger().advance_time(chrono::Duration::minutes(30));
+        assert_eq!(server.ledger().expire().len(), 1);
+        assert_eq!(server.ledger().balance(ALICE), d("1.0"));
+    }
+
+    #[tokio::test]
+    async fn contention_fails_the_submission_without_applying_it() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().create_holding(ALICE, d("1.0")).unwrap();
+        server.ledger().inject_fault(FaultRule {
+            choice: Some("TransferFactory_Transfer".to_string()),
+            ..FaultRule::new(Endpoint::Submit, Fault::Contention)
+        });
+
+        let params = || crate::transfer::Params {
+            transfer: transfer(&server, "0.3"),
+            ledger_host: server.ledger_host(),
+            access_token: String::new(),
+            registry_url: server.registry_url(),
+            decentralized_party_id: server.decentralized_party_id(),
+        };
+        let err = crate::transfer::submit(params()).await.unwrap_err();
+        assert!(
+            err.contains("LOCAL_VERDICT_LOCKED_CONTRACTS"),
+            "unexpected error: {err}"
+        );
+        assert_eq!(server.ledger().balance(ALICE), d("1.0"));
+
+        // The fault is used up; a retry goes through
+        crate::transfer::submit(params()).await.unwrap();
+        assert_eq!(server.ledger().balance(ALICE), d("0.7"));
+    }
+
+    #[tokio::test]
+    async fn delayed_response_times_out_on_the_client() {
+        let server = MockServer::start().await.unwrap();
+        server.ledger().inject_fault(FaultRule::new(
+            Endpoint::Bitsafe,
+            Fault::Delay(Duration::from_millis(500)),
+        ));
+        let client = BitsafeClient::with_config(attestor::BitsafeClientConfig {
+            timeout: Duration::from_millis(50),
+            retry: attestor::RetryPolicy::none(),
+            ..attestor::BitsafeClientConfig::new(server.bitsafe_api_url())
+        })
+        .unwrap();
+
+        let err = client.get_token_standard_contracts().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BitsafeError::Request {
+                    timed_out: true,
+                    ..
+                }
+            ),
+            "unexpected error: {err}"
+        );
+        client.get_token_standard_contracts().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn merge_split_spends_inputs_and_returns_the_remainder() {
+        let server = MockServer::start().await.unwrap();
+        let mut ledger = server.ledger();
+        let inputs = vec![
+            ledger.create_holding(ALICE, d("0.5")).unwrap(),
+            ledger.create_holding(ALICE, d("0.25")).unwrap(),
+        ];
+
+        let outputs = ledger
+            .merge_split(ALICE, &inputs, &[d("0.1"), d("0.2")])
+            .unwrap();
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(ledger.holdings(ALICE).len(), 3);
+        assert_eq!(ledger.balance(ALICE), d("0.75"));
+
+        // Inputs are archived, so they can't be spent twice
+        let err = ledger.merge_split(ALICE, &inputs, &[d("0.1")]).unwrap_err();
+        assert!(
+            err.contains("could not be found"),
+            "unexpected error: {err}"
+        );
+        let err = ledger.merge_split(ALICE, &outputs, &[d("1")]).unwrap_err();
+        assert!(
+            err.contains("Insufficient funds"),
+            "unexpected error: {err}"
+        );
+        assert_eq!(ledger.balance(ALICE), d("0.75"));
+    }
+
+    #[tokio::test]
+    async fn credited_deposit_is_reported_by_poll_deposits() {
+        let server = MockServer::start().await.unwrap();
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+        let account = mint::create_deposit_account(mint::CreateDepositAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules: rules,
+            credential_cids: vec![],
+            decentralized_party_id: None,
+        })
+        .await
+        .unwrap();
+        let after_offset = server.ledger().ledger_end();
+
+        let account_id = account.account_id().to_string();
+        server
+            .ledger()
+            .credit_deposit(&account_id, d("0.01"), 100)
+            .unwrap();
+        let err = server
+            .ledger()
+            .credit_deposit(&account_id, d("0.01"), 100)
+            .unwrap_err();
+        assert!(err.contains("not after"), "unexpected error: {err}");
+
+        let poll = mint::poll_deposits(mint::PollDepositsParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            access_token: String::new(),
+            account_id: account_id.clone(),
+            after_offset,
+        })
+        .await
+        .unwrap();
+        assert_eq!(poll.deposits.len(), 1);
+        assert_eq!(poll.deposits[0].account_id, account_id);
+        assert_eq!(poll.deposits[0].amount, d("0.01"));
+        assert_eq!(poll.deposits[0].bitcoin_block, 100);
+        assert_eq!(poll.next_offset, server.ledger().ledger_end());
+        assert_eq!(server.ledger().balance(ALICE), d("0.01"));
+    }
+
+    #[tokio::test]
+    async fn watch_deposits_resumes_after_the_last_delivered_deposit() {
+        let server = MockServer::start().await.unwrap();
+        let rules = attestor::get_account_contract_rules(&server.bitsafe_api_url())
+            .await
+            .unwrap();
+        let account = mint::create_deposit_account(mint::CreateDepositAccountParams {
+            ledger_host: server.ledger_host(),
+            party: ALICE.to_string(),
+            user_name: "alice".to_string(),
+            access_token: String::new(),
+            account_rules: rules,
+            credential_cids: vec![],
+            decentralized_party_id: None,
+        })
+        .await
+        .unwrap();
+        let after_offset = server.ledger().ledger_end();
+
+        let account_id = account.account_id().to_string();
+        for block in [100, 200, 300] {
+            server
+                .ledger()
+                .credit_deposit(&account_id, d("0.01"), block)
+                .unwrap();
+        }
+
+        // Stop after the first deposit of the batch, then resume until the last
+        let watch = |from_offset: i64, stop_at_block: i64| {
+            let blocks = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let seen = blocks.clone();
+            let params = mint::WatchDepositsParams {
+                ledger_host: server.ledger_host(),
+                party: ALICE.to_string(),
+                access_token: String::new(),
+                account_id: account_id.clone(),
+                from_offset: Some(from_offset),
+                poll_interval: Duration::from_millis(10),
+                on_deposit: Box::new(move |deposit| {
+                    seen.lock().unwrap().push(deposit.bitcoin_block);
+                    deposit.bitcoin_block != stop_at_block
+                }),
+            };
+            async move {
+                let offset = mint::watch_deposits(params).await.unwrap();
+                let blocks = blocks.lock().unwrap().clone();
+                (offset, blocks)
+            }
+        };
+
+        let (offset, blocks) = watch(after_offset, 100).await;
+        assert_eq!(blocks, vec![100]);
+        assert!(offset < server.ledger().ledger_end());
+
+        let (offset, blocks) = watch(offset, 300).await;
+        assert_eq!(blocks, vec![200, 300]);
+        assert_eq!(offset, server.ledger().ledger_end());
+    }
+
+    #[tokio::test]
+    async fn unknown_choices_are_rejected() {
+        let server = MockServer::start().await.unwrap();
+        let service = server.ledger().add_user_service(ALICE);
+
+        let response = reqwest::Client::new()
+            .post(format!(
+                "{}/v2/commands/submit-and-wait-for-transaction",
+                server.ledger_host()
+            ))
+            .json(&serde_json::json!({
+                "actAs": [ALICE],
+                "commands": [{
+                    "ExerciseCommand": {
+                        "templateId": "#utility-credential-app-v0:Utility.Credential.App.V0.Service.User:UserService",
+                        "contractId": service,
+                        "choice": "UserService_WithdrawCredentialOffer",
+                        "choiceArgument": { "credentialOfferCid": "00offer" },
+                    }
+                }],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 400);
+        let body = response.text().await.unwrap();
+        assert!(body.contains("not supported"), "unexpected error: {body}");
+    }
+
+    #[tokio::test]
+    async fn keycloak_logins_always_succeed() {
+        let server = MockServer::start().await.unwrap();
+        let url = keycloak::login::password_url(&server.url(), "mock");
+
+        let login = keycloak::login::password(keycloak::login::PasswordParams {
+            client_id: "cbtc".to_string(),
+            username: "alice".to_string(),
+            password: "secret".to_string(),
+            url: url.clone(),
+        })
+        .await
+        .unwrap();
+        assert!(!login.access_token.is_empty());
+
+        let refreshed = keycloak::login::refresh(keycloak::login::RefreshParams {
+            client_id: "cbtc".to_string(),
+            refresh_token: login.refresh_token,
+            url,
+        })
+        .await
+        .unwrap();
+        assert!(!refreshed.access_token.is_empty());
+    }
+}
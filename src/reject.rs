@@ -4,6 +4,11 @@
 //! choice and fetches the matching `/choice-contexts/reject` registry context.
 //! The `registry` crate only ships `accept_context`, so the reject choice-context
 //! is fetched here directly (it has the same request/response shape).
+//!
+//! [`reject_batch`] and [`reject_all`] share one reject context across many
+//! offers and submit in batches, retrying a failed batch per offer. An optional
+//! reason is attached to the choice meta so the sender learns why the offer was
+//! refused.
 
 /// Parameters for rejecting a transfer offer (receiver side).
 pub struct Params {
@@ -19,6 +24,76 @@ pub struct Params {
     pub registry_url: String,
     /// Decentralized party ID for CBTC
     pub decentralized_party_id: String,
+    /// Optional rejection reason, sent to the sender in the choice meta
+    pub reason: Option<String>,
+}
+
+/// Default number of rejections submitted per transaction.
+pub const DEFAULT_BATCH_SIZE: usize = 5;
+
+/// Parameters for rejecting a specific set of CBTC transfer offers (by contract
+/// id) as the receiving party, using an existing access token. Submissions are
+/// batched; the registry reject-context is fetched once and shared.
+pub struct RejectBatchParams {
+    /// Contract IDs of the TransferOffer/TransferInstructions to reject
+    pub contract_ids: Vec<String>,
+    /// The receiver party ID
+    pub receiver_party: String,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Access token for the receiver party
+    pub access_token: String,
+    /// Registry URL
+    pub registry_url: String,
+    /// Decentralized party ID for CBTC
+    pub decentralized_party_id: String,
+    /// Optional rejection reason, sent to the sender in the choice meta
+    pub reason: Option<String>,
+    /// Rejections per transaction (defaults to [`DEFAULT_BATCH_SIZE`])
+    pub batch_size: Option<usize>,
+}
+
+/// Parameters for rejecting all pending CBTC transfers for a party.
+pub struct RejectAllParams {
+    /// The receiver party ID
+    pub receiver_party: String,
+    /// Ledger host URL
+    pub ledger_host: String,
+    /// Registry URL
+    pub registry_url: String,
+    /// Decentralized party ID for CBTC
+    pub decentralized_party_id: String,
+    // Keycloak authentication
+    pub keycloak_client_id: String,
+    pub keycloak_username: String,
+    pub keycloak_password: String,
+    pub keycloak_url: String,
+    /// Only reject offers matching this filter (the default rejects everything)
+    pub filter: crate::utils::OfferFilter,
+    /// Optional rejection reason, sent to the sender in the choice meta
+    pub reason: Option<String>,
+    /// Rejections per transaction (defaults to [`DEFAULT_BATCH_SIZE`])
+    pub batch_size: Option<usize>,
+}
+
+/// Result of rejecting a single transfer
+#[derive(Debug, Clone)]
+pub struct RejectResult {
+    pub success: bool,
+    pub contract_id: String,
+    pub amount: Option<String>,
+    pub sender: Option<String>,
+    pub error: Option<String>,
+    /// Classification of `error`, so expired and already-archived offers can be told apart
+    pub error_kind: Option<crate::utils::OfferErrorKind>,
+}
+
+/// Result of rejecting a set of pending transfers
+#[derive(Debug)]
+pub struct RejectAllResult {
+    pub results: Vec<RejectResult>,
+    pub successful_count: usize,
+    pub failed_count: usize,
 }
 
 /// Fetch the reject choice-context from the registry.
@@ -43,8 +118,7 @@ async fn reject_context(
         },
     };
 
-    let client = reqwest::Client::new();
-    let response = client
+    let response = crate::utils::http_client()
         .post(&url)
         .json(&request)
         .send()
@@ -57,7 +131,9 @@ async fn reject_context(
             .text()
             .await
             .unwrap_or_else(|_| "Unable to read response body".to_string());
-        return Err(format!("Registry request failed with status {status}: {body}"));
+        return Err(format!(
+            "Registry request failed with status {status}: {body}"
+        ));
     }
 
     response
@@ -82,35 +158,15 @@ pub async fn submit(params: Params) -> Result<(), String> {
     )
     .await?;
 
-    // `TransferInstruction_Reject` takes the same `ExtraArgs` shape as Accept, so
-    // the `Accept` choice-argument variant serializes the wire payload correctly.
-    let exercise_command = common::submission::ExerciseCommand {
-        exercise_command: common::submission::ExerciseCommandData {
-            template_id: common::consts::TEMPLATE_TRANSFER_INSTRUCTION.to_string(),
-            contract_id: params.transfer_offer_contract_id,
-            choice: "TransferInstruction_Reject".to_string(),
-            choice_argument: common::submission::ChoiceArgumentsVariations::Accept(
-                common::accept::ChoiceArguments {
-                    extra_args: common::accept::ExtraArgs {
-                        context: common::accept::Context {
-                            values: ctx.choice_context_data.values,
-                        },
-                        meta: common::accept::Meta {
-                            values: common::accept::MetaValue {},
-                        },
-                    },
-                },
-            ),
-        },
-    };
-
     let submission_request = common::submission::Submission {
         act_as: vec![params.receiver_party],
         read_as: None,
         command_id: uuid::Uuid::new_v4().to_string(),
-        disclosed_contracts: ctx.disclosed_contracts,
-        commands: vec![common::submission::Command::ExerciseCommand(
-            exercise_command,
+        disclosed_contracts: ctx.disclosed_contracts.clone(),
+        commands: vec![build_reject_command(
+            &params.transfer_offer_contract_id,
+            &ctx,
+            params.reason.as_deref(),
         )],
         ..Default::default()
     };
@@ -124,3 +180,280 @@ pub async fn submit(params: Params) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Build the `extraArgs` for `TransferInstruction_Reject`: the registry context
+/// plus, when given, the rejection reason under [`crate::utils::REASON_META_KEY`].
+fn reject_extra_args(
+    context_values: &impl serde::Serialize,
+    reason: Option<&str>,
+) -> serde_json::Value {
+    let mut meta = serde_json::Map::new();
+    if let Some(reason) = reason {
+        meta.insert(
+            crate::utils::REASON_META_KEY.to_string(),
+            serde_json::Value::String(reason.to_string()),
+        );
+    }
+    serde_json::json!({
+        "context": {
+            "values": context_values
+        },
+        "meta": {
+            "values": meta
+        }
+    })
+}
+
+/// Build a single `TransferInstruction_Reject` exercise command from a shared context.
+fn build_reject_command(
+    contract_id: &str,
+    context: &registry::accept_context::Response,
+    reason: Option<&str>,
+) -> common::submission::Command {
+    // The typed `Accept` choice-argument variant has an empty meta, so the
+    // argument is built as JSON to be able to carry the reason.
+    common::submission::Command::ExerciseCommand(common::submission::ExerciseCommand {
+        exercise_command: common::submission::ExerciseCommandData {
+            template_id: common::consts::TEMPLATE_TRANSFER_INSTRUCTION.to_string(),
+            contract_id: contract_id.to_string(),
+            choice: "TransferInstruction_Reject".to_string(),
+            choice_argument: common::submission::ChoiceArgumentsVariations::Generic(
+                serde_json::json!({
+                    "extraArgs": reject_extra_args(&context.choice_context_data.values, reason)
+                }),
+            ),
+        },
+    })
+}
+
+/// Submit a reject for the given contract ids as one (atomic) transaction.
+async fn submit_rejects(
+    contract_ids: &[String],
+    receiver_party: &str,
+    ledger_host: &str,
+    access_token: &str,
+    context: &registry::accept_context::Response,
+    reason: Option<&str>,
+) -> Result<(), String> {
+    let commands = contract_ids
+        .iter()
+        .map(|cid| build_reject_command(cid, context, reason))
+        .collect();
+    let submission_request = common::submission::Submission {
+        act_as: vec![receiver_party.to_string()],
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts: context.disclosed_contracts.clone(),
+        commands,
+        ..Default::default()
+    };
+    ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
+        request: submission_request,
+    })
+    .await
+    .map(|_| ())
+}
+
+impl crate::utils::OfferOutcome for RejectResult {
+    fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    fn mark_success(&mut self) {
+        self.success = true;
+    }
+
+    fn mark_failed(&mut self, error: String) {
+        self.error_kind = Some(crate::utils::OfferErrorKind::classify(&error));
+        self.error = Some(error);
+    }
+}
+
+/// Reject the given offers in batches of `batch_size`, sharing one context.
+///
+/// A failed batch is retried per offer (see [`crate::utils::submit_offer_batches`]).
+async fn reject_in_batches(
+    pending: Vec<RejectResult>,
+    batch_size: usize,
+    receiver_party: &str,
+    ledger_host: &str,
+    access_token: &str,
+    context: &registry::accept_context::Response,
+    reason: Option<&str>,
+) -> RejectAllResult {
+    let (results, successful_count, failed_count) =
+        crate::utils::submit_offer_batches(pending, batch_size, |contract_ids| async move {
+            submit_rejects(
+                &contract_ids,
+                receiver_party,
+                ledger_host,
+                access_token,
+                context,
+                reason,
+            )
+            .await
+        })
+        .await;
+
+    log::debug!(
+        "Summary: Rejected: {}, Failed: {}",
+        successful_count,
+        failed_count
+    );
+
+    RejectAllResult {
+        results,
+        successful_count,
+        failed_count,
+    }
+}
+
+/// Reject a specific set of CBTC transfer offers by contract id, batched.
+///
+/// Like [`reject_all`] but operates on a provided list of contract ids and an
+/// existing access token (no re-authentication). The reject context is fetched
+/// once and reused for every command.
+///
+/// # Errors
+/// Returns an error string only if the shared registry context cannot be fetched.
+/// Individual offer failures are recorded in the returned result.
+pub async fn reject_batch(params: RejectBatchParams) -> Result<RejectAllResult, String> {
+    if params.contract_ids.is_empty() {
+        return Ok(RejectAllResult {
+            results: Vec::new(),
+            successful_count: 0,
+            failed_count: 0,
+        });
+    }
+
+    let ctx = reject_context(
+        &params.registry_url,
+        &params.decentralized_party_id,
+        &params.contract_ids[0],
+    )
+    .await?;
+
+    let pending = params
+        .contract_ids
+        .into_iter()
+        .map(|contract_id| RejectResult {
+            success: false,
+            contract_id,
+            amount: None,
+            sender: None,
+            error: None,
+            error_kind: None,
+        })
+        .collect();
+
+    Ok(reject_in_batches(
+        pending,
+        params.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        &params.receiver_party,
+        &params.ledger_host,
+        &params.access_token,
+        &ctx,
+        params.reason.as_deref(),
+    )
+    .await)
+}
+
+/// Reject all pending CBTC transfers for a party that match `params.filter`.
+///
+/// This function:
+/// 1. Authenticates with Keycloak
+/// 2. Fetches all pending TransferInstruction contracts where the party is the receiver
+/// 3. Keeps the ones matching `params.filter`
+/// 4. Rejects them in batches of `params.batch_size` (default 5), sharing one
+///    reject context and retrying failed batches per offer
+///
+/// Returns a summary of successful and failed rejections.
+pub async fn reject_all(params: RejectAllParams) -> Result<RejectAllResult, String> {
+    log::debug!("Authenticating with Keycloak...");
+    let auth = keycloak::login::password(keycloak::login::PasswordParams {
+        client_id: params.keycloak_client_id,
+        username: params.keycloak_username,
+        password: params.keycloak_password,
+        url: params.keycloak_url,
+    })
+    .await
+    .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let pending_transfers: Vec<ledger::models::JsActiveContract> =
+        crate::utils::fetch_incoming_transfers(
+            params.ledger_host.clone(),
+            params.receiver_party.clone(),
+            auth.access_token.clone(),
+        )
+        .await?
+        .into_iter()
+        .filter(|contract| params.filter.matches(contract))
+        .collect();
+
+    if pending_transfers.is_empty() {
+        log::debug!("No pending transfers to reject");
+        return Ok(RejectAllResult {
+            results: Vec::new(),
+            successful_count: 0,
+            failed_count: 0,
+        });
+    }
+
+    log::debug!("Rejecting {} pending transfer(s)", pending_transfers.len());
+
+    let ctx = reject_context(
+        &params.registry_url,
+        &params.decentralized_party_id,
+        &pending_transfers[0].created_event.contract_id,
+    )
+    .await?;
+
+    let pending = pending_transfers
+        .iter()
+        .map(|transfer| {
+            let (amount, sender) = crate::utils::offer_details(transfer);
+            RejectResult {
+                success: false,
+                contract_id: transfer.created_event.contract_id.clone(),
+                amount,
+                sender,
+                error: None,
+                error_kind: None,
+            }
+        })
+        .collect();
+
+    Ok(reject_in_batches(
+        pending,
+        params.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        &params.receiver_party,
+        &params.ledger_host,
+        &auth.access_token,
+        &ctx,
+        params.reason.as_deref(),
+    )
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extra_args_carry_reason_in_meta() {
+        let args = reject_extra_args(&json!({}), Some("unknown sender"));
+        assert_eq!(
+            args["meta"]["values"][crate::utils::REASON_META_KEY],
+            json!("unknown sender")
+        );
+    }
+
+    #[test]
+    fn extra_args_without_reason_have_empty_meta() {
+        let args = reject_extra_args(&json!({}), None);
+        assert_eq!(args["meta"]["values"], json!({}));
+    }
+}
@@ -0,0 +1,699 @@
+//! Multi-leg Delivery-versus-Payment settlement, coordinated by the executor.
+//!
+//! The executor of a settlement collects the allocations funded for each leg,
+//! checks them against the agreed [`SettlementSpec`], and executes every leg in
+//! one atomic submission via `Allocation_ExecuteTransfer`. If a leg is still
+//! missing (or doesn't match the spec) once the allocation deadline has passed,
+//! or `settle_before` passes before execution, the executor's own allocations
+//! for the spec'd legs are cancelled instead; allocations sent by other parties
+//! are left for their senders to withdraw.
+//!
+//! `Allocation_ExecuteTransfer` and `Allocation_Cancel` are controlled by the
+//! executor *and* every leg's sender and receiver, so the submission acts as
+//! all of them: the access token must grant `actAs` for each of those parties.
+//! Venues without that authority should settle through a workflow contract the
+//! counterparties have signed instead, e.g. the settlement app's `Dvp` used by
+//! [`crate::swap`].
+
+use crate::allocation::{self, Allocation, Leg, Settlement};
+use registry::allocation_context::AllocationChoice;
+use std::collections::{HashMap, HashSet};
+
+/// The settlement all parties agreed to: its reference, executor, deadlines and legs.
+#[derive(Debug, Clone)]
+pub struct SettlementSpec {
+    /// Executor, settlement reference and deadlines (`requested_at` is not checked)
+    pub settlement: Settlement,
+    /// Every leg that must be allocated before the settlement can execute
+    pub legs: Vec<Leg>,
+}
+
+/// Parameters for settling (or cancelling) a settlement as its executor.
+pub struct SettleParams {
+    pub spec: SettlementSpec,
+    pub ledger_host: String,
+    /// Access token for the executor party (`spec.settlement.executor`)
+    pub access_token: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+    /// Registry URLs for legs whose instrument is administered by another
+    /// party, keyed by instrument admin (e.g. the other side of a swap)
+    pub other_registries: HashMap<String, String>,
+}
+
+/// What [`settle`] did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettlementOutcome {
+    /// Every leg was executed in one transaction
+    Settled {
+        /// Allocation contract IDs that were executed
+        allocation_cids: Vec<String>,
+    },
+    /// Not all legs are allocated yet and the allocation deadline hasn't passed
+    Pending {
+        /// Leg IDs with no allocation yet
+        missing_legs: Vec<String>,
+        /// Allocations that don't match the spec (with the reason)
+        mismatched: Vec<String>,
+    },
+    /// The allocation deadline passed with legs missing; the executor's
+    /// allocations for the spec'd legs have been cancelled
+    Cancelled {
+        missing_legs: Vec<String>,
+        mismatched: Vec<String>,
+        /// Allocation contract IDs that were cancelled
+        allocation_cids: Vec<String>,
+    },
+    /// Every leg was allocated but `settle_before` passed before execution; the
+    /// executor's allocations have been cancelled
+    Expired {
+        /// Allocation contract IDs that were cancelled
+        allocation_cids: Vec<String>,
+    },
+}
+
+/// What to do with a settlement given its current allocations.
+#[derive(Debug, PartialEq)]
+enum Plan {
+    Execute(Vec<String>),
+    Wait {
+        missing_legs: Vec<String>,
+        mismatched: Vec<String>,
+    },
+    Cancel {
+        missing_legs: Vec<String>,
+        mismatched: Vec<String>,
+        allocation_cids: Vec<String>,
+    },
+    Expire(Vec<String>),
+}
+
+/// Which allocation choice [`submit_allocation_actions`] exercises.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LegAction {
+    Execute,
+    Cancel,
+}
+
+impl LegAction {
+    fn allocation_choice(self) -> AllocationChoice {
+        match self {
+            LegAction::Execute => AllocationChoice::ExecuteTransfer,
+            LegAction::Cancel => AllocationChoice::Cancel,
+        }
+    }
+
+    fn daml_choice(self) -> &'static str {
+        match self {
+            LegAction::Execute => "Allocation_ExecuteTransfer",
+            LegAction::Cancel => "Allocation_Cancel",
+        }
+    }
+}
+
+/// Describe how `allocation` deviates from the spec'd `settlement` and `leg`.
+fn mismatches(settlement: &Settlement, leg: &Leg, allocation: &Allocation) -> Vec<String> {
+    let actual = &allocation.settlement;
+    let same_time = |a: &str, b: &str| match (
+        chrono::DateTime::parse_from_rfc3339(a),
+        chrono::DateTime::parse_from_rfc3339(b),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    };
+
+    let mut problems = Vec::new();
+    if actual.executor != settlement.executor {
+        problems.push(format!(
+            "executor {} != {}",
+            actual.executor, settlement.executor
+        ));
+    }
+    if !same_time(&actual.allocate_before, &settlement.allocate_before) {
+        problems.push(format!(
+            "allocateBefore {} != {}",
+            actual.allocate_before, settlement.allocate_before
+        ));
+    }
+    if !same_time(&actual.settle_before, &settlement.settle_before) {
+        problems.push(format!(
+            "settleBefore {} != {}",
+            actual.settle_before, settlement.settle_before
+        ));
+    }
+    let got = &allocation.leg;
+    if got.sender != leg.sender {
+        problems.push(format!("sender {} != {}", got.sender, leg.sender));
+    }
+    if got.receiver != leg.receiver {
+        problems.push(format!("receiver {} != {}", got.receiver, leg.receiver));
+    }
+    if got.amount != leg.amount {
+        problems.push(format!("amount {} != {}", got.amount, leg.amount));
+    }
+    if got.instrument_admin != leg.instrument_admin || got.instrument_id != leg.instrument_id {
+        problems.push(format!(
+            "instrument {}/{} != {}/{}",
+            got.instrument_admin, got.instrument_id, leg.instrument_admin, leg.instrument_id
+        ));
+    }
+    problems
+}
+
+/// The allocations to cancel once the settlement can no longer execute: those
+/// for a spec'd leg whose sender is the executor.
+///
+/// Fails if a spec'd leg has an allocation from another sender, which the
+/// executor can't cancel on its own; its sender has to withdraw it.
+fn cancellable(spec: &SettlementSpec, allocations: &[Allocation]) -> Result<Vec<String>, String> {
+    let spec_legs: HashSet<&str> = spec.legs.iter().map(|l| l.leg_id.as_str()).collect();
+    let (ours, others): (Vec<&Allocation>, Vec<&Allocation>) = allocations
+        .iter()
+        .filter(|a| spec_legs.contains(a.leg.leg_id.as_str()))
+        .partition(|a| a.leg.sender == spec.settlement.executor);
+
+    if !others.is_empty() {
+        let others: Vec<String> = others
+            .iter()
+            .map(|a| {
+                format!(
+                    "{} (leg {}, sender {})",
+                    a.contract_id, a.leg.leg_id, a.leg.sender
+                )
+            })
+            .collect();
+        return Err(format!(
+            "Settlement {} can't be cancelled by its executor {}: allocations {} must be withdrawn by their senders",
+            spec.settlement.settlement_ref,
+            spec.settlement.executor,
+            others.join(", ")
+        ));
+    }
+    Ok(ours.iter().map(|a| a.contract_id.clone()).collect())
+}
+
+/// Match allocations to the spec'd legs and decide whether to execute, wait or cancel.
+///
+/// `allocations` should already be narrowed to the spec's settlement reference.
+fn plan(
+    spec: &SettlementSpec,
+    allocations: &[Allocation],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Plan, String> {
+    let mut matched = Vec::new();
+    let mut missing_legs = Vec::new();
+    let mut mismatched = Vec::new();
+
+    for leg in &spec.legs {
+        let candidates: Vec<&Allocation> = allocations
+            .iter()
+            .filter(|a| a.leg.leg_id == leg.leg_id)
+            .collect();
+        let valid = candidates
+            .iter()
+            .find(|a| mismatches(&spec.settlement, leg, a).is_empty());
+        match valid {
+            Some(allocation) => matched.push(allocation.contract_id.clone()),
+            None => missing_legs.push(leg.leg_id.clone()),
+        }
+        for allocation in candidates {
+            let problems = mismatches(&spec.settlement, leg, allocation);
+            if !problems.is_empty() {
+                mismatched.push(format!(
+                    "{} (leg {}): {}",
+                    allocation.contract_id,
+                    leg.leg_id,
+                    problems.join(", ")
+                ));
+            }
+        }
+    }
+
+    let spec_legs: HashSet<&str> = spec.legs.iter().map(|l| l.leg_id.as_str()).collect();
+    for allocation in allocations {
+        if !spec_legs.contains(allocation.leg.leg_id.as_str()) {
+            mismatched.push(format!(
+                "{} (leg {}): not part of the settlement",
+                allocation.contract_id, allocation.leg.leg_id
+            ));
+        }
+    }
+
+    if missing_legs.is_empty() {
+        let settle_before = chrono::DateTime::parse_from_rfc3339(&spec.settlement.settle_before)
+            .map_err(|e| {
+                format!(
+                    "Invalid settleBefore '{}': {}",
+                    spec.settlement.settle_before, e
+                )
+            })?;
+        if now >= settle_before {
+            return Ok(Plan::Expire(cancellable(spec, allocations)?));
+        }
+        return Ok(Plan::Execute(matched));
+    }
+
+    let allocate_before = chrono::DateTime::parse_from_rfc3339(&spec.settlement.allocate_before)
+        .map_err(|e| {
+            format!(
+                "Invalid allocateBefore '{}': {}",
+                spec.settlement.allocate_before, e
+            )
+        })?;
+    if now < allocate_before {
+        return Ok(Plan::Wait {
+            missing_legs,
+            mismatched,
+        });
+    }
+
+    Ok(Plan::Cancel {
+        missing_legs,
+        mismatched,
+        allocation_cids: cancellable(spec, allocations)?,
+    })
+}
+
+/// The registry URL serving choice contexts for `instrument_admin`'s instruments.
+fn registry_for<'a>(params: &'a SettleParams, instrument_admin: &str) -> Result<&'a str, String> {
+    if instrument_admin == params.decentralized_party_id {
+        return Ok(&params.registry_url);
+    }
+    params
+        .other_registries
+        .get(instrument_admin)
+        .map(String::as_str)
+        .ok_or_else(|| {
+            format!(
+                "No registry configured for instrument admin {}",
+                instrument_admin
+            )
+        })
+}
+
+/// The parties controlling the allocation choices on `legs`: the executor
+/// followed by each leg's sender and receiver, without duplicates.
+fn controllers<'a>(executor: &str, legs: impl IntoIterator<Item = &'a Leg>) -> Vec<String> {
+    let mut parties = vec![executor.to_string()];
+    for leg in legs {
+        for party in [&leg.sender, &leg.receiver] {
+            if !parties.contains(party) {
+                parties.push(party.clone());
+            }
+        }
+    }
+    parties
+}
+
+/// Exercise `action` on every allocation in one atomic submission, acting as
+/// the executor and every leg's sender and receiver (the choices' controllers).
+///
+/// Each allocation's choice context comes from the registry of its instrument admin.
+async fn submit_allocation_actions(
+    action: LegAction,
+    allocation_cids: &[String],
+    allocations: &[Allocation],
+    executor: &str,
+    params: &SettleParams,
+) -> Result<(), String> {
+    let mut commands = Vec::with_capacity(allocation_cids.len());
+    let mut disclosed_contracts = Vec::new();
+    let mut seen = HashSet::new();
+    let mut legs = Vec::with_capacity(allocation_cids.len());
+
+    for cid in allocation_cids {
+        let allocation = allocations
+            .iter()
+            .find(|a| &a.contract_id == cid)
+            .ok_or_else(|| format!("Allocation {} not found", cid))?;
+        let instrument_admin = allocation.leg.instrument_admin.as_str();
+        let (command, disclosed) = allocation::action_command_with_context(
+            action.allocation_choice(),
+            action.daml_choice(),
+            cid,
+            registry_for(params, instrument_admin)?,
+            instrument_admin,
+        )
+        .await?;
+        legs.push(&allocation.leg);
+        commands.push(common::submission::Command::ExerciseCommand(command));
+        for contract in disclosed {
+            if seen.insert(contract.contract_id.clone()) {
+                disclosed_contracts.push(contract);
+            }
+        }
+    }
+
+    let submission_request = common::submission::Submission {
+        act_as: controllers(executor, legs),
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts,
+        commands,
+        ..Default::default()
+    };
+
+    ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+        request: submission_request,
+    })
+    .await
+    .map(|_| ())
+}
+
+/// Settle a multi-leg DvP settlement as its executor.
+///
+/// 1. Lists the executor's allocations for `spec.settlement.settlement_ref`.
+/// 2. Matches one allocation to every spec'd leg, checking parties, amount,
+///    instrument, executor and deadlines.
+/// 3. If every leg is matched and `settle_before` hasn't passed, executes all
+///    of them atomically in one submission (`Allocation_ExecuteTransfer`).
+/// 4. Otherwise, once `allocate_before` (or, with every leg matched,
+///    `settle_before`) has passed, cancels the executor's own allocations for
+///    the spec'd legs in one submission (`Allocation_Cancel`); before the
+///    deadline it reports what is still missing and does nothing.
+///
+/// Both choices are submitted acting as the executor and the submitted legs'
+/// senders and receivers, so `access_token` must grant `actAs` for all of them.
+///
+/// Call it periodically (e.g. after each allocation arrives) until it returns
+/// [`SettlementOutcome::Settled`] or [`SettlementOutcome::Cancelled`].
+///
+/// # Errors
+///
+/// Returns an error string if listing allocations, fetching a registry
+/// context or the ledger submission fails, or if the settlement has to be
+/// cancelled while a spec'd leg has an allocation sent by another party (no
+/// allocation is cancelled then).
+pub async fn settle(params: SettleParams) -> Result<SettlementOutcome, String> {
+    let executor = params.spec.settlement.executor.clone();
+    let allocations: Vec<Allocation> = allocation::list_allocations(allocation::ListParams {
+        party: executor.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?
+    .into_iter()
+    .filter(|a| a.settlement.settlement_ref == params.spec.settlement.settlement_ref)
+    .collect();
+
+    log::debug!(
+        "Settlement {}: {} allocation(s) for {} leg(s)",
+        params.spec.settlement.settlement_ref,
+        allocations.len(),
+        params.spec.legs.len()
+    );
+
+    match plan(&params.spec, &allocations, chrono::Utc::now())? {
+        Plan::Execute(allocation_cids) => {
+            submit_allocation_actions(
+                LegAction::Execute,
+                &allocation_cids,
+                &allocations,
+                &executor,
+                &params,
+            )
+            .await?;
+            Ok(SettlementOutcome::Settled { allocation_cids })
+        }
+        Plan::Wait {
+            missing_legs,
+            mismatched,
+        } => Ok(SettlementOutcome::Pending {
+            missing_legs,
+            mismatched,
+        }),
+        Plan::Cancel {
+            missing_legs,
+            mismatched,
+            allocation_cids,
+        } => {
+            if !allocation_cids.is_empty() {
+                submit_allocation_actions(
+                    LegAction::Cancel,
+                    &allocation_cids,
+                    &allocations,
+                    &executor,
+                    &params,
+                )
+                .await?;
+            }
+            Ok(SettlementOutcome::Cancelled {
+                missing_legs,
+                mismatched,
+                allocation_cids,
+            })
+        }
+        Plan::Expire(allocation_cids) => {
+            if !allocation_cids.is_empty() {
+                submit_allocation_actions(
+                    LegAction::Cancel,
+                    &allocation_cids,
+                    &allocations,
+                    &executor,
+                    &params,
+                )
+                .await?;
+            }
+            Ok(SettlementOutcome::Expired { allocation_cids })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use allocation::AllocationStatus;
+    use common::decimal::DamlDecimal;
+
+    fn at(s: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    fn settlement() -> Settlement {
+        Settlement {
+            executor: "venue::1220".to_string(),
+            settlement_ref: "trade-42".to_string(),
+            settlement_ref_cid: None,
+            requested_at: "2026-01-01T00:00:00Z".to_string(),
+            allocate_before: "2026-01-02T00:00:00Z".to_string(),
+            settle_before: "2026-01-03T00:00:00Z".to_string(),
+            meta: common::allocation::Metadata::default(),
+        }
+    }
+
+    fn leg(leg_id: &str, sender: &str, receiver: &str, amount: &str, id: &str) -> Leg {
+        Leg {
+            leg_id: leg_id.to_string(),
+            sender: sender.to_string(),
+            receiver: receiver.to_string(),
+            amount: DamlDecimal::parse(amount).unwrap(),
+            instrument_admin: "admin::1220".to_string(),
+            instrument_id: id.to_string(),
+            meta: common::allocation::Metadata::default(),
+        }
+    }
+
+    fn spec() -> SettlementSpec {
+        SettlementSpec {
+            settlement: settlement(),
+            legs: vec![
+                leg("leg-0", "alice::1220", "bob::1220", "0.5", "CBTC"),
+                leg("leg-1", "bob::1220", "alice::1220", "50000", "USDC"),
+            ],
+        }
+    }
+
+    fn allocation(cid: &str, leg: Leg) -> Allocation {
+        Allocation {
+            contract_id: cid.to_string(),
+            settlement: settlement(),
+            leg,
+            holding_cids: vec![],
+            status: AllocationStatus::AwaitingSettlement,
+        }
+    }
+
+    #[test]
+    fn all_legs_allocated_executes() {
+        let spec = spec();
+        let allocations = vec![
+            allocation("00a0", spec.legs[0].clone()),
+            allocation("00a1", spec.legs[1].clone()),
+        ];
+        let plan = plan(&spec, &allocations, at("2026-01-01T12:00:00Z")).unwrap();
+        assert_eq!(
+            plan,
+            Plan::Execute(vec!["00a0".to_string(), "00a1".to_string()])
+        );
+    }
+
+    #[test]
+    fn missing_leg_before_deadline_waits() {
+        let spec = spec();
+        let allocations = vec![allocation("00a0", spec.legs[0].clone())];
+        let plan = plan(&spec, &allocations, at("2026-01-01T12:00:00Z")).unwrap();
+        assert_eq!(
+            plan,
+            Plan::Wait {
+                missing_legs: vec!["leg-1".to_string()],
+                mismatched: vec![],
+            }
+        );
+    }
+
+    /// The spec with alice, the sender of leg-0, as its executor
+    fn spec_executed_by_alice() -> SettlementSpec {
+        let mut spec = spec();
+        spec.settlement.executor = "alice::1220".to_string();
+        spec
+    }
+
+    fn allocation_for(spec: &SettlementSpec, cid: &str, leg: Leg) -> Allocation {
+        Allocation {
+            settlement: spec.settlement.clone(),
+            ..allocation(cid, leg)
+        }
+    }
+
+    #[test]
+    fn missing_leg_after_deadline_cancels_the_executors_allocations() {
+        let spec = spec_executed_by_alice();
+        let allocations = vec![
+            allocation_for(&spec, "00a0", spec.legs[0].clone()),
+            allocation_for(
+                &spec,
+                "00x",
+                leg("leg-9", "eve::1220", "bob::1220", "1", "CBTC"),
+            ),
+        ];
+        let plan = plan(&spec, &allocations, at("2026-01-02T00:00:00Z")).unwrap();
+        assert_eq!(
+            plan,
+            Plan::Cancel {
+                missing_legs: vec!["leg-1".to_string()],
+                mismatched: vec!["00x (leg leg-9): not part of the settlement".to_string()],
+                allocation_cids: vec!["00a0".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn wrong_amount_is_mismatched_and_not_executed() {
+        let spec = spec();
+        let allocations = vec![
+            allocation("00a0", spec.legs[0].clone()),
+            allocation(
+                "00a1",
+                leg("leg-1", "bob::1220", "alice::1220", "49999", "USDC"),
+            ),
+        ];
+        match plan(&spec, &allocations, at("2026-01-01T12:00:00Z")).unwrap() {
+            Plan::Wait {
+                missing_legs,
+                mismatched,
+            } => {
+                assert_eq!(missing_legs, vec!["leg-1".to_string()]);
+                assert_eq!(mismatched.len(), 1);
+                assert!(
+                    mismatched[0].contains("amount"),
+                    "unexpected: {mismatched:?}"
+                );
+            }
+            other => panic!("expected Wait, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn extra_allocations_do_not_block_execution() {
+        let spec = spec();
+        let allocations = vec![
+            allocation("00a0", spec.legs[0].clone()),
+            allocation("00a1", spec.legs[1].clone()),
+            allocation("00x", leg("leg-9", "eve::1220", "bob::1220", "1", "CBTC")),
+        ];
+        let plan = plan(&spec, &allocations, at("2026-01-01T12:00:00Z")).unwrap();
+        assert_eq!(
+            plan,
+            Plan::Execute(vec!["00a0".to_string(), "00a1".to_string()])
+        );
+    }
+
+    #[test]
+    fn all_legs_allocated_after_settle_before_expires() {
+        let mut spec = spec_executed_by_alice();
+        spec.legs[1] = leg("leg-1", "alice::1220", "carol::1220", "1", "CBTC");
+        let allocations = vec![
+            allocation_for(&spec, "00a0", spec.legs[0].clone()),
+            allocation_for(&spec, "00a1", spec.legs[1].clone()),
+        ];
+        let plan = plan(&spec, &allocations, at("2026-01-03T00:00:00Z")).unwrap();
+        assert_eq!(
+            plan,
+            Plan::Expire(vec!["00a0".to_string(), "00a1".to_string()])
+        );
+    }
+
+    #[test]
+    fn other_senders_allocations_are_not_cancelled() {
+        let spec = spec_executed_by_alice();
+        let allocations = vec![
+            allocation_for(&spec, "00a0", spec.legs[0].clone()),
+            allocation_for(&spec, "00a1", spec.legs[1].clone()),
+        ];
+        let err = plan(&spec, &allocations, at("2026-01-03T00:00:00Z")).unwrap_err();
+        assert!(
+            err.contains("00a1 (leg leg-1, sender bob::1220)") && !err.contains("00a0"),
+            "unexpected error: {err}"
+        );
+
+        let spec = spec();
+        let allocations = vec![allocation("00a0", spec.legs[0].clone())];
+        let err = plan(&spec, &allocations, at("2026-01-02T00:00:00Z")).unwrap_err();
+        assert!(err.contains("withdrawn"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn controllers_are_executor_then_leg_parties_once() {
+        let spec = spec();
+        assert_eq!(
+            controllers("venue::1220", &spec.legs),
+            vec!["venue::1220", "alice::1220", "bob::1220"]
+        );
+    }
+
+    #[test]
+    fn equivalent_deadline_formats_match() {
+        let spec = spec();
+        let mut other = allocation("00a0", spec.legs[0].clone());
+        other.settlement.settle_before = "2026-01-03T00:00:00.000000Z".to_string();
+        assert!(mismatches(&spec.settlement, &spec.legs[0], &other).is_empty());
+    }
+
+    #[test]
+    fn registry_chosen_by_instrument_admin() {
+        let params = SettleParams {
+            spec: spec(),
+            ledger_host: String::new(),
+            access_token: String::new(),
+            registry_url: "https://cbtc-registry".to_string(),
+            decentralized_party_id: "cbtc-network::1220".to_string(),
+            other_registries: HashMap::from([(
+                "usdc-admin::1220".to_string(),
+                "https://usdc-registry".to_string(),
+            )]),
+        };
+        assert_eq!(
+            registry_for(&params, "cbtc-network::1220").unwrap(),
+            "https://cbtc-registry"
+        );
+        assert_eq!(
+            registry_for(&params, "usdc-admin::1220").unwrap(),
+            "https://usdc-registry"
+        );
+        assert!(registry_for(&params, "unknown::1220").is_err());
+    }
+}
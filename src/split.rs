@@ -1,3 +1,6 @@
+use crate::active_contracts;
+use crate::mint_redeem::models::Holding;
+use common::decimal::DamlDecimal;
 use ledger::models::JsSubmitAndWaitForTransactionResponse;
 use std::collections::HashMap;
 use std::ops::Add;
@@ -14,22 +17,85 @@ pub struct Params {
 }
 
 pub struct SplitResult {
+    /// One holding per requested amount, in the order of `Params::amounts`
     pub output_holding_cids: Vec<String>,
     pub change_holding_cids: Vec<String>,
 }
 
-/// Split a single amount using MergeSplit
-#[allow(clippy::too_many_arguments)]
-async fn split_once(
-    party: String,
-    amount: common::decimal::DamlDecimal,
+/// Amounts for `n` equal outputs of `amount` each, e.g. to pre-fund payment lanes.
+pub fn split_equal(n: usize, amount: DamlDecimal) -> Vec<DamlDecimal> {
+    vec![amount; n]
+}
+
+/// Amounts for a set of `(denomination, count)` pairs, e.g.
+/// `[(0.1, 5), (0.01, 10)]` for five 0.1 and ten 0.01 outputs.
+pub fn split_into_denominations(denominations: &[(DamlDecimal, usize)]) -> Vec<DamlDecimal> {
+    denominations
+        .iter()
+        .flat_map(|(amount, count)| std::iter::repeat_n(*amount, *count))
+        .collect()
+}
+
+/// Share the input holdings out among the requested amounts so that every
+/// amount gets its own self-transfer in one transaction: each amount is funded
+/// by holdings no other amount uses. Amounts a single holding covers take the
+/// smallest such holding, largest amounts first; the rest then merge the
+/// largest holdings left. Returns the inputs per amount, in order, and the
+/// holdings no amount needs.
+fn assign_inputs(
+    amounts: &[DamlDecimal],
+    mut holdings: Vec<Holding>,
+) -> Result<(Vec<Vec<String>>, Vec<String>), String> {
+    let holding_count = holdings.len();
+    holdings.sort_by(|a, b| {
+        a.amount
+            .partial_cmp(&b.amount)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut order: Vec<usize> = (0..amounts.len()).collect();
+    order.sort_by(|a, b| {
+        amounts[*b]
+            .partial_cmp(&amounts[*a])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut inputs: Vec<Vec<String>> = vec![Vec::new(); amounts.len()];
+    for idx in &order {
+        if let Some(pos) = holdings.iter().position(|h| h.amount >= amounts[*idx]) {
+            inputs[*idx].push(holdings.remove(pos).contract_id);
+        }
+    }
+    for idx in order {
+        if !inputs[idx].is_empty() {
+            continue;
+        }
+        let mut total = DamlDecimal::ZERO;
+        while total < amounts[idx] {
+            let holding = holdings.pop().ok_or_else(|| {
+                format!(
+                    "Cannot split {} input holding(s) into {} outputs in one transaction: each output needs input holdings of its own that cover it",
+                    holding_count,
+                    amounts.len()
+                )
+            })?;
+            total += holding.amount;
+            inputs[idx].push(holding.contract_id);
+        }
+    }
+
+    Ok((
+        inputs,
+        holdings.into_iter().map(|h| h.contract_id).collect(),
+    ))
+}
+
+/// Build a MergeSplit self-transfer of `amount` from `input_holding_cids`.
+fn self_transfer(
+    party: &str,
+    amount: DamlDecimal,
     instrument_id: common::transfer::InstrumentId,
     input_holding_cids: Vec<String>,
-    ledger_host: String,
-    access_token: String,
-    registry_url: String,
-    decentralized_party_id: String,
-) -> Result<(String, Vec<String>), String> {
+) -> common::transfer::Transfer {
     // Create metadata with the MergeSplit transaction kind
     let mut transfer_meta: HashMap<String, String> = HashMap::new();
     transfer_meta.insert(
@@ -41,10 +107,10 @@ async fn split_once(
         "merge-split".to_string(),
     );
 
-    // Create a self-transfer (sender == receiver triggers MergeSplit)
-    let transfer = common::transfer::Transfer {
-        sender: party.clone(),
-        receiver: party.clone(), // Self-transfer
+    // Sender == receiver triggers MergeSplit
+    common::transfer::Transfer {
+        sender: party.to_string(),
+        receiver: party.to_string(),
         amount,
         instrument_id,
         requested_at: chrono::Utc::now().to_rfc3339(),
@@ -55,16 +121,25 @@ async fn split_once(
         meta: Some(common::transfer::Meta {
             values: Some(transfer_meta),
         }),
-    };
+    }
+}
 
+/// Submit several self-transfers (over disjoint holdings) as one transaction.
+///
+/// The registry context is fetched once, for the first transfer, and shared.
+/// Returns `(output_cid, change_cids)` per transfer, in order.
+async fn submit_self_transfers(
+    transfers: Vec<common::transfer::Transfer>,
+    params: &Params,
+) -> Result<Vec<(String, Vec<String>)>, String> {
     let additional_information =
         registry::transfer_factory::get(registry::transfer_factory::Params {
-            registry_url,
-            decentralized_party_id: decentralized_party_id.clone(),
+            registry_url: params.registry_url.clone(),
+            decentralized_party_id: params.decentralized_party_id.clone(),
             request: registry::transfer_factory::Request {
                 choice_arguments: common::transfer_factory::ChoiceArguments {
-                    expected_admin: decentralized_party_id.clone(),
-                    transfer: transfer.clone(),
+                    expected_admin: params.decentralized_party_id.clone(),
+                    transfer: transfers[0].clone(),
                     extra_args: common::transfer_factory::ExtraArgs {
                         context: common::transfer_factory::Context {
                             values: HashMap::new(),
@@ -79,40 +154,47 @@ async fn split_once(
         })
         .await?;
 
-    let exercise_command = common::submission::ExerciseCommand {
-        exercise_command: common::submission::ExerciseCommandData {
-            template_id: common::consts::TEMPLATE_TRANSFER_FACTORY.to_string(),
-            contract_id: additional_information.factory_id,
-            choice: "TransferFactory_Transfer".to_string(),
-            choice_argument: common::submission::ChoiceArgumentsVariations::TransferFactory(
-                common::transfer_factory::ChoiceArguments {
-                    expected_admin: decentralized_party_id,
-                    transfer: transfer.clone(),
-                    extra_args: common::transfer_factory::ExtraArgs {
-                        context: additional_information.choice_context.choice_context_data,
-                        meta: common::transfer_factory::Meta {
-                            values: common::transfer_factory::MetaValue {},
+    let expected = transfers.len();
+    let commands = transfers
+        .into_iter()
+        .map(|transfer| {
+            common::submission::Command::ExerciseCommand(common::submission::ExerciseCommand {
+                exercise_command: common::submission::ExerciseCommandData {
+                    template_id: common::consts::TEMPLATE_TRANSFER_FACTORY.to_string(),
+                    contract_id: additional_information.factory_id.clone(),
+                    choice: "TransferFactory_Transfer".to_string(),
+                    choice_argument: common::submission::ChoiceArgumentsVariations::TransferFactory(
+                        common::transfer_factory::ChoiceArguments {
+                            expected_admin: params.decentralized_party_id.clone(),
+                            transfer,
+                            extra_args: common::transfer_factory::ExtraArgs {
+                                context: additional_information
+                                    .choice_context
+                                    .choice_context_data
+                                    .clone(),
+                                meta: common::transfer_factory::Meta {
+                                    values: common::transfer_factory::MetaValue {},
+                                },
+                            },
                         },
-                    },
+                    ),
                 },
-            ),
-        },
-    };
+            })
+        })
+        .collect();
 
     let submission_request = common::submission::Submission {
-        act_as: vec![transfer.sender],
+        act_as: vec![params.party.clone()],
         read_as: None,
         command_id: uuid::Uuid::new_v4().to_string(),
         disclosed_contracts: additional_information.choice_context.disclosed_contracts,
-        commands: vec![common::submission::Command::ExerciseCommand(
-            exercise_command,
-        )],
+        commands,
         ..Default::default()
     };
 
     let response_raw = ledger::submit::wait_for_transaction(ledger::submit::Params {
-        ledger_host,
-        access_token,
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
         request: submission_request,
     })
     .await?;
@@ -121,86 +203,163 @@ async fn split_once(
     let response: JsSubmitAndWaitForTransactionResponse = serde_json::from_str(&response_raw)
         .map_err(|e| format!("Failed to parse submit response: {e}"))?;
 
-    parse_split_response(&response)
+    let outputs = parse_split_response(&response)?;
+    if outputs.len() != expected {
+        return Err(format!(
+            "Expected {} split results in transaction, found {}",
+            expected,
+            outputs.len()
+        ));
+    }
+    Ok(outputs)
 }
 
-/// Extract `(output_cid, change_cids)` from a flat-shaped submit response for
-/// a split (MergeSplit self-transfer).
+/// Extract `(output_cid, change_cids)` per split from a flat-shaped submit
+/// response containing one or more MergeSplit self-transfers.
 ///
-/// Walks `transaction.events`, finds the first `ExercisedEvent` whose
-/// `exercise_result` is an object, then pulls the first
+/// Walks `transaction.events` and, for every `TransferFactory_Transfer`
+/// `ExercisedEvent` whose `exercise_result` is an object, pulls the first
 /// `output.value.receiverHoldingCids[0]` as the output and
-/// `senderChangeCids` as the change list. The `exercise_result` payload is a
-/// raw `serde_json::Value` because the Daml-encoded variant shape isn't part
-/// of the Ledger API schema.
+/// `senderChangeCids` as the change list, in event (= command) order. The
+/// `exercise_result` payload is a raw `serde_json::Value` because the
+/// Daml-encoded variant shape isn't part of the Ledger API schema.
 fn parse_split_response(
     response: &JsSubmitAndWaitForTransactionResponse,
-) -> Result<(String, Vec<String>), String> {
-    let events = &response.transaction.events;
-
-    let mut exercise_result = None;
-    for event in events {
-        if let Some(exercised) = crate::event_helpers::as_exercised_event(event) {
-            if let Some(Some(result)) = exercised.exercise_result.as_ref() {
-                if result.is_object() {
-                    exercise_result = Some(result);
-                    break;
-                }
-            }
+) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut splits = Vec::new();
+
+    for event in &response.transaction.events {
+        let Some(exercised) = crate::event_helpers::as_exercised_event(event) else {
+            continue;
+        };
+        if exercised.choice != "TransferFactory_Transfer" {
+            continue;
+        }
+        let Some(Some(exercise_result)) = exercised.exercise_result.as_ref() else {
+            continue;
+        };
+        if !exercise_result.is_object() {
+            continue;
         }
-    }
 
-    let exercise_result = exercise_result.ok_or("Failed to find ExercisedEvent")?;
+        // Extract receiverHoldingCids from output.value.receiverHoldingCids
+        let output_cid = exercise_result["output"]["value"]["receiverHoldingCids"][0]
+            .as_str()
+            .ok_or("Failed to extract output holding CID")?
+            .to_string();
 
-    // Extract receiverHoldingCids from output.value.receiverHoldingCids
-    let output_cid = exercise_result["output"]["value"]["receiverHoldingCids"][0]
-        .as_str()
-        .ok_or("Failed to extract output holding CID")?
-        .to_string();
+        // Extract senderChangeCids (remaining holdings after split)
+        let change_cids: Vec<String> = exercise_result["senderChangeCids"]
+            .as_array()
+            .ok_or("Failed to extract change holding CIDs")?
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect();
 
-    // Extract senderChangeCids (remaining holdings after split)
-    let change_cids: Vec<String> = exercise_result["senderChangeCids"]
-        .as_array()
-        .ok_or("Failed to extract change holding CIDs")?
-        .iter()
-        .filter_map(|v| v.as_str().map(|s| s.to_string()))
-        .collect();
+        splits.push((output_cid, change_cids));
+    }
 
-    Ok((output_cid, change_cids))
+    if splits.is_empty() {
+        return Err("Failed to find ExercisedEvent".to_string());
+    }
+
+    Ok(splits)
 }
 
-/// Split holdings into multiple chunks plus change.
-/// Takes input holdings and splits them sequentially into the specified amounts.
-/// Returns all output holdings plus any remaining change.
+/// Split holdings into the requested amounts plus change, in one transaction.
+///
+/// A MergeSplit self-transfer produces a single output, and commands in one
+/// transaction can't spend each other's outputs, so every amount is carved
+/// out of input holdings of its own by one self-transfer, and all of them are
+/// submitted together. The split is applied completely or not at all.
+///
+/// The input holdings must be shareable among the amounts that way; a request
+/// for more outputs than they can fund is rejected before anything is
+/// submitted. Split in several calls to fan out further, feeding each call the
+/// previous one's outputs and change.
+///
+/// Returns one output holding per amount (in order) plus the change, which
+/// includes any input holdings the amounts didn't need.
+///
+/// # Errors
+/// Returns an error if the input holdings can't be read, aren't the party's
+/// unlocked CBTC holdings, can't fund the amounts in one transaction, or the
+/// transaction fails.
 pub async fn submit(params: Params) -> Result<SplitResult, String> {
-    let mut output_holding_cids = Vec::new();
-    let mut current_holdings = params.input_holding_cids;
-
-    // Split off each amount sequentially
-    for amount in params.amounts {
-        let (output_cid, change_cids) = split_once(
-            params.party.clone(),
-            amount,
-            params.instrument_id.clone(),
-            current_holdings,
-            params.ledger_host.clone(),
-            params.access_token.clone(),
-            params.registry_url.clone(),
-            params.decentralized_party_id.clone(),
-        )
-        .await?;
+    if params.amounts.is_empty() {
+        return Ok(SplitResult {
+            output_holding_cids: Vec::new(),
+            change_holding_cids: params.input_holding_cids,
+        });
+    }
+    if let Some(amount) = params.amounts.iter().find(|a| **a <= DamlDecimal::ZERO) {
+        return Err(format!("Split amounts must be positive, got {}", amount));
+    }
 
-        output_holding_cids.push(output_cid);
-        current_holdings = change_cids;
+    let contracts = active_contracts::get(active_contracts::Params {
+        ledger_host: params.ledger_host.clone(),
+        party: params.party.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+    let mut holdings = Vec::with_capacity(params.input_holding_cids.len());
+    for cid in &params.input_holding_cids {
+        let holding = contracts
+            .iter()
+            .find(|c| &c.created_event.contract_id == cid)
+            .map(Holding::from_active_contract)
+            .transpose()?
+            .filter(|h| h.owner == params.party)
+            .ok_or_else(|| {
+                format!(
+                    "Input holding {} is not an unlocked CBTC holding of {}",
+                    cid, params.party
+                )
+            })?;
+        holdings.push(holding);
+    }
 
-        if current_holdings.is_empty() {
-            return Err("Insufficient funds for split".to_string());
-        }
+    let available = holdings
+        .iter()
+        .fold(DamlDecimal::ZERO, |acc, h| acc + h.amount);
+    let required = params
+        .amounts
+        .iter()
+        .fold(DamlDecimal::ZERO, |acc, amount| acc + *amount);
+    if available < required {
+        return Err(format!(
+            "Insufficient funds for split: need {}, input holdings total {}",
+            required, available
+        ));
     }
 
+    let (inputs, unused) = assign_inputs(&params.amounts, holdings)?;
+    let transfers = params
+        .amounts
+        .iter()
+        .zip(inputs)
+        .map(|(amount, input_holding_cids)| {
+            self_transfer(
+                &params.party,
+                *amount,
+                params.instrument_id.clone(),
+                input_holding_cids,
+            )
+        })
+        .collect();
+    let results = submit_self_transfers(transfers, &params).await?;
+
+    let mut output_holding_cids = Vec::with_capacity(results.len());
+    let mut change_holding_cids = Vec::new();
+    for (output_cid, change_cids) in results {
+        output_holding_cids.push(output_cid);
+        change_holding_cids.extend(change_cids);
+    }
+    change_holding_cids.extend(unused);
+
     Ok(SplitResult {
         output_holding_cids,
-        change_holding_cids: current_holdings,
+        change_holding_cids,
     })
 }
 
@@ -208,35 +367,22 @@ pub async fn submit(params: Params) -> Result<SplitResult, String> {
 mod tests {
     use super::*;
     use crate::active_contracts;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
 
     #[tokio::test]
     async fn test_split() {
-        // Load environment variables from .env file
-        dotenvy::dotenv().ok();
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
+        let env = TestEnv::load().await;
+        let access_token = env.access_token().await;
 
-        let party = env::var("PARTY_ID").expect("PARTY_ID must be set");
-        let ledger_host = env::var("LEDGER_HOST").expect("LEDGER_HOST must be set");
-        let decentralized_party =
-            env::var("DECENTRALIZED_PARTY_ID").expect("DECENTRALIZED_PARTY_ID must be set");
+        let party = env.party();
+        let ledger_host = env.ledger_host();
+        let decentralized_party = env.decentralized_party_id();
 
         // Get active contracts to use as input
         let contracts = active_contracts::get(active_contracts::Params {
             ledger_host: ledger_host.clone(),
             party: party.clone(),
-            access_token: login_response.access_token.clone(),
+            access_token: access_token.clone(),
         })
         .await
         .unwrap();
@@ -250,33 +396,102 @@ mod tests {
 
         let split_params = Params {
             party,
-            amounts: vec![
-                common::decimal::DamlDecimal::parse("1.0").unwrap(),
-                common::decimal::DamlDecimal::parse("2.0").unwrap(),
-                common::decimal::DamlDecimal::parse("0.5").unwrap(),
-            ], // Split into 1.0, 2.0, 0.5, and change
+            // One output per input holding, at most three, and change
+            amounts: split_equal(
+                contracts.len().min(3),
+                common::decimal::DamlDecimal::parse("0.1").unwrap(),
+            ),
             instrument_id: common::transfer::InstrumentId {
                 admin: decentralized_party.clone(),
                 id: "CBTC".to_string(),
             },
             input_holding_cids,
             ledger_host,
-            access_token: login_response.access_token,
-            registry_url: env::var("REGISTRY_URL").expect("REGISTRY_URL must be set"),
+            access_token,
+            registry_url: env.registry_url(),
             decentralized_party_id: decentralized_party,
         };
 
         let result = submit(split_params).await.unwrap();
 
-        assert!(!result.output_holding_cids.is_empty());
+        assert_eq!(result.output_holding_cids.len(), contracts.len().min(3));
         assert!(!result.change_holding_cids.is_empty());
     }
+
+    fn d(s: &str) -> DamlDecimal {
+        DamlDecimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn split_equal_repeats_amount() {
+        assert_eq!(split_equal(3, d("0.1")), vec![d("0.1"), d("0.1"), d("0.1")]);
+        assert!(split_equal(0, d("0.1")).is_empty());
+    }
+
+    #[test]
+    fn split_into_denominations_flattens_in_order() {
+        let amounts = split_into_denominations(&[(d("1"), 2), (d("0.5"), 1)]);
+        assert_eq!(amounts, vec![d("1"), d("1"), d("0.5")]);
+    }
+
+    fn holding(cid: &str, amount: &str) -> Holding {
+        Holding {
+            contract_id: cid.to_string(),
+            amount: d(amount),
+            instrument_id: "CBTC".to_string(),
+            owner: "party::1220".to_string(),
+        }
+    }
+
+    #[test]
+    fn each_amount_takes_the_smallest_covering_holding() {
+        let (inputs, unused) = assign_inputs(
+            &[d("0.5"), d("2")],
+            vec![
+                holding("00big", "5"),
+                holding("00half", "0.5"),
+                holding("00spare", "0.1"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            inputs,
+            vec![vec!["00half".to_string()], vec!["00big".to_string()]]
+        );
+        assert_eq!(unused, vec!["00spare".to_string()]);
+    }
+
+    #[test]
+    fn amount_no_holding_covers_merges_the_largest() {
+        let (inputs, unused) = assign_inputs(
+            &[d("2"), d("1.5")],
+            vec![
+                holding("00a", "1"),
+                holding("00b", "1"),
+                holding("00c", "1.5"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(inputs[0], vec!["00b".to_string(), "00a".to_string()]);
+        assert_eq!(inputs[1], vec!["00c".to_string()]);
+        assert!(unused.is_empty());
+    }
+
+    #[test]
+    fn more_outputs_than_one_transaction_can_fund_are_rejected() {
+        let err =
+            assign_inputs(&[d("1"), d("1"), d("1")], vec![holding("00only", "5")]).unwrap_err();
+        assert!(
+            err.contains("in one transaction"),
+            "unexpected error: {err}"
+        );
+    }
 }
 
 #[cfg(test)]
 mod parser_tests {
     //! Pure-data fixture tests for the flat-event parser used by
-    //! `split_once` (`parse_split_response`).
+    //! `submit_self_transfers` (`parse_split_response`).
 
     use super::*;
     use crate::utils::test_fixtures::{
@@ -309,9 +524,11 @@ mod parser_tests {
             )]),
         );
 
-        let (output_cid, change_cids) = parse_split_response(&response).unwrap();
+        let splits = parse_split_response(&response).unwrap();
+        assert_eq!(splits.len(), 1);
+        let (output_cid, change_cids) = &splits[0];
         assert_eq!(output_cid, "00output-cid");
-        assert_eq!(change_cids, vec!["00change-1", "00change-2"]);
+        assert_eq!(change_cids, &vec!["00change-1", "00change-2"]);
     }
 
     #[test]
@@ -319,11 +536,7 @@ mod parser_tests {
         // Only a CreatedEvent — parser cannot find an ExercisedEvent.
         let response = transaction_response(
             "tx-x",
-            json!([created_event_value(
-                "pkg:Some:Template",
-                "00x",
-                json!(null),
-            )]),
+            json!([created_event_value("pkg:Some:Template", "00x", json!(null),)]),
         );
 
         let err = parse_split_response(&response).unwrap_err();
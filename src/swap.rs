@@ -0,0 +1,819 @@
+//! CBTC-versus-other-instrument swaps through the settlement app
+//! (`utility-settlement-app-v1`).
+//!
+//! "Party A gives X CBTC, party B gives Y of instrument Z" is a delivery
+//! versus payment (`Dvp`) with A as seller and B as buyer:
+//!
+//! 1. [`propose`]: one side proposes the Dvp via `UserService_ProposeDvp`.
+//! 2. [`accept_proposal`]: the other side accepts it, creating the `Dvp`
+//!    (signed by the app operator and both parties). It can instead
+//!    [`reject_proposal`], or the proposer can [`cancel_proposal`].
+//! 3. Each party allocates its leg via [`allocation::allocate`].
+//! 4. [`settle`]: the operator executes both allocations with `Dvp_Settle`.
+//!
+//! The operator controls `Dvp_Settle` and the `Dvp` carries both parties'
+//! authority, so the allocations' `Allocation_ExecuteTransfer` (controlled by
+//! executor, sender and receiver) is authorized without further signatures.
+//! [`build_spec`] derives the settlement spec the allocations must match, and
+//! [`submit`] runs all four steps.
+
+use crate::allocation::{self, Leg, Settlement};
+use crate::mint_redeem::constants::CBTC_INSTRUMENT_ID;
+use crate::settlement::SettlementSpec;
+use common::decimal::DamlDecimal;
+use ledger::active_contracts;
+use ledger::common::{TemplateFilter, TemplateFilterValue, TemplateIdentifierFilter};
+use ledger::ledger_end;
+use ledger::models::{JsActiveContract, JsSubmitAndWaitForTransactionResponse};
+use registry::allocation_context::AllocationChoice;
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+// Template IDs for settlement app contracts
+const USER_SERVICE_TEMPLATE_ID: &str =
+    "#utility-settlement-app-v1:Utility.Settlement.App.V1.Service.User:UserService";
+const DVP_TEMPLATE_ID: &str = "#utility-settlement-app-v1:Utility.Settlement.App.V1.Model.Dvp:Dvp";
+
+/// Leg ID of the CBTC leg (party A to party B). The `Dvp` numbers its legs
+/// from 1, deliveries before payments.
+pub const CBTC_LEG_ID: &str = "1";
+
+/// Leg ID of the counter leg (party B to party A)
+pub const COUNTER_LEG_ID: &str = "2";
+
+/// Default time the parties have to allocate their legs
+pub const DEFAULT_ALLOCATE_WITHIN: chrono::Duration = chrono::Duration::hours(1);
+
+/// Default time the operator has to settle
+pub const DEFAULT_SETTLE_WITHIN: chrono::Duration = chrono::Duration::hours(2);
+
+/// One side of a swap: who gives, and the token they give.
+pub struct SwapSide {
+    pub party: String,
+    /// Access token for `party`, used for its proposal step and allocation
+    pub access_token: String,
+    pub amount: DamlDecimal,
+    /// Holdings to fund the allocation. If empty, the party's holdings are
+    /// auto-selected; pass them explicitly if it holds other instruments.
+    pub input_holding_cids: Vec<String>,
+}
+
+/// The non-CBTC instrument of a swap and the registry that administers it.
+pub struct CounterInstrument {
+    pub admin: String,
+    pub id: String,
+    pub registry_url: String,
+}
+
+/// Terms of a swap: who gives what, who executes, and the deadlines.
+pub struct SwapTerms {
+    /// Party A (the Dvp seller), giving `cbtc_amount` CBTC
+    pub cbtc_party: String,
+    pub cbtc_amount: DamlDecimal,
+    /// Party B (the Dvp buyer), giving `counter_amount` of `counter_instrument_*`
+    pub counter_party: String,
+    pub counter_amount: DamlDecimal,
+    pub counter_instrument_admin: String,
+    pub counter_instrument_id: String,
+    /// Settlement app operator, which executes the Dvp
+    pub executor: String,
+    /// CBTC instrument admin (the decentralized party)
+    pub decentralized_party_id: String,
+    /// Settlement reference (the Dvp id). If `None`, a fresh UUID is used.
+    pub settlement_ref: Option<String>,
+    pub allocate_within: chrono::Duration,
+    pub settle_within: chrono::Duration,
+}
+
+/// Parameters for running a swap end to end.
+pub struct Params {
+    /// Party A's side (gives CBTC, proposes the Dvp)
+    pub cbtc_side: SwapSide,
+    /// Party B's side (gives `counter_instrument`, accepts the Dvp)
+    pub counter_side: SwapSide,
+    pub counter_instrument: CounterInstrument,
+    /// Access token for the settlement app operator, which runs `Dvp_Settle`
+    pub executor_access_token: String,
+    /// Settlement reference (the Dvp id). If `None`, a fresh UUID is used.
+    pub settlement_ref: Option<String>,
+    pub allocate_within: chrono::Duration,
+    pub settle_within: chrono::Duration,
+    pub ledger_host: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+}
+
+/// Result of [`submit`].
+#[derive(Debug, Clone)]
+pub struct SwapResult {
+    /// The settlement spec both legs were allocated against
+    pub spec: SettlementSpec,
+    /// The settled `Dvp` contract ID
+    pub dvp_cid: String,
+    /// The executed allocations, in leg order
+    pub allocation_cids: Vec<String>,
+}
+
+/// Parameters for proposing a swap as one of its parties.
+pub struct ProposeParams {
+    /// The swap, as built by [`build_spec`]
+    pub spec: SettlementSpec,
+    /// The proposing party: the sender of either leg
+    pub party: String,
+    pub ledger_host: String,
+    pub access_token: String,
+}
+
+/// Parameters for accepting, rejecting or cancelling a Dvp proposal.
+pub struct ProposalParams {
+    /// The counterparty to accept or reject, the proposer to cancel
+    pub party: String,
+    pub proposal_cid: String,
+    pub ledger_host: String,
+    pub access_token: String,
+}
+
+/// Parameters for settling an accepted swap as the settlement app operator.
+pub struct SettleParams {
+    pub spec: SettlementSpec,
+    pub dvp_cid: String,
+    pub ledger_host: String,
+    /// Access token for the operator (`spec.settlement.executor`)
+    pub access_token: String,
+    pub registry_url: String,
+    pub decentralized_party_id: String,
+    /// Registry administering the counter leg's instrument
+    pub counter_registry_url: String,
+}
+
+/// Format a time the way the Dvp stores it: whole seconds, so the allocations'
+/// settlement times compare equal to the Dvp's.
+fn daml_time(t: chrono::DateTime<chrono::Utc>) -> String {
+    t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Build the two-leg settlement spec for a swap, with deadlines measured from `now`.
+///
+/// Legs, reference and deadlines are the ones the settlement app derives from
+/// the resulting `Dvp`, so allocations made against them match its request.
+///
+/// # Errors
+///
+/// Returns an error string if an amount is not positive, the parties are the
+/// same, or `settle_within` is not longer than `allocate_within`.
+pub fn build_spec(
+    terms: &SwapTerms,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<SettlementSpec, String> {
+    if terms.cbtc_amount <= DamlDecimal::ZERO || terms.counter_amount <= DamlDecimal::ZERO {
+        return Err("Swap amounts must be positive".to_string());
+    }
+    if terms.cbtc_party == terms.counter_party {
+        return Err("Swap parties must differ".to_string());
+    }
+    if terms.settle_within <= terms.allocate_within {
+        return Err("settle_within must be longer than allocate_within".to_string());
+    }
+
+    let settlement_ref = terms
+        .settlement_ref
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    Ok(SettlementSpec {
+        settlement: Settlement {
+            executor: terms.executor.clone(),
+            settlement_ref,
+            settlement_ref_cid: None,
+            requested_at: daml_time(now),
+            allocate_before: daml_time(now + terms.allocate_within),
+            settle_before: daml_time(now + terms.settle_within),
+            meta: common::allocation::Metadata::default(),
+        },
+        legs: vec![
+            Leg {
+                leg_id: CBTC_LEG_ID.to_string(),
+                sender: terms.cbtc_party.clone(),
+                receiver: terms.counter_party.clone(),
+                amount: terms.cbtc_amount,
+                instrument_admin: terms.decentralized_party_id.clone(),
+                instrument_id: CBTC_INSTRUMENT_ID.to_string(),
+                meta: common::allocation::Metadata::default(),
+            },
+            Leg {
+                leg_id: COUNTER_LEG_ID.to_string(),
+                sender: terms.counter_party.clone(),
+                receiver: terms.cbtc_party.clone(),
+                amount: terms.counter_amount,
+                instrument_admin: terms.counter_instrument_admin.clone(),
+                instrument_id: terms.counter_instrument_id.clone(),
+                meta: common::allocation::Metadata::default(),
+            },
+        ],
+    })
+}
+
+/// The `Terms` of the Dvp for a swap spec: the CBTC leg is the delivery, the
+/// counter leg the payment.
+fn dvp_terms(spec: &SettlementSpec) -> Value {
+    let quantity = |leg: &Leg| {
+        json!({
+            "instrument": { "admin": leg.instrument_admin, "id": leg.instrument_id },
+            "amount": leg.amount.to_string(),
+        })
+    };
+    json!({
+        "id": spec.settlement.settlement_ref,
+        "deliveries": [quantity(&spec.legs[0])],
+        "payments": [quantity(&spec.legs[1])],
+        "createdAt": spec.settlement.requested_at,
+        "allocateBefore": spec.settlement.allocate_before,
+        "settleBefore": spec.settlement.settle_before,
+    })
+}
+
+/// The `UserService_ProposeDvp` argument for `party` proposing the swap in `spec`.
+fn propose_argument(spec: &SettlementSpec, party: &str) -> Result<Value, String> {
+    let (cbtc, counter) = (&spec.legs[0], &spec.legs[1]);
+    // The CBTC sender is the seller; the buyer pays with the counter leg.
+    let (proposer_is_buyer, counterparty) = if party == cbtc.sender {
+        (false, &counter.sender)
+    } else if party == counter.sender {
+        (true, &cbtc.sender)
+    } else {
+        return Err(format!(
+            "{} is not a party to swap {}",
+            party, spec.settlement.settlement_ref
+        ));
+    };
+    Ok(json!({
+        "proposerIsBuyer": proposer_is_buyer,
+        "counterparty": counterparty,
+        "terms": dvp_terms(spec),
+    }))
+}
+
+/// Active contracts of `template_id` visible to `party`.
+async fn active_by_template(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+    template_id: &str,
+) -> Result<Vec<JsActiveContract>, String> {
+    let ledger_end_response = ledger_end::get(ledger_end::Params {
+        access_token: access_token.to_string(),
+        ledger_host: ledger_host.to_string(),
+    })
+    .await?;
+
+    let filter =
+        ledger::common::IdentifierFilter::TemplateIdentifierFilter(TemplateIdentifierFilter {
+            template_filter: TemplateFilter {
+                value: TemplateFilterValue {
+                    template_id: Some(template_id.to_string()),
+                    include_created_event_blob: false,
+                },
+            },
+        });
+
+    active_contracts::get_by_party(active_contracts::Params {
+        ledger_host: ledger_host.to_string(),
+        party: party.to_string(),
+        filter,
+        access_token: access_token.to_string(),
+        ledger_end: ledger_end_response.offset,
+        unknown_contract_entry_handler: None,
+    })
+    .await
+}
+
+/// Find the settlement app `UserService` for `party`, returning its contract
+/// ID and operator.
+async fn find_user_service(
+    ledger_host: &str,
+    party: &str,
+    access_token: &str,
+) -> Result<(String, String), String> {
+    active_by_template(ledger_host, party, access_token, USER_SERVICE_TEMPLATE_ID)
+        .await?
+        .into_iter()
+        .find_map(|contract| {
+            let args = contract.created_event.create_argument.as_ref()?;
+            if args.get("user").and_then(|v| v.as_str()) != Some(party) {
+                return None;
+            }
+            let operator = args.get("operator").and_then(|v| v.as_str())?.to_string();
+            Some((contract.created_event.contract_id, operator))
+        })
+        .ok_or_else(|| {
+            format!(
+                "No settlement app UserService contract found for party {}",
+                party
+            )
+        })
+}
+
+/// Exercise `choice` on `contract_id` as `party` and return the parsed response.
+#[allow(clippy::too_many_arguments)]
+async fn exercise(
+    ledger_host: &str,
+    access_token: &str,
+    party: &str,
+    template_id: &str,
+    contract_id: &str,
+    choice: &str,
+    argument: Value,
+    disclosed_contracts: Vec<common::transfer::DisclosedContract>,
+) -> Result<JsSubmitAndWaitForTransactionResponse, String> {
+    let exercise_command = common::submission::ExerciseCommand {
+        exercise_command: common::submission::ExerciseCommandData {
+            template_id: template_id.to_string(),
+            contract_id: contract_id.to_string(),
+            choice: choice.to_string(),
+            choice_argument: common::submission::ChoiceArgumentsVariations::Generic(argument),
+        },
+    };
+
+    let submission_request = common::submission::Submission {
+        act_as: vec![party.to_string()],
+        read_as: None,
+        command_id: uuid::Uuid::new_v4().to_string(),
+        disclosed_contracts,
+        commands: vec![common::submission::Command::ExerciseCommand(
+            exercise_command,
+        )],
+        ..Default::default()
+    };
+
+    let response_raw = ledger::submit::wait_for_transaction(ledger::submit::Params {
+        ledger_host: ledger_host.to_string(),
+        access_token: access_token.to_string(),
+        request: submission_request,
+    })
+    .await?;
+    serde_json::from_str(&response_raw)
+        .map_err(|e| format!("Failed to parse submit response: {}", e))
+}
+
+/// Exercise a `UserService` choice as `party` on its own service.
+async fn exercise_user_service(
+    ledger_host: &str,
+    access_token: &str,
+    party: &str,
+    choice: &str,
+    argument: Value,
+) -> Result<JsSubmitAndWaitForTransactionResponse, String> {
+    let (user_service_cid, _) = find_user_service(ledger_host, party, access_token).await?;
+    exercise(
+        ledger_host,
+        access_token,
+        party,
+        USER_SERVICE_TEMPLATE_ID,
+        &user_service_cid,
+        choice,
+        argument,
+        vec![],
+    )
+    .await
+}
+
+/// The contract ID of the `template` (`Module:Entity`) created in `response`.
+fn created_cid(
+    response: &JsSubmitAndWaitForTransactionResponse,
+    template: &str,
+) -> Result<String, String> {
+    let suffix = format!(":Utility.Settlement.App.V1.Model.Dvp:{}", template);
+    response
+        .transaction
+        .events
+        .iter()
+        .filter_map(crate::event_helpers::as_created_event)
+        .find(|created| created.template_id.ends_with(&suffix))
+        .map(|created| created.contract_id.clone())
+        .ok_or_else(|| format!("No {} contract was created in the transaction", template))
+}
+
+/// Propose the swap in `params.spec` as either of its parties
+/// (`UserService_ProposeDvp`).
+///
+/// # Errors
+///
+/// Returns an error string if `party` is not a leg sender, has no settlement
+/// app `UserService`, or the submission fails. Returns the `DvpProposal` contract ID.
+pub async fn propose(params: ProposeParams) -> Result<String, String> {
+    let argument = propose_argument(&params.spec, &params.party)?;
+    let response = exercise_user_service(
+        &params.ledger_host,
+        &params.access_token,
+        &params.party,
+        "UserService_ProposeDvp",
+        argument,
+    )
+    .await?;
+    created_cid(&response, "DvpProposal")
+}
+
+/// Accept a Dvp proposal as its counterparty (`UserService_AcceptDvpProposal`).
+///
+/// # Errors
+///
+/// Returns an error string if the submission fails, e.g. after
+/// `allocate_before`. Returns the created `Dvp` contract ID.
+pub async fn accept_proposal(params: ProposalParams) -> Result<String, String> {
+    let response = exercise_user_service(
+        &params.ledger_host,
+        &params.access_token,
+        &params.party,
+        "UserService_AcceptDvpProposal",
+        json!({ "cid": params.proposal_cid, "payload": {} }),
+    )
+    .await?;
+    created_cid(&response, "Dvp")
+}
+
+/// Reject a Dvp proposal as its counterparty (`UserService_RejectDvpProposal`).
+///
+/// # Errors
+///
+/// Returns an error string if the submission fails.
+pub async fn reject_proposal(params: ProposalParams, reason: &str) -> Result<(), String> {
+    exercise_user_service(
+        &params.ledger_host,
+        &params.access_token,
+        &params.party,
+        "UserService_RejectDvpProposal",
+        json!({ "cid": params.proposal_cid, "payload": { "reason": reason } }),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Cancel a Dvp proposal as its proposer (`UserService_CancelDvpProposal`).
+///
+/// # Errors
+///
+/// Returns an error string if the submission fails.
+pub async fn cancel_proposal(params: ProposalParams) -> Result<(), String> {
+    exercise_user_service(
+        &params.ledger_host,
+        &params.access_token,
+        &params.party,
+        "UserService_CancelDvpProposal",
+        json!({ "cid": params.proposal_cid, "payload": {} }),
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Settle an accepted swap as the settlement app operator (`Dvp_Settle`).
+///
+/// Finds the allocation for each leg, fetches each one's execute-transfer
+/// context from its instrument's registry, and executes both in one
+/// transaction.
+///
+/// # Errors
+///
+/// Returns an error string if `settle_before` has passed, a leg has no
+/// allocation yet, or a registry request or the submission fails. Returns the
+/// executed allocation contract IDs in leg order.
+pub async fn settle(params: SettleParams) -> Result<Vec<String>, String> {
+    let settlement = &params.spec.settlement;
+    let settle_before = chrono::DateTime::parse_from_rfc3339(&settlement.settle_before)
+        .map_err(|e| format!("Invalid settleBefore '{}': {}", settlement.settle_before, e))?;
+    if chrono::Utc::now() >= settle_before {
+        return Err(format!(
+            "Swap {}: settleBefore {} has passed",
+            settlement.settlement_ref, settlement.settle_before
+        ));
+    }
+
+    let allocations = allocation::list_allocations(allocation::ListParams {
+        party: settlement.executor.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.access_token.clone(),
+    })
+    .await?;
+
+    let mut allocation_cids = Vec::with_capacity(params.spec.legs.len());
+    let mut extra_argss = Vec::with_capacity(params.spec.legs.len());
+    let mut disclosed_contracts = Vec::new();
+    let mut seen = HashSet::new();
+    for leg in &params.spec.legs {
+        let allocation_cid = allocations
+            .iter()
+            .find(|a| {
+                a.settlement.settlement_ref == settlement.settlement_ref
+                    && a.leg.leg_id == leg.leg_id
+            })
+            .map(|a| a.contract_id.clone())
+            .ok_or_else(|| {
+                format!(
+                    "Swap {}: leg {} is not allocated yet",
+                    settlement.settlement_ref, leg.leg_id
+                )
+            })?;
+        let registry_url = if leg.instrument_admin == params.decentralized_party_id {
+            &params.registry_url
+        } else {
+            &params.counter_registry_url
+        };
+        let (extra_args, disclosed) = allocation::extra_args_with_context(
+            AllocationChoice::ExecuteTransfer,
+            &allocation_cid,
+            registry_url,
+            &leg.instrument_admin,
+        )
+        .await?;
+        for contract in disclosed {
+            if seen.insert(contract.contract_id.clone()) {
+                disclosed_contracts.push(contract);
+            }
+        }
+        allocation_cids.push(allocation_cid);
+        extra_argss.push(extra_args);
+    }
+
+    exercise(
+        &params.ledger_host,
+        &params.access_token,
+        &settlement.executor,
+        DVP_TEMPLATE_ID,
+        &params.dvp_cid,
+        "Dvp_Settle",
+        json!({ "allocationCids": allocation_cids, "extraArgss": extra_argss }),
+        disclosed_contracts,
+    )
+    .await?;
+
+    Ok(allocation_cids)
+}
+
+/// Run a swap: party A proposes the Dvp and party B accepts it, both allocate
+/// their leg, then the settlement app operator settles both legs atomically.
+///
+/// If the counter leg fails to allocate, the CBTC allocation stays locked
+/// until party A withdraws it ([`allocation::withdraw`]).
+///
+/// # Errors
+///
+/// Returns an error string if the terms are invalid, the parties use
+/// different settlement app operators, or any step fails.
+pub async fn submit(params: Params) -> Result<SwapResult, String> {
+    let (_, executor) = find_user_service(
+        &params.ledger_host,
+        &params.cbtc_side.party,
+        &params.cbtc_side.access_token,
+    )
+    .await?;
+    let (_, counter_operator) = find_user_service(
+        &params.ledger_host,
+        &params.counter_side.party,
+        &params.counter_side.access_token,
+    )
+    .await?;
+    if counter_operator != executor {
+        return Err(format!(
+            "Swap parties use different settlement app operators: {} and {}",
+            executor, counter_operator
+        ));
+    }
+
+    let spec = build_spec(
+        &SwapTerms {
+            cbtc_party: params.cbtc_side.party.clone(),
+            cbtc_amount: params.cbtc_side.amount,
+            counter_party: params.counter_side.party.clone(),
+            counter_amount: params.counter_side.amount,
+            counter_instrument_admin: params.counter_instrument.admin.clone(),
+            counter_instrument_id: params.counter_instrument.id.clone(),
+            executor,
+            decentralized_party_id: params.decentralized_party_id.clone(),
+            settlement_ref: params.settlement_ref,
+            allocate_within: params.allocate_within,
+            settle_within: params.settle_within,
+        },
+        chrono::Utc::now(),
+    )?;
+    let settlement_ref = spec.settlement.settlement_ref.clone();
+
+    log::debug!(
+        "Swap {}: {} proposes {} CBTC for {} {}",
+        settlement_ref,
+        params.cbtc_side.party,
+        params.cbtc_side.amount,
+        params.counter_side.amount,
+        params.counter_instrument.id
+    );
+    let proposal_cid = propose(ProposeParams {
+        spec: spec.clone(),
+        party: params.cbtc_side.party.clone(),
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.cbtc_side.access_token.clone(),
+    })
+    .await?;
+    let dvp_cid = accept_proposal(ProposalParams {
+        party: params.counter_side.party.clone(),
+        proposal_cid,
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.counter_side.access_token.clone(),
+    })
+    .await?;
+
+    log::debug!(
+        "Swap {}: Dvp {} accepted, allocating",
+        settlement_ref,
+        dvp_cid
+    );
+    allocation::allocate(allocation::Params {
+        allocation: allocation::specification(&spec.settlement, &spec.legs[0]),
+        requested_at: spec.settlement.requested_at.clone(),
+        input_holding_cids: params.cbtc_side.input_holding_cids,
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.cbtc_side.access_token,
+        registry_url: params.registry_url.clone(),
+        decentralized_party_id: params.decentralized_party_id.clone(),
+    })
+    .await
+    .map_err(|e| format!("Swap {}: CBTC leg allocation failed: {}", settlement_ref, e))?;
+
+    allocation::allocate(allocation::Params {
+        allocation: allocation::specification(&spec.settlement, &spec.legs[1]),
+        requested_at: spec.settlement.requested_at.clone(),
+        input_holding_cids: params.counter_side.input_holding_cids,
+        ledger_host: params.ledger_host.clone(),
+        access_token: params.counter_side.access_token,
+        registry_url: params.counter_instrument.registry_url.clone(),
+        decentralized_party_id: params.counter_instrument.admin.clone(),
+    })
+    .await
+    .map_err(|e| {
+        format!(
+            "Swap {}: counter leg allocation failed (CBTC leg remains allocated): {}",
+            settlement_ref, e
+        )
+    })?;
+
+    let allocation_cids = settle(SettleParams {
+        spec: spec.clone(),
+        dvp_cid: dvp_cid.clone(),
+        ledger_host: params.ledger_host,
+        access_token: params.executor_access_token,
+        registry_url: params.registry_url,
+        decentralized_party_id: params.decentralized_party_id,
+        counter_registry_url: params.counter_instrument.registry_url,
+    })
+    .await?;
+
+    Ok(SwapResult {
+        spec,
+        dvp_cid,
+        allocation_cids,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_fixtures::{created_event_value, transaction_response};
+
+    fn terms() -> SwapTerms {
+        SwapTerms {
+            cbtc_party: "alice::1220".to_string(),
+            cbtc_amount: DamlDecimal::parse("0.5").unwrap(),
+            counter_party: "bob::1220".to_string(),
+            counter_amount: DamlDecimal::parse("50000").unwrap(),
+            counter_instrument_admin: "usdc-admin::1220".to_string(),
+            counter_instrument_id: "USDC".to_string(),
+            executor: "venue::1220".to_string(),
+            decentralized_party_id: "cbtc-network::1220".to_string(),
+            settlement_ref: Some("swap-1".to_string()),
+            allocate_within: DEFAULT_ALLOCATE_WITHIN,
+            settle_within: DEFAULT_SETTLE_WITHIN,
+        }
+    }
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00.123456Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    #[test]
+    fn spec_has_opposite_legs_and_deadlines() {
+        let spec = build_spec(&terms(), now()).unwrap();
+
+        assert_eq!(spec.settlement.settlement_ref, "swap-1");
+        assert_eq!(spec.settlement.executor, "venue::1220");
+        assert_eq!(spec.settlement.requested_at, "2026-01-01T00:00:00Z");
+        assert_eq!(spec.settlement.allocate_before, "2026-01-01T01:00:00Z");
+        assert_eq!(spec.settlement.settle_before, "2026-01-01T02:00:00Z");
+
+        let cbtc = &spec.legs[0];
+        assert_eq!(cbtc.leg_id, CBTC_LEG_ID);
+        assert_eq!(
+            (cbtc.sender.as_str(), cbtc.receiver.as_str()),
+            ("alice::1220", "bob::1220")
+        );
+        assert_eq!(cbtc.instrument_admin, "cbtc-network::1220");
+        assert_eq!(cbtc.instrument_id, CBTC_INSTRUMENT_ID);
+
+        let counter = &spec.legs[1];
+        assert_eq!(counter.leg_id, COUNTER_LEG_ID);
+        assert_eq!(
+            (counter.sender.as_str(), counter.receiver.as_str()),
+            ("bob::1220", "alice::1220")
+        );
+        assert_eq!(counter.instrument_id, "USDC");
+        assert_eq!(counter.amount, DamlDecimal::parse("50000").unwrap());
+    }
+
+    #[test]
+    fn generates_settlement_ref_when_missing() {
+        let mut terms = terms();
+        terms.settlement_ref = None;
+        let a = build_spec(&terms, now()).unwrap();
+        let b = build_spec(&terms, now()).unwrap();
+        assert!(!a.settlement.settlement_ref.is_empty());
+        assert_ne!(a.settlement.settlement_ref, b.settlement.settlement_ref);
+    }
+
+    #[test]
+    fn rejects_invalid_terms() {
+        let mut same_party = terms();
+        same_party.counter_party = "alice::1220".to_string();
+        assert!(build_spec(&same_party, now()).is_err());
+
+        let mut zero = terms();
+        zero.cbtc_amount = DamlDecimal::ZERO;
+        assert!(build_spec(&zero, now()).is_err());
+
+        let mut deadlines = terms();
+        deadlines.settle_within = deadlines.allocate_within;
+        assert!(build_spec(&deadlines, now()).is_err());
+    }
+
+    #[test]
+    fn dvp_terms_deliver_cbtc_against_payment() {
+        let spec = build_spec(&terms(), now()).unwrap();
+        let terms = dvp_terms(&spec);
+
+        assert_eq!(terms["id"], "swap-1");
+        assert_eq!(terms["deliveries"][0]["instrument"]["id"], "CBTC");
+        assert_eq!(
+            terms["deliveries"][0]["amount"],
+            spec.legs[0].amount.to_string()
+        );
+        assert_eq!(
+            terms["payments"][0]["instrument"]["admin"],
+            "usdc-admin::1220"
+        );
+        assert_eq!(
+            terms["payments"][0]["amount"],
+            spec.legs[1].amount.to_string()
+        );
+        assert_eq!(terms["createdAt"], spec.settlement.requested_at.as_str());
+        assert_eq!(
+            terms["settleBefore"],
+            spec.settlement.settle_before.as_str()
+        );
+    }
+
+    #[test]
+    fn either_side_can_propose() {
+        let spec = build_spec(&terms(), now()).unwrap();
+
+        let seller = propose_argument(&spec, "alice::1220").unwrap();
+        assert_eq!(seller["proposerIsBuyer"], false);
+        assert_eq!(seller["counterparty"], "bob::1220");
+
+        let buyer = propose_argument(&spec, "bob::1220").unwrap();
+        assert_eq!(buyer["proposerIsBuyer"], true);
+        assert_eq!(buyer["counterparty"], "alice::1220");
+
+        assert!(propose_argument(&spec, "eve::1220").is_err());
+    }
+
+    #[test]
+    fn finds_created_dvp_not_proposal() {
+        let response = transaction_response(
+            "tx-1",
+            json!([
+                created_event_value(
+                    "pkg:Utility.Settlement.App.V1.Model.Dvp:DvpProposal",
+                    "00proposal",
+                    json!({}),
+                ),
+                created_event_value(
+                    "pkg:Utility.Settlement.App.V1.Model.Dvp:Dvp",
+                    "00dvp",
+                    json!({}),
+                ),
+            ]),
+        );
+        assert_eq!(created_cid(&response, "Dvp").unwrap(), "00dvp");
+        assert_eq!(created_cid(&response, "DvpProposal").unwrap(), "00proposal");
+
+        let empty = transaction_response("tx-2", json!([]));
+        assert!(created_cid(&empty, "Dvp").is_err());
+    }
+}
@@ -665,36 +665,18 @@ fn generate_unique_reference(reference_base: &str, sender: &str, receiver: &str)
 #[cfg(test)]
 mod tests {
     use super::*;
-    use keycloak::login::{PasswordParams, password, password_url};
-    use std::env;
+    use crate::utils::test_env::TestEnv;
     use std::ops::Add;
 
     #[tokio::test]
     async fn test_submit() {
-        // Load environment variables from .env file
-        dotenvy::dotenv().ok();
-
-        let params = PasswordParams {
-            client_id: env::var("KEYCLOAK_CLIENT_ID").expect("KEYCLOAK_CLIENT_ID must be set"),
-            username: env::var("KEYCLOAK_USERNAME").expect("KEYCLOAK_USERNAME must be set"),
-            password: env::var("KEYCLOAK_PASSWORD").expect("KEYCLOAK_PASSWORD must be set"),
-            url: password_url(
-                &env::var("KEYCLOAK_HOST").expect("KEYCLOAK_HOST must be set"),
-                &env::var("KEYCLOAK_REALM").expect("KEYCLOAK_REALM must be set"),
-            ),
-        };
-        let login_response = password(params).await.unwrap();
-
-        let sender_party = env::var("PARTY_ID").expect("PARTY_ID must be set");
-        let receiver_party =
-            env::var("LIB_TEST_RECEIVER_PARTY_ID").expect("LIB_TEST_RECEIVER_PARTY_ID must be set");
-        let decentralized_party =
-            env::var("DECENTRALIZED_PARTY_ID").expect("DECENTRALIZED_PARTY_ID must be set");
+        let env = TestEnv::load().await;
+        let decentralized_party = env.decentralized_party_id();
 
         let params = Params {
             transfer: common::transfer::Transfer {
-                sender: sender_party,
-                receiver: receiver_party,
+                sender: env.party(),
+                receiver: env.receiver(),
                 amount: common::decimal::DamlDecimal::parse("0.02").unwrap(),
                 instrument_id: common::transfer::InstrumentId {
                     admin: decentralized_party.clone(),
@@ -707,9 +689,9 @@ mod tests {
                 input_holding_cids: None,
                 meta: None,
             },
-            ledger_host: env::var("LEDGER_HOST").expect("LEDGER_HOST must be set"),
-            access_token: login_response.access_token,
-            registry_url: env::var("REGISTRY_URL").expect("REGISTRY_URL must be set"),
+            ledger_host: env.ledger_host(),
+            access_token: env.access_token().await,
+            registry_url: env.registry_url(),
             decentralized_party_id: decentralized_party,
         };
 
@@ -16,6 +16,290 @@ pub fn extract_amount(contract: &ledger::models::JsActiveContract) -> Option<Dam
     None
 }
 
+/// Extract `(amount, sender)` from a TransferInstruction's create argument.
+pub(crate) fn offer_details(
+    contract: &ledger::models::JsActiveContract,
+) -> (Option<String>, Option<String>) {
+    let transfer = contract
+        .created_event
+        .create_argument
+        .as_ref()
+        .and_then(|arg| arg.get("transfer"));
+    let field = |key: &str| {
+        transfer
+            .and_then(|t| t.get(key))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    };
+    (field("amount"), field("sender"))
+}
+
+/// Transfer metadata key carrying the sender-supplied reference ID
+pub const REFERENCE_META_KEY: &str = "splice.lfdecentralizedtrust.org/reference";
+
+/// Metadata key carrying a human-readable reason (e.g. why an offer was rejected)
+pub const REASON_META_KEY: &str = "splice.lfdecentralizedtrust.org/reason";
+
+/// Criteria for selecting pending transfer offers.
+///
+/// Every field that is set must match; the default filter matches all offers.
+/// Offer age is measured from the transfer's `requestedAt`.
+#[derive(Debug, Clone, Default)]
+pub struct OfferFilter {
+    /// Only offers sent by this party
+    pub sender: Option<String>,
+    /// Only offers of at least this amount
+    pub min_amount: Option<DamlDecimal>,
+    /// Only offers of at most this amount
+    pub max_amount: Option<DamlDecimal>,
+    /// Only offers whose reference meta value equals this
+    pub reference: Option<String>,
+    /// Only offers requested at least this long ago
+    pub min_age: Option<chrono::Duration>,
+    /// Only offers requested at most this long ago
+    pub max_age: Option<chrono::Duration>,
+}
+
+impl OfferFilter {
+    /// Check whether a TransferInstruction contract matches this filter.
+    pub fn matches(&self, contract: &ledger::models::JsActiveContract) -> bool {
+        self.matches_at(contract, chrono::Utc::now())
+    }
+
+    fn matches_at(
+        &self,
+        contract: &ledger::models::JsActiveContract,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        let Some(transfer) = contract
+            .created_event
+            .create_argument
+            .as_ref()
+            .and_then(|arg| arg.get("transfer"))
+        else {
+            return false;
+        };
+
+        if let Some(expected) = &self.sender {
+            if transfer.get("sender").and_then(|v| v.as_str()) != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if self.min_amount.is_some() || self.max_amount.is_some() {
+            let Some(amount) = transfer
+                .get("amount")
+                .and_then(|v| v.as_str())
+                .and_then(|s| DamlDecimal::parse(s).ok())
+            else {
+                return false;
+            };
+            if self.min_amount.is_some_and(|min| amount < min) {
+                return false;
+            }
+            if self.max_amount.is_some_and(|max| amount > max) {
+                return false;
+            }
+        }
+
+        if let Some(expected) = &self.reference {
+            let reference = transfer
+                .get("meta")
+                .and_then(|meta| meta.get("values"))
+                .and_then(|values| values.get(REFERENCE_META_KEY))
+                .and_then(|v| v.as_str());
+            if reference != Some(expected.as_str()) {
+                return false;
+            }
+        }
+
+        if self.min_age.is_some() || self.max_age.is_some() {
+            let Some(requested_at) = transfer
+                .get("requestedAt")
+                .and_then(|v| v.as_str())
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            else {
+                return false;
+            };
+            let age = now - requested_at.with_timezone(&chrono::Utc);
+            if self.min_age.is_some_and(|min| age < min) {
+                return false;
+            }
+            if self.max_age.is_some_and(|max| age > max) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Why an accept, reject or withdraw of a single transfer offer failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfferErrorKind {
+    /// The offer's `executeBefore` deadline has passed
+    Expired,
+    /// The offer was already accepted, rejected or withdrawn (contract no longer active)
+    AlreadyArchived,
+    /// Any other failure (auth, network, validation, ...)
+    Other,
+}
+
+/// Assertion the registry's transfer choices fail with once `executeBefore`
+/// has passed
+const EXECUTE_BEFORE_ASSERTION: &str = "`executeBefore` must be in the future";
+
+/// Canton error codes for a contract that is no longer active
+const INACTIVE_CONTRACT_CODES: [&str; 3] = [
+    "CONTRACT_NOT_FOUND",
+    "CONTRACT_NOT_ACTIVE",
+    "LOCAL_VERDICT_INACTIVE_CONTRACTS",
+];
+
+impl OfferErrorKind {
+    /// Classify a ledger/registry error string for a single-offer submission.
+    ///
+    /// Uses the ledger's error code and the registry's `executeBefore`
+    /// assertion, so unrelated failures (an expired access token, say) are
+    /// [`OfferErrorKind::Other`].
+    pub fn classify(error: &str) -> Self {
+        if error.contains(EXECUTE_BEFORE_ASSERTION) {
+            return OfferErrorKind::Expired;
+        }
+        match ledger_error_code(error) {
+            Some(code) if INACTIVE_CONTRACT_CODES.contains(&code.as_str()) => {
+                OfferErrorKind::AlreadyArchived
+            }
+            _ => OfferErrorKind::Other,
+        }
+    }
+}
+
+/// The Canton error code in a ledger error: the `code` of a JSON API error
+/// body, or the `CODE(category,correlation)` prefix of a gRPC status message.
+fn ledger_error_code(error: &str) -> Option<String> {
+    let from_json = error.find('{').and_then(|start| {
+        serde_json::Deserializer::from_str(&error[start..])
+            .into_iter::<serde_json::Value>()
+            .next()?
+            .ok()?
+            .get("code")?
+            .as_str()
+            .map(str::to_string)
+    });
+    from_json.or_else(|| {
+        error.match_indices('(').find_map(|(index, _)| {
+            let code_start = error[..index]
+                .rfind(|c: char| !(c.is_ascii_uppercase() || c == '_'))
+                .map_or(0, |i| i + 1);
+            let code = &error[code_start..index];
+            let is_status = error[index + 1..].starts_with(|c: char| c.is_ascii_digit());
+            (code.len() > 1 && code.contains('_') && is_status).then(|| code.to_string())
+        })
+    })
+}
+
+/// Per-offer result of a batched accept, reject or withdraw.
+pub(crate) trait OfferOutcome: Clone {
+    fn contract_id(&self) -> &str;
+    fn mark_success(&mut self);
+    fn mark_failed(&mut self, error: String);
+}
+
+/// Submit offers in batches of `batch_size`, marking each pending result
+/// successful or failed as its batch completes.
+///
+/// `submit` sends one transaction for the given contract ids. A Canton
+/// transaction is atomic, so one withdrawn or expired offer fails the whole
+/// batch; a failed batch is retried per offer so the others still go through
+/// and only the offender(s) fail with their own error.
+///
+/// Returns the results with the successful and failed counts.
+pub(crate) async fn submit_offer_batches<T, F, Fut>(
+    pending: Vec<T>,
+    batch_size: usize,
+    submit: F,
+) -> (Vec<T>, usize, usize)
+where
+    T: OfferOutcome,
+    F: Fn(Vec<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<(), String>>,
+{
+    let batch_size = batch_size.max(1);
+    let num_batches = pending.len().div_ceil(batch_size);
+    let mut results = Vec::with_capacity(pending.len());
+    let mut successful_count = 0;
+    let mut failed_count = 0;
+    let mut record = |mut result: T, outcome: Result<(), String>| {
+        match outcome {
+            Ok(()) => {
+                successful_count += 1;
+                result.mark_success();
+            }
+            Err(e) => {
+                failed_count += 1;
+                result.mark_failed(e);
+            }
+        }
+        results.push(result);
+    };
+
+    for (batch_idx, batch) in pending.chunks(batch_size).enumerate() {
+        log::debug!(
+            "Submitting batch {}/{} ({} offer(s))...",
+            batch_idx + 1,
+            num_batches,
+            batch.len()
+        );
+
+        let contract_ids = batch.iter().map(|r| r.contract_id().to_string()).collect();
+        match submit(contract_ids).await {
+            Ok(()) => {
+                log::debug!("  ✓ Batch {}/{} successful", batch_idx + 1, num_batches);
+                for result in batch {
+                    record(result.clone(), Ok(()));
+                }
+            }
+            Err(e) if batch.len() > 1 => {
+                log::debug!(
+                    "  ✗ Batch {}/{} failed, retrying per offer: {}",
+                    batch_idx + 1,
+                    num_batches,
+                    e
+                );
+                for result in batch {
+                    let single = submit(vec![result.contract_id().to_string()]).await;
+                    record(result.clone(), single);
+                }
+            }
+            Err(e) => {
+                log::debug!("  ✗ Batch {}/{} failed: {}", batch_idx + 1, num_batches, e);
+                record(batch[0].clone(), Err(e));
+            }
+        }
+    }
+
+    (results, successful_count, failed_count)
+}
+
+/// Request timeout for [`http_client`]
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Connect timeout for [`http_client`]
+const HTTP_CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// HTTP client with request and connect timeouts, shared across the crate
+pub(crate) fn http_client() -> &'static reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .connect_timeout(HTTP_CONNECT_TIMEOUT)
+            .build()
+            .expect("Failed to build HTTP client")
+    })
+}
+
 /// Fetch all pending CBTC TransferInstruction contracts for a party where the party is the receiver
 pub async fn fetch_incoming_transfers(
     ledger_host: String,
@@ -151,6 +435,174 @@ async fn fetch_transfers(
     Ok(filtered)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_fixtures::active_contract;
+    use serde_json::json;
+
+    fn offer(
+        sender: &str,
+        amount: &str,
+        reference: Option<&str>,
+    ) -> ledger::models::JsActiveContract {
+        let mut meta = serde_json::Map::new();
+        if let Some(reference) = reference {
+            meta.insert(REFERENCE_META_KEY.to_string(), json!(reference));
+        }
+        active_contract(
+            "pkg:Splice.Api.Token.TransferInstructionV1:TransferInstruction",
+            "00offer",
+            json!({
+                "transfer": {
+                    "sender": sender,
+                    "receiver": "receiver::1220",
+                    "amount": amount,
+                    "instrumentId": { "admin": "admin", "id": "CBTC" },
+                    "requestedAt": "2026-01-01T00:00:00Z",
+                    "executeBefore": "2026-01-08T00:00:00Z",
+                    "meta": { "values": meta }
+                }
+            }),
+        )
+    }
+
+    fn at(s: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(s)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    #[test]
+    fn default_filter_matches_everything() {
+        assert!(OfferFilter::default().matches(&offer("alice::1220", "1.0", None)));
+    }
+
+    #[test]
+    fn filter_by_sender() {
+        let filter = OfferFilter {
+            sender: Some("alice::1220".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&offer("alice::1220", "1.0", None)));
+        assert!(!filter.matches(&offer("bob::1220", "1.0", None)));
+    }
+
+    #[test]
+    fn filter_by_amount_range() {
+        let filter = OfferFilter {
+            min_amount: Some(DamlDecimal::parse("0.5").unwrap()),
+            max_amount: Some(DamlDecimal::parse("2").unwrap()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&offer("alice::1220", "0.5", None)));
+        assert!(filter.matches(&offer("alice::1220", "2.0", None)));
+        assert!(!filter.matches(&offer("alice::1220", "0.1", None)));
+        assert!(!filter.matches(&offer("alice::1220", "3", None)));
+    }
+
+    #[test]
+    fn filter_by_reference() {
+        let filter = OfferFilter {
+            reference: Some("invoice-42".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&offer("alice::1220", "1.0", Some("invoice-42"))));
+        assert!(!filter.matches(&offer("alice::1220", "1.0", Some("invoice-43"))));
+        assert!(!filter.matches(&offer("alice::1220", "1.0", None)));
+    }
+
+    #[test]
+    fn filter_by_age() {
+        let filter = OfferFilter {
+            min_age: Some(chrono::Duration::hours(1)),
+            max_age: Some(chrono::Duration::days(2)),
+            ..Default::default()
+        };
+        let contract = offer("alice::1220", "1.0", None);
+        assert!(!filter.matches_at(&contract, at("2026-01-01T00:30:00Z")));
+        assert!(filter.matches_at(&contract, at("2026-01-01T12:00:00Z")));
+        assert!(!filter.matches_at(&contract, at("2026-01-04T00:00:00Z")));
+    }
+
+    #[test]
+    fn classify_offer_errors() {
+        assert_eq!(
+            OfferErrorKind::classify(
+                "DAML_FAILURE(9,1a2b): Interpretation error: Error: \
+                 Transfer `executeBefore` must be in the future"
+            ),
+            OfferErrorKind::Expired
+        );
+        assert_eq!(
+            OfferErrorKind::classify(
+                r#"Submit failed with status 404: {"code":"CONTRACT_NOT_FOUND","cause":"Contract could not be found with id 00ab"}"#
+            ),
+            OfferErrorKind::AlreadyArchived
+        );
+        assert_eq!(
+            OfferErrorKind::classify(
+                "Transaction failed: CONTRACT_NOT_FOUND(11,abc): Contract could not be found"
+            ),
+            OfferErrorKind::AlreadyArchived
+        );
+        assert_eq!(
+            OfferErrorKind::classify("LOCAL_VERDICT_INACTIVE_CONTRACTS(1,0): inactive"),
+            OfferErrorKind::AlreadyArchived
+        );
+        assert_eq!(
+            OfferErrorKind::classify("Registry request failed with status 500"),
+            OfferErrorKind::Other
+        );
+        // Auth failures and unrelated messages mentioning expiry or archiving
+        for error in [
+            "Keycloak login failed: token expired",
+            r#"Submit failed with status 401: {"code":"UNAUTHENTICATED","cause":"JWT expired"}"#,
+            "Settlement reference was archived by the venue",
+        ] {
+            assert_eq!(
+                OfferErrorKind::classify(error),
+                OfferErrorKind::Other,
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn filter_rejects_contract_without_transfer() {
+        let contract = active_contract("pkg:Some:Template", "00x", json!({}));
+        assert!(!OfferFilter::default().matches(&contract));
+    }
+
+    #[test]
+    fn offer_details_extracts_amount_and_sender() {
+        let contract = active_contract(
+            "pkg:Splice.Api.Token.TransferInstructionV1:TransferInstruction",
+            "00offer",
+            json!({
+                "transfer": {
+                    "sender": "alice::1220",
+                    "receiver": "bob::1220",
+                    "amount": "0.25"
+                }
+            }),
+        );
+
+        let (amount, sender) = offer_details(&contract);
+        assert_eq!(amount.as_deref(), Some("0.25"));
+        assert_eq!(sender.as_deref(), Some("alice::1220"));
+    }
+
+    #[test]
+    fn offer_details_missing_transfer_is_none() {
+        let contract = active_contract("pkg:Some:Template", "00x", json!({}));
+
+        let (amount, sender) = offer_details(&contract);
+        assert!(amount.is_none());
+        assert!(sender.is_none());
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod test_fixtures {
     //! Helpers for building typed `JsSubmitAndWaitForTransactionResponse`
@@ -206,11 +658,7 @@ pub(crate) mod test_fixtures {
     /// Build a flat-event `ExercisedEvent` as a JSON value with required
     /// structural fields filled in with placeholders. Pass `exercise_result`
     /// as `json!(null)` if the test doesn't care about it.
-    pub fn exercised_event_value(
-        template_id: &str,
-        choice: &str,
-        exercise_result: Value,
-    ) -> Value {
+    pub fn exercised_event_value(template_id: &str, choice: &str, exercise_result: Value) -> Value {
         json!({
             "ExercisedEvent": {
                 "offset": 1_i64,
@@ -230,6 +678,24 @@ pub(crate) mod test_fixtures {
         })
     }
 
+    /// Build a `JsActiveContract` (as returned by ACS queries) around a
+    /// `CreatedEvent` with the given template, contract id and create argument.
+    pub fn active_contract(
+        template_id: &str,
+        contract_id: &str,
+        create_argument: Value,
+    ) -> ledger::models::JsActiveContract {
+        let event = created_event_value(template_id, contract_id, create_argument);
+        let created_event: ledger::models::CreatedEvent =
+            serde_json::from_value(event["CreatedEvent"].clone())
+                .expect("test fixture is not a valid CreatedEvent");
+        ledger::models::JsActiveContract {
+            created_event: Box::new(created_event),
+            reassignment_counter: 0,
+            synchronizer_id: String::new(),
+        }
+    }
+
     /// Build a `JsSubmitAndWaitForTransactionResponse` from an updateId and
     /// an `events` value. Pass `json!(null)` to construct a response with an
     /// empty events list (the typed model now treats `events` as required, so
@@ -271,3 +737,132 @@ pub(crate) mod test_fixtures {
         response
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test_env {
+    //! Network settings for the tests that talk to a ledger. With
+    //! `LEDGER_HOST` set (usually from `.env`) they run against that network
+    //! and read the rest of their settings from the environment; otherwise
+    //! they run against a local [`MockServer`] seeded with holdings, a Minter
+    //! credential and a credential-app UserService for [`MOCK_PARTY`].
+    use crate::mint_redeem::bitcoin_address::BitcoinNetwork;
+    use crate::mock_server::MockServer;
+    use common::decimal::DamlDecimal;
+    use keycloak::login::{PasswordParams, password, password_url};
+    use std::env;
+
+    /// Party the tests act as against the mock
+    pub const MOCK_PARTY: &str = "lib-test::1220aaaa";
+
+    /// Party the tests send to against the mock
+    pub const MOCK_RECEIVER: &str = "lib-test-receiver::1220bbbb";
+
+    /// The network a test runs against. Keep it alive for the whole test;
+    /// dropping it stops the mock.
+    pub struct TestEnv {
+        /// The mock, when no network is configured. Tests seed it further
+        /// through [`MockServer::ledger`].
+        pub server: Option<MockServer>,
+    }
+
+    impl TestEnv {
+        pub async fn load() -> Self {
+            dotenvy::dotenv().ok();
+            if env::var("LEDGER_HOST").is_ok() {
+                return Self { server: None };
+            }
+
+            let server = MockServer::start().await.unwrap();
+            {
+                let mut ledger = server.ledger();
+                for amount in ["5.0", "0.5"] {
+                    ledger
+                        .create_holding(MOCK_PARTY, DamlDecimal::parse(amount).unwrap())
+                        .unwrap();
+                }
+                ledger.issue_credential(MOCK_PARTY, &[("hasCBTCRole", "Minter")]);
+                ledger.add_user_service(MOCK_PARTY);
+            }
+            Self {
+                server: Some(server),
+            }
+        }
+
+        pub fn ledger_host(&self) -> String {
+            self.setting("LEDGER_HOST", MockServer::ledger_host)
+        }
+
+        pub fn registry_url(&self) -> String {
+            self.setting("REGISTRY_URL", MockServer::registry_url)
+        }
+
+        pub fn decentralized_party_id(&self) -> String {
+            self.setting("DECENTRALIZED_PARTY_ID", MockServer::decentralized_party_id)
+        }
+
+        pub fn bitsafe_api_url(&self) -> String {
+            self.setting("BITSAFE_API_URL", MockServer::bitsafe_api_url)
+        }
+
+        /// `BITCOIN_NETWORK`, or the network implied by the Bitsafe API URL
+        pub fn bitcoin_network(&self) -> BitcoinNetwork {
+            let setting = match self.server {
+                Some(_) => None,
+                None => env::var("BITCOIN_NETWORK").ok(),
+            };
+            BitcoinNetwork::resolve(setting.as_deref(), &self.bitsafe_api_url())
+                .expect("Invalid Bitcoin network")
+        }
+
+        pub fn party(&self) -> String {
+            self.setting("PARTY_ID", |_| MOCK_PARTY.to_string())
+        }
+
+        pub fn receiver(&self) -> String {
+            self.setting("LIB_TEST_RECEIVER_PARTY_ID", |_| MOCK_RECEIVER.to_string())
+        }
+
+        pub fn keycloak_client_id(&self) -> String {
+            self.setting("KEYCLOAK_CLIENT_ID", |_| "cbtc".to_string())
+        }
+
+        pub fn keycloak_username(&self) -> String {
+            self.setting("KEYCLOAK_USERNAME", |_| "lib-test".to_string())
+        }
+
+        pub fn keycloak_password(&self) -> String {
+            self.setting("KEYCLOAK_PASSWORD", |_| "lib-test".to_string())
+        }
+
+        pub fn keycloak_url(&self) -> String {
+            match &self.server {
+                Some(server) => password_url(&server.url(), "mock"),
+                None => password_url(&var("KEYCLOAK_HOST"), &var("KEYCLOAK_REALM")),
+            }
+        }
+
+        /// Log in with the Keycloak settings
+        pub async fn access_token(&self) -> String {
+            password(PasswordParams {
+                client_id: self.keycloak_client_id(),
+                username: self.keycloak_username(),
+                password: self.keycloak_password(),
+                url: self.keycloak_url(),
+            })
+            .await
+            .unwrap()
+            .access_token
+        }
+
+        fn setting(&self, name: &str, mock: impl FnOnce(&MockServer) -> String) -> String {
+            match &self.server {
+                Some(server) => mock(server),
+                None => var(name),
+            }
+        }
+    }
+
+    fn var(name: &str) -> String {
+        env::var(name).unwrap_or_else(|_| panic!("{} must be set", name))
+    }
+}